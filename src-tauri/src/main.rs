@@ -1,18 +1,65 @@
 #![cfg_attr(target_os = "windows", windows_subsystem = "windows")]
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fs;
-use std::io::{BufRead, BufReader, Write};
-use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{mpsc, Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
 use std::thread;
 use std::time::{Duration, Instant, SystemTime};
 
+// Bumped whenever an action or event changes incompatibly; sent in `hello`.
+const REMOTE_PROTOCOL_VERSION: u32 = 1;
 const MAX_CONNECTIONS: usize = 50;
 const MAX_LINE_LENGTH: u64 = 10 * 1024 * 1024; // 10MB
+const MAX_READ_BYTES: u64 = 5 * 1024 * 1024;
+const DEFAULT_MAX_CONCURRENT_SCANS: usize = 2;
+const MAX_QUEUED_SCANS: usize = 64;
+const MAX_FINISHED_SCANS: usize = 16;
+// Finished scan trees are dropped after this long, or once the retained trees hold
+// more nodes than the budget below; their status stays queryable.
+const FINISHED_RESULT_TTL: Duration = Duration::from_secs(15 * 60);
+const MAX_RETAINED_RESULT_NODES: u64 = 2_000_000;
+// Server-assigned and scheduled scan ids use these prefixes, so clients may not pick them.
+const SERVER_SCAN_ID_PREFIX: &str = "scan-";
+const WATCH_SCAN_ID_PREFIX: &str = "watch:";
+const LAUNCH_MERGE_WINDOW: Duration = Duration::from_secs(2);
+// Archives with more entries than this are counted as plain files.
+const MAX_ARCHIVE_ENTRIES: usize = 100_000;
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const AUTH_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const AUTH_MAC_CONTEXT: &str = "dragabyte-auth-v1:";
+const AUTH_MAX_FAILURES: u32 = 5;
+const AUTH_FAILURE_WINDOW: Duration = Duration::from_secs(60);
+const AUTH_LOCKOUT: Duration = Duration::from_secs(60);
+const DOWNLOAD_CHUNK_SIZE: usize = 256 * 1024;
+const MAX_DOWNLOAD_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+// Unwritten download bytes allowed per connection before the sender waits.
+const DOWNLOAD_WINDOW_BYTES: u64 = 8 * 1024 * 1024;
+const DOWNLOAD_SCAN_BLOCK: usize = 64 * 1024;
+const MAX_FILE_OP_ITEMS: usize = 1000;
+const MAX_LIST_PAGE_SIZE: usize = 5000;
+const WS_ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const MAX_WS_HANDSHAKE_BYTES: usize = 8 * 1024;
+const MAX_HTTP_BODY_BYTES: usize = 1024 * 1024;
+const HTTP_RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+const SSE_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+const OPENAPI_SPEC: &str = include_str!("openapi.json");
+const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+const DEFAULT_ALERT_INTERVAL: Duration = Duration::from_secs(60);
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+const REMOTE_RECONNECT_MIN_DELAY: Duration = Duration::from_secs(1);
+const REMOTE_RECONNECT_MAX_DELAY: Duration = Duration::from_secs(60);
+const REMOTE_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const FLEET_SCAN_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+const MDNS_SERVICE_TYPE: &str = "_dragabyte._tcp.local.";
+const MDNS_BROWSE_TIMEOUT: Duration = Duration::from_secs(3);
+const MDNS_MAX_BROWSE_TIMEOUT: Duration = Duration::from_secs(30);
+// Tried in order for HTTPS webhooks when no CA file is configured.
 
 #[cfg(target_os = "windows")]
 use winreg::enums::*;
@@ -21,18 +68,22 @@ use winreg::RegKey;
 
 use base64::prelude::*;
 use jwalk::{Parallelism, WalkDir};
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
+use subtle::ConstantTimeEq;
 use tauri::Emitter;
 use tauri::Manager;
 use tauri_plugin_updater::UpdaterExt;
 use tauri_plugin_window_state::{StateFlags, WindowExt};
 
 struct StartupPath(Mutex<Option<String>>);
-struct LaunchContextState(Mutex<LaunchContext>);
-struct ScanCancellation(Mutex<HashMap<String, Arc<AtomicBool>>>);
-struct RemoteClientState(Mutex<Option<RemoteClientHandle>>);
+// The latest launch and when it arrived, so launches that arrive together are merged.
+struct LaunchContextState(Mutex<(LaunchContext, Instant)>);
+struct ScanCancellation(Mutex<HashMap<String, Arc<ScanControl>>>);
+// Outgoing connections keyed by `host:port`.
+struct RemoteClientState(Mutex<HashMap<String, RemoteClientHandle>>);
 struct SettingsState {
     path: PathBuf,
     value: Mutex<AppSettings>,
@@ -57,6 +108,16 @@ enum ScanEvent {
     Complete(ScanSummary),
     Error(String),
     Cancelled(String),
+    Paused(String),
+    Resumed(String),
+}
+
+// Shared between a running scan and whoever may cancel, pause or resume it.
+struct ScanControl {
+    cancelled: AtomicBool,
+    paused: AtomicBool,
+    pause_lock: Mutex<()>,
+    resumed: Condvar,
 }
 
 type ScanEmitter = Arc<dyn Fn(ScanEvent) + Send + Sync>;
@@ -71,6 +132,8 @@ struct ScanNode {
     dir_count: u64,
     files: Vec<ScanFile>,
     children: Vec<ScanNode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    archive: Option<&'static str>,
 }
 
 #[derive(Clone, Serialize)]
@@ -80,6 +143,9 @@ struct ScanFile {
     name: String,
     size_bytes: u64,
     modified: Option<u64>,
+    // Only set for archive entries, where `size_bytes` holds the compressed share.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uncompressed_bytes: Option<u64>,
 }
 
 #[derive(Clone, Serialize)]
@@ -118,6 +184,7 @@ enum ScanThrottleLevel {
     Low,
     Medium,
     High,
+    Adaptive,
 }
 
 #[derive(Deserialize)]
@@ -126,10 +193,12 @@ enum RemoteRequest {
     Ping {
         id: Option<String>,
     },
-    List {
+    Hello(RemoteHelloRequest),
+    Auth {
         id: Option<String>,
-        path: Option<String>,
+        mac: String,
     },
+    List(RemoteListRequest),
     Disk {
         id: Option<String>,
         path: String,
@@ -138,19 +207,187 @@ enum RemoteRequest {
         id: Option<String>,
         path: String,
     },
+    Download(RemoteDownloadRequest),
+    Delete {
+        id: Option<String>,
+        paths: Vec<String>,
+    },
+    Mkdir {
+        id: Option<String>,
+        paths: Vec<String>,
+    },
+    Rename {
+        id: Option<String>,
+        items: Vec<RemoteFileOpItem>,
+    },
+    Copy {
+        id: Option<String>,
+        items: Vec<RemoteFileOpItem>,
+    },
+    Move {
+        id: Option<String>,
+        paths: Vec<String>,
+        destination: String,
+    },
     Scan {
         id: Option<String>,
         path: String,
+        paths: Option<Vec<String>>,
         options: Option<ScanOptions>,
     },
     Cancel {
         id: Option<String>,
     },
+    Pause {
+        id: Option<String>,
+    },
+    Resume {
+        id: Option<String>,
+    },
+    Status {
+        id: Option<String>,
+    },
+    Result {
+        id: Option<String>,
+    },
+    Subscribe {
+        id: Option<String>,
+    },
+    Unsubscribe {
+        id: Option<String>,
+    },
+    Metrics {
+        id: Option<String>,
+    },
     Shutdown {
         id: Option<String>,
     },
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteHelloRequest {
+    id: Option<String>,
+    // The newest protocol version the client speaks.
+    protocol_version: Option<u32>,
+}
+
+// Every action the server understands and the scope it requires; `required_scope` and
+// `hello` both read this.
+const REMOTE_ACTIONS: [(&str, Option<RemoteScope>); 22] = [
+    ("ping", None),
+    ("hello", None),
+    ("auth", None),
+    ("list", Some(RemoteScope::List)),
+    ("disk", Some(RemoteScope::Disk)),
+    ("read", Some(RemoteScope::Read)),
+    ("download", Some(RemoteScope::Read)),
+    ("delete", Some(RemoteScope::Delete)),
+    ("mkdir", Some(RemoteScope::Write)),
+    ("rename", Some(RemoteScope::Write)),
+    ("copy", Some(RemoteScope::Write)),
+    ("move", Some(RemoteScope::Write)),
+    ("scan", Some(RemoteScope::Scan)),
+    ("cancel", Some(RemoteScope::Scan)),
+    ("pause", Some(RemoteScope::Scan)),
+    ("resume", Some(RemoteScope::Scan)),
+    ("status", Some(RemoteScope::Scan)),
+    ("result", Some(RemoteScope::Scan)),
+    ("subscribe", Some(RemoteScope::Scan)),
+    ("unsubscribe", Some(RemoteScope::Scan)),
+    ("metrics", Some(RemoteScope::Metrics)),
+    ("shutdown", Some(RemoteScope::Shutdown)),
+];
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteDownloadRequest {
+    id: Option<String>,
+    path: String,
+    // Resume point for an interrupted download.
+    #[serde(default)]
+    offset: u64,
+    length: Option<u64>,
+    chunk_size: Option<usize>,
+    // Preview modes that send only the first or last lines of the file.
+    head_lines: Option<usize>,
+    tail_lines: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteListRequest {
+    id: Option<String>,
+    path: Option<String>,
+    #[serde(default)]
+    include_files: bool,
+    // Adds size, modified time, permissions and symlink targets to each entry.
+    #[serde(default)]
+    details: bool,
+    #[serde(default)]
+    sort: RemoteListSort,
+    #[serde(default)]
+    descending: bool,
+    #[serde(default)]
+    offset: usize,
+    limit: Option<usize>,
+}
+
+impl RemoteListRequest {
+    // Sorting by size or age needs the metadata anyway.
+    fn wants_details(&self) -> bool {
+        self.details || matches!(self.sort, RemoteListSort::Size | RemoteListSort::Modified)
+    }
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum RemoteListSort {
+    #[default]
+    Name,
+    Size,
+    Modified,
+    // Folders first, then by extension.
+    Type,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteFileOpItem {
+    path: String,
+    new_path: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RemoteFileOpKind {
+    Delete,
+    Mkdir,
+    Rename,
+    Copy,
+    Move,
+}
+
+impl RemoteFileOpKind {
+    fn action(self) -> &'static str {
+        match self {
+            Self::Delete => "delete",
+            Self::Mkdir => "mkdir",
+            Self::Rename => "rename",
+            Self::Copy => "copy",
+            Self::Move => "move",
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteFileOpResult {
+    path: String,
+    new_path: Option<String>,
+    ok: bool,
+    error: Option<String>,
+}
+
 #[derive(Deserialize)]
 struct RemoteEnvelope {
     token: Option<String>,
@@ -160,8 +397,53 @@ struct RemoteEnvelope {
 
 #[derive(Clone)]
 struct TcpConfig {
-    bind_addr: SocketAddr,
+    // `None` when only the Unix socket was requested.
+    bind_addr: Option<SocketAddr>,
     token: Option<String>,
+    api_tokens: Vec<ApiTokenSettings>,
+    allowed_roots: Vec<String>,
+    audit_log: Option<PathBuf>,
+    file_ops: bool,
+    // Accept the token on every request line from clients without the handshake.
+    legacy_tokens: bool,
+    max_concurrent_scans: usize,
+    tls: Option<TlsServerPaths>,
+    ws_bind: Option<SocketAddr>,
+    // Browser origins allowed to open WebSocket connections.
+    ws_allowed_origins: Vec<String>,
+    http_bind: Option<SocketAddr>,
+    socket_path: Option<PathBuf>,
+    // Advertise the TCP listener on the local network as `_dragabyte._tcp`.
+    mdns: bool,
+    // Directories rescanned every `watch_interval` and reported by `metrics`.
+    watch_paths: Vec<PathBuf>,
+    watch_interval: Duration,
+    alerts: Option<AlertConfig>,
+}
+
+#[derive(Clone)]
+struct AlertConfig {
+    rules: Vec<AlertRuleSettings>,
+    webhook: Option<String>,
+    webhook_ca: Option<PathBuf>,
+    command: Option<String>,
+    interval: Duration,
+}
+
+#[derive(Clone)]
+enum RemoteTransport {
+    Ndjson,
+    // Carries the allowed browser origins.
+    WebSocket(Arc<Vec<String>>),
+    Http,
+}
+
+#[derive(Clone)]
+struct TlsServerPaths {
+    cert: PathBuf,
+    key: PathBuf,
+    // When set, clients must present a certificate signed by this CA.
+    client_ca: Option<PathBuf>,
 }
 
 struct RuntimeOptions {
@@ -178,6 +460,28 @@ struct AppSettings {
     tcp_bind: Option<String>,
     headless: Option<bool>,
     auto_update: Option<bool>,
+    max_concurrent_scans: Option<usize>,
+    tls_cert_path: Option<String>,
+    tls_key_path: Option<String>,
+    tls_client_ca_path: Option<String>,
+    api_tokens: Option<Vec<ApiTokenSettings>>,
+    allowed_roots: Option<Vec<String>>,
+    audit_log_path: Option<String>,
+    remote_file_ops: Option<bool>,
+    allow_legacy_tokens: Option<bool>,
+    ws_bind: Option<String>,
+    ws_allowed_origins: Option<Vec<String>>,
+    http_bind: Option<String>,
+    socket_path: Option<String>,
+    mdns: Option<bool>,
+    watch_paths: Option<Vec<String>>,
+    watch_interval_secs: Option<u64>,
+    alert_rules: Option<Vec<AlertRuleSettings>>,
+    alert_webhook: Option<String>,
+    alert_webhook_ca_path: Option<String>,
+    alert_command: Option<String>,
+    alert_interval_secs: Option<u64>,
+    remote_hosts: Option<Vec<RemoteHostProfile>>,
 }
 
 #[derive(Deserialize)]
@@ -187,1608 +491,7654 @@ struct AppSettingsUpdate {
     tcp_bind: Option<String>,
     headless: Option<bool>,
     auto_update: Option<bool>,
+    max_concurrent_scans: Option<usize>,
+    tls_cert_path: Option<String>,
+    tls_key_path: Option<String>,
+    tls_client_ca_path: Option<String>,
+    api_tokens: Option<Vec<ApiTokenSettings>>,
+    allowed_roots: Option<Vec<String>>,
+    audit_log_path: Option<String>,
+    remote_file_ops: Option<bool>,
+    allow_legacy_tokens: Option<bool>,
+    ws_bind: Option<String>,
+    ws_allowed_origins: Option<Vec<String>>,
+    http_bind: Option<String>,
+    socket_path: Option<String>,
+    mdns: Option<bool>,
+    watch_paths: Option<Vec<String>>,
+    watch_interval_secs: Option<u64>,
+    alert_rules: Option<Vec<AlertRuleSettings>>,
+    alert_webhook: Option<String>,
+    alert_webhook_ca_path: Option<String>,
+    alert_command: Option<String>,
+    alert_interval_secs: Option<u64>,
+    remote_hosts: Option<Vec<RemoteHostProfile>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+enum RemoteScope {
+    List,
+    Disk,
+    Read,
+    Scan,
+    Write,
+    Delete,
+    Metrics,
+    Shutdown,
+}
+
+const ALL_REMOTE_SCOPES: [RemoteScope; 8] = [
+    RemoteScope::List,
+    RemoteScope::Disk,
+    RemoteScope::Read,
+    RemoteScope::Scan,
+    RemoteScope::Write,
+    RemoteScope::Delete,
+    RemoteScope::Metrics,
+    RemoteScope::Shutdown,
+];
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiTokenSettings {
+    name: String,
+    token: String,
+    scopes: Vec<RemoteScope>,
+    // Empty means the token may touch any path.
+    #[serde(default)]
+    roots: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AlertRuleSettings {
+    name: String,
+    path: String,
+    // Fires while free space on the volume holding `path` is below this percentage.
+    min_free_percent: Option<f64>,
+    // Fires when `path` grew by more than this between two scheduled scans.
+    max_growth_bytes: Option<u64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteHostProfile {
+    name: String,
+    host: String,
+    port: u16,
+    // `env:NAME` or `file:PATH`; the token itself is never stored in settings.
+    token_ref: Option<String>,
+    tls_fingerprint: Option<String>,
+}
+
+struct RemoteToken {
+    digest: Vec<u8>,
+    // Raw secret, kept as the HMAC key for the connection handshake.
+    secret: Vec<u8>,
+    grant: RemoteGrant,
+}
+
+// What an authenticated request is allowed to do.
+#[derive(Clone)]
+struct RemoteGrant {
+    name: String,
+    scopes: HashSet<RemoteScope>,
+    // Canonical roots; `None` leaves paths unrestricted.
+    roots: Option<Vec<PathBuf>>,
 }
 
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct RemoteConnectPayload {
     host: String,
     port: u16,
     token: Option<String>,
+    // Used when `token` is empty, e.g. for saved host profiles.
+    #[serde(default)]
+    token_ref: Option<String>,
+    #[serde(default)]
+    tls: Option<RemoteTlsOptions>,
+    // Dropped connections are redialed with backoff unless this is false.
+    #[serde(default)]
+    reconnect: Option<bool>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct RemoteTlsOptions {
+    // PEM file with the CA (or self-signed certificate) used to verify the server.
+    ca_cert: Option<String>,
+    // SHA-256 of the server's DER certificate, hex encoded; colons are ignored.
+    pin_sha256: Option<String>,
+    server_name: Option<String>,
+    client_cert: Option<String>,
+    client_key: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct RemoteSendPayload {
     #[serde(default)]
     payload: JsonValue,
+    // Required once more than one server is connected.
+    #[serde(default)]
+    address: Option<String>,
 }
 
-struct RemoteHub {
-    clients: Mutex<Vec<mpsc::Sender<String>>>,
-    scan_cancel: Mutex<Option<Arc<AtomicBool>>>,
-    scan_active: AtomicBool,
-    token: Option<String>,
-    shutdown: Option<mpsc::Sender<()>>,
-}
-
-#[derive(Clone, Deserialize)]
+#[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct ScanFilters {
-    include_extensions: Vec<String>,
-    exclude_extensions: Vec<String>,
-    include_names: Vec<String>,
-    exclude_names: Vec<String>,
-    min_size_bytes: Option<u64>,
-    max_size_bytes: Option<u64>,
-    min_modified_timestamp: Option<u64>,
-    max_modified_timestamp: Option<u64>,
-    include_regex: Option<String>,
-    exclude_regex: Option<String>,
-    include_paths: Vec<String>,
-    exclude_paths: Vec<String>,
+struct RemoteRequestPayload {
+    #[serde(default)]
+    payload: JsonValue,
+    #[serde(default)]
+    address: Option<String>,
+    #[serde(default)]
+    timeout_ms: Option<u64>,
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct ScanOptions {
-    priority_mode: ScanPriorityMode,
-    throttle_level: ScanThrottleLevel,
-    filters: ScanFilters,
+struct RemoteFleetScanPayload {
+    // Names of saved `remoteHosts`; empty scans all of them.
+    #[serde(default)]
+    hosts: Vec<String>,
+    path: String,
+    #[serde(default)]
+    paths: Option<Vec<String>>,
+    // Passed through unchanged as the `scan` request's options.
+    #[serde(default)]
+    options: Option<JsonValue>,
+    #[serde(default)]
+    timeout_secs: Option<u64>,
 }
 
-impl Default for ScanPriorityMode {
-    fn default() -> Self {
-        ScanPriorityMode::Balanced
-    }
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteDiscoverPayload {
+    #[serde(default)]
+    timeout_ms: Option<u64>,
 }
 
-impl Default for ScanThrottleLevel {
-    fn default() -> Self {
-        ScanThrottleLevel::Off
-    }
+#[derive(Deserialize)]
+struct RemoteDisconnectPayload {
+    // `None` closes every connection.
+    #[serde(default)]
+    address: Option<String>,
 }
 
-impl Default for ScanFilters {
-    fn default() -> Self {
-        Self {
-            include_extensions: Vec::new(),
-            exclude_extensions: Vec::new(),
-            include_names: Vec::new(),
-            exclude_names: Vec::new(),
-            min_size_bytes: None,
-            max_size_bytes: None,
-            min_modified_timestamp: None,
-            max_modified_timestamp: None,
-            include_regex: None,
-            exclude_regex: None,
-            include_paths: Vec::new(),
-            exclude_paths: Vec::new(),
-        }
-    }
+struct RemoteHub {
+    tokens: Vec<RemoteToken>,
+    // Server-wide sandbox applied on top of each token's own roots.
+    allowed_roots: Option<Vec<PathBuf>>,
+    clients: Mutex<HashMap<u64, mpsc::Sender<String>>>,
+    next_client_id: AtomicU64,
+    // Clients that asked to observe every scan they may see, including ones started later.
+    watchers: Mutex<HashMap<u64, RemoteGrant>>,
+    scans: Mutex<RemoteScanQueue>,
+    max_concurrent_scans: usize,
+    auth_failures: AuthRateLimiter,
+    audit: Option<RemoteAuditLog>,
+    // Delete, rename, copy, move and mkdir are refused unless the server enables them.
+    file_ops: bool,
+    legacy_tokens: bool,
+    shutdown: Option<mpsc::Sender<()>>,
+    watch_paths: Vec<PathBuf>,
+    metrics: RemoteMetrics,
+    // Set once the accept loop has stopped, which ends the watch scheduler.
+    closed: AtomicBool,
 }
 
-impl Default for ScanOptions {
-    fn default() -> Self {
-        Self {
-            priority_mode: ScanPriorityMode::default(),
-            throttle_level: ScanThrottleLevel::default(),
-            filters: ScanFilters::default(),
-        }
-    }
+// Counters behind the `metrics` action, kept in memory since the server started.
+#[derive(Default)]
+struct RemoteMetrics {
+    // Keyed by action and outcome.
+    requests: Mutex<BTreeMap<(&'static str, &'static str), u64>>,
+    // Count and total seconds per final scan state.
+    scan_durations: Mutex<BTreeMap<&'static str, (u64, f64)>>,
+    watched: Mutex<BTreeMap<PathBuf, WatchedDirSample>>,
 }
 
-struct FilterConfig {
-    include_extensions: HashSet<String>,
-    exclude_extensions: HashSet<String>,
-    include_names: Vec<String>,
-    exclude_names: Vec<String>,
-    min_size_bytes: Option<u64>,
-    max_size_bytes: Option<u64>,
-    min_modified_timestamp: Option<u64>,
-    max_modified_timestamp: Option<u64>,
-    include_regex: Option<Regex>,
-    exclude_regex: Option<Regex>,
-    include_paths: Vec<String>,
-    exclude_paths: Vec<String>,
-    flags: FilterFlags,
+// Outcome of the last completed scheduled scan of a watched directory.
+struct WatchedDirSample {
+    total_bytes: u64,
+    // Size at the scan before, used by growth alerts.
+    previous_total_bytes: Option<u64>,
+    file_count: u64,
+    dir_count: u64,
+    duration_ms: u128,
+    finished_at: SystemTime,
 }
 
-struct FilterFlags {
-    has_includes: bool,
-    has_file_excludes: bool,
-    has_dir_excludes: bool,
-    needs_path: bool,
-    needs_name: bool,
-    needs_extension: bool,
+// Append-only JSON Lines record of remote requests and authentication attempts.
+struct RemoteAuditLog {
+    file: Mutex<fs::File>,
 }
 
-struct ThrottleConfig {
-    every_entries: u64,
-    sleep_ms: u64,
-}
+impl RemoteAuditLog {
+    fn open(path: &Path) -> Result<Self, String> {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|error| format!("Failed to open audit log {}: {error}", path.display()))?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
 
-struct ScanConfig {
-    filters: FilterConfig,
-    emit_every: u64,
-    emit_interval: Duration,
-    throttle: Option<ThrottleConfig>,
-    parallelism: Parallelism,
+    fn append(&self, entry: &JsonValue) {
+        // One write per entry so concurrent connections never interleave lines.
+        let line = format!("{entry}\n");
+        let Ok(mut file) = self.file.lock() else {
+            return;
+        };
+        if let Err(error) = file.write_all(line.as_bytes()) {
+            eprintln!("[remote] audit log write failed: {error}");
+        }
+    }
 }
 
-#[derive(Default)]
-struct NodeStats {
-    direct_bytes: u64,
-    direct_files: u64,
-    direct_dirs: u64,
+// What a request touched, captured before it is dispatched.
+struct RemoteAuditTarget {
+    action: &'static str,
+    id: Option<String>,
+    path: Option<String>,
+    paths: Option<Vec<String>>,
 }
 
-impl RemoteHub {
-    fn new(token: Option<String>, shutdown: Option<mpsc::Sender<()>>) -> Self {
-        Self {
-            clients: Mutex::new(Vec::new()),
-            scan_cancel: Mutex::new(None),
-            scan_active: AtomicBool::new(false),
-            token,
-            shutdown,
+impl RemoteRequest {
+    // The `action` name the request was sent with.
+    fn action(&self) -> &'static str {
+        match self {
+            RemoteRequest::Ping { .. } => "ping",
+            RemoteRequest::Hello(_) => "hello",
+            RemoteRequest::Auth { .. } => "auth",
+            RemoteRequest::List(_) => "list",
+            RemoteRequest::Disk { .. } => "disk",
+            RemoteRequest::Read { .. } => "read",
+            RemoteRequest::Download(_) => "download",
+            RemoteRequest::Delete { .. } => "delete",
+            RemoteRequest::Mkdir { .. } => "mkdir",
+            RemoteRequest::Rename { .. } => "rename",
+            RemoteRequest::Copy { .. } => "copy",
+            RemoteRequest::Move { .. } => "move",
+            RemoteRequest::Scan { .. } => "scan",
+            RemoteRequest::Cancel { .. } => "cancel",
+            RemoteRequest::Pause { .. } => "pause",
+            RemoteRequest::Resume { .. } => "resume",
+            RemoteRequest::Status { .. } => "status",
+            RemoteRequest::Result { .. } => "result",
+            RemoteRequest::Subscribe { .. } => "subscribe",
+            RemoteRequest::Unsubscribe { .. } => "unsubscribe",
+            RemoteRequest::Metrics { .. } => "metrics",
+            RemoteRequest::Shutdown { .. } => "shutdown",
         }
     }
+}
 
-    fn register_client(&self, sender: mpsc::Sender<String>) {
-        if let Ok(mut clients) = self.clients.lock() {
-            clients.push(sender);
+impl RemoteAuditTarget {
+    fn of(request: &RemoteRequest) -> Self {
+        let (path, paths) = match request {
+            RemoteRequest::List(request) => (request.path.clone(), None),
+            RemoteRequest::Disk { path, .. } | RemoteRequest::Read { path, .. } => {
+                (Some(path.clone()), None)
+            }
+            RemoteRequest::Download(request) => (Some(request.path.clone()), None),
+            RemoteRequest::Delete { paths, .. } | RemoteRequest::Mkdir { paths, .. } => {
+                (None, Some(paths.clone()))
+            }
+            RemoteRequest::Rename { items, .. } | RemoteRequest::Copy { items, .. } => {
+                (None, Some(file_op_sources(items)))
+            }
+            RemoteRequest::Move {
+                paths, destination, ..
+            } => (Some(destination.clone()), Some(paths.clone())),
+            RemoteRequest::Scan { path, paths, .. } => (Some(path.clone()), paths.clone()),
+            _ => (None, None),
+        };
+        Self {
+            action: request.action(),
+            id: request_id(request).map(str::to_string),
+            path,
+            paths,
         }
     }
 
-    fn broadcast(&self, message: String) {
-        if let Ok(mut clients) = self.clients.lock() {
-            clients.retain(|sender| sender.send(message.clone()).is_ok());
+    fn connection() -> Self {
+        Self {
+            action: "connect",
+            id: None,
+            path: None,
+            paths: None,
         }
     }
+}
 
-    fn start_scan(&self, cancel_flag: Arc<AtomicBool>) -> bool {
-        if self.scan_active.swap(true, Ordering::SeqCst) {
-            return false;
-        }
-        if let Ok(mut cancel) = self.scan_cancel.lock() {
-            *cancel = Some(cancel_flag);
-        }
-        true
-    }
+// Failed authentication attempts per peer address. Peers that fail too often inside
+// the window are refused outright until the lockout expires.
+#[derive(Default)]
+struct AuthRateLimiter {
+    peers: Mutex<HashMap<IpAddr, AuthFailures>>,
+}
 
-    fn cancel_scan(&self) -> bool {
-        if let Ok(cancel) = self.scan_cancel.lock() {
-            if let Some(flag) = cancel.as_ref() {
-                flag.store(true, Ordering::SeqCst);
-                return true;
-            }
-        }
-        false
-    }
+struct AuthFailures {
+    count: u32,
+    window_start: Instant,
+    blocked_until: Option<Instant>,
+}
 
-    fn finish_scan(&self) {
-        self.scan_active.store(false, Ordering::SeqCst);
-        if let Ok(mut cancel) = self.scan_cancel.lock() {
-            *cancel = None;
-        }
+impl AuthRateLimiter {
+    fn is_blocked(&self, peer: Option<SocketAddr>) -> bool {
+        let Some(ip) = peer.map(|address| address.ip()) else {
+            return false;
+        };
+        let Ok(peers) = self.peers.lock() else {
+            return false;
+        };
+        peers
+            .get(&ip)
+            .and_then(|entry| entry.blocked_until)
+            .is_some_and(|until| Instant::now() < until)
     }
 
-    fn validate_token(&self, token: Option<&str>) -> bool {
-        match self.token.as_deref() {
-            None => true,
-            Some(expected) => token == Some(expected),
+    fn record_failure(&self, peer: Option<SocketAddr>) {
+        let Some(ip) = peer.map(|address| address.ip()) else {
+            return;
+        };
+        let Ok(mut peers) = self.peers.lock() else {
+            return;
+        };
+        let now = Instant::now();
+        peers.retain(|_, entry| match entry.blocked_until {
+            Some(until) => now < until,
+            None => now.duration_since(entry.window_start) < AUTH_FAILURE_WINDOW,
+        });
+        let entry = peers.entry(ip).or_insert(AuthFailures {
+            count: 0,
+            window_start: now,
+            blocked_until: None,
+        });
+        entry.count += 1;
+        if entry.count >= AUTH_MAX_FAILURES {
+            eprintln!("[remote] blocking {ip} after {} failed logins", entry.count);
+            entry.blocked_until = Some(now + AUTH_LOCKOUT);
         }
     }
 
-    fn request_shutdown(&self) -> bool {
-        match &self.shutdown {
-            Some(sender) => sender.send(()).is_ok(),
-            None => false,
+    fn record_success(&self, peer: Option<SocketAddr>) {
+        if let (Some(address), Ok(mut peers)) = (peer, self.peers.lock()) {
+            peers.remove(&address.ip());
         }
     }
 }
 
-fn emit_to_window(window: &tauri::Window, event: ScanEvent) {
-    match event {
-        ScanEvent::Progress(summary) => {
-            let _ = window.emit("scan-progress", summary);
-        }
-        ScanEvent::Complete(summary) => {
-            let _ = window.emit("scan-complete", summary);
-        }
-        ScanEvent::Error(message) => {
-            let _ = window.emit("scan-error", message);
-        }
-        ScanEvent::Cancelled(message) => {
-            let _ = window.emit("scan-cancelled", message);
-        }
-    }
+#[derive(Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum RemoteScanState {
+    Queued,
+    Running,
+    Complete,
+    Cancelled,
+    Failed,
 }
 
-fn emit_to_remote(hub: &RemoteHub, event: ScanEvent, request_id: Option<&str>) {
-    let payload = match event {
-        ScanEvent::Progress(summary) => serde_json::json!({
-          "event": "scan-progress",
-          "id": request_id,
-          "data": summary
-        }),
-        ScanEvent::Complete(summary) => serde_json::json!({
-          "event": "scan-complete",
-          "id": request_id,
-          "data": summary
-        }),
-        ScanEvent::Error(message) => serde_json::json!({
-          "event": "scan-error",
-          "id": request_id,
-          "message": message
-        }),
-        ScanEvent::Cancelled(message) => serde_json::json!({
-          "event": "scan-cancelled",
-          "id": request_id,
-          "message": message
-        }),
-    };
-    let line = format!("{}\n", payload);
-    hub.broadcast(line);
+// One TCP connection as seen by the request handlers.
+struct RemoteSession {
+    id: u64,
+    sender: mpsc::Sender<String>,
+    peer: Option<SocketAddr>,
+    // Challenge sent on connect; the client proves its token by signing it.
+    nonce: String,
+    grant: Mutex<Option<RemoteGrant>>,
+    flow: Arc<RemoteFlowControl>,
 }
 
-#[derive(Deserialize)]
-struct BatchRenameItem {
-    path: String,
-    new_path: String,
+impl RemoteSession {
+    // Registers a new client with the hub and returns the receiving end of its queue.
+    // Fails without a nonce, since the connection could not be authenticated safely.
+    fn open(
+        hub: &RemoteHub,
+        peer: Option<SocketAddr>,
+    ) -> Result<(Self, mpsc::Receiver<String>), String> {
+        let nonce = random_nonce()?;
+        let (sender, receiver) = mpsc::channel::<String>();
+        let session = Self {
+            id: hub.register_client(sender.clone()),
+            sender,
+            peer,
+            nonce,
+            grant: Mutex::new(None),
+            flow: Arc::new(RemoteFlowControl::default()),
+        };
+        Ok((session, receiver))
+    }
 }
 
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-struct BatchRenameResult {
-    success_count: u32,
-    errors: Vec<String>,
+// Progress of a connection's writer thread, so bulk senders can keep the outgoing queue
+// bounded instead of buffering a whole file in memory.
+#[derive(Default)]
+struct RemoteFlowControl {
+    written: AtomicU64,
+    // End of the connection's window, in the writer's byte count; shared by every
+    // download on the connection.
+    queued: AtomicU64,
+    closed: AtomicBool,
 }
 
-#[tauri::command]
-fn batch_rename(items: Vec<BatchRenameItem>) -> Result<BatchRenameResult, String> {
-    let mut success_count = 0;
-    let mut errors = Vec::new();
-
-    for item in items {
-        let source = PathBuf::from(&item.path);
-        let dest = PathBuf::from(&item.new_path);
-
-        if !source.exists() {
-            errors.push(format!("Source does not exist: {}", item.path));
-            continue;
-        }
-
-        // Safety check to prevent accidental overwrites
-        if dest.exists() {
-            // Check if it's a case-only rename (e.g. "a.txt" -> "A.TXT")
-            let source_str = source.to_string_lossy().to_string();
-            let dest_str = dest.to_string_lossy().to_string();
-
-            // If paths are different but look the same in lowercase, it's likely a case rename.
-            // On case-insensitive filesystems (Windows/macOS default), dest.exists() is true for case rename.
-            // On case-sensitive (Linux), it is false unless a file named "A.TXT" actually exists.
-
-            // If it is NOT a case-only rename, fail.
-            if source_str != dest_str && source_str.to_lowercase() != dest_str.to_lowercase() {
-                errors.push(format!("Destination already exists: {}", item.new_path));
-                continue;
+impl RemoteFlowControl {
+    // Reserves `len` bytes in the connection's window and waits until at most
+    // DOWNLOAD_WINDOW_BYTES of it are still unwritten. Returns false once the connection is gone.
+    fn wait_for_room(&self, len: u64) -> bool {
+        let written = self.written.load(Ordering::Relaxed);
+        let previous = self
+            .queued
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |queued| {
+                Some(queued.max(written) + len)
+            })
+            .unwrap_or_else(|value| value);
+        let position = previous.max(written) + len;
+        loop {
+            if self.closed.load(Ordering::Relaxed) {
+                return false;
             }
-        }
-
-        match fs::rename(&source, &dest) {
-            Ok(_) => success_count += 1,
-            Err(e) => errors.push(format!("Failed to rename {}: {}", item.path, e)),
+            if position <= self.written.load(Ordering::Relaxed) + DOWNLOAD_WINDOW_BYTES {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(5));
         }
     }
-
-    Ok(BatchRenameResult {
-        success_count,
-        errors,
-    })
 }
 
-#[tauri::command]
-fn get_startup_path(state: tauri::State<StartupPath>) -> Option<String> {
-    state.0.lock().unwrap().clone()
+struct RemoteScanEntry {
+    owner: u64,
+    // Name of the grant that submitted the scan; scheduled scans have none.
+    grant: Option<String>,
+    // Canonical form of `roots`, checked against the roots of other clients' grants.
+    resolved_roots: Vec<PathBuf>,
+    subscribers: HashSet<u64>,
+    control: Arc<ScanControl>,
+    state: RemoteScanState,
+    roots: Vec<PathBuf>,
+    config: Option<ScanConfig>,
+    submitted_at: Instant,
+    finished_at: Option<Instant>,
+    file_count: u64,
+    total_bytes: u64,
+    result: Option<ScanSummary>,
+    error: Option<String>,
 }
 
-#[tauri::command]
-fn get_launch_context(state: tauri::State<LaunchContextState>) -> LaunchContext {
-    state.0.lock().unwrap().clone()
+impl RemoteScanEntry {
+    // The submitting connection and other connections holding the same grant may act
+    // on a scan; scheduled scans are open to every client. Either way, every scanned
+    // root must lie inside the client's own roots.
+    fn visible_to(&self, client: u64, grant: &RemoteGrant) -> bool {
+        (self.owner == client || self.grant.as_deref().is_none_or(|name| name == grant.name))
+            && self
+                .resolved_roots
+                .iter()
+                .all(|root| grant.allows_resolved(root))
+    }
 }
 
-#[tauri::command]
-fn scan_path(
-    window: tauri::Window,
-    path: String,
-    options: ScanOptions,
-    id: Option<String>,
-    state: tauri::State<ScanCancellation>,
-) -> Result<(), String> {
-    let root = PathBuf::from(&path);
-    if !root.exists() {
-        return Err("Path does not exist".to_string());
-    }
+#[derive(Default)]
+struct RemoteScanQueue {
+    entries: HashMap<String, RemoteScanEntry>,
+    pending: VecDeque<String>,
+    finished: VecDeque<String>,
+    running: usize,
+    next_id: u64,
+}
 
-    let config = build_scan_config(&options)?;
-    let label = window.label().to_string();
-    let cancel_flag = Arc::new(AtomicBool::new(false));
-    {
-        let mut cancellations = state
-            .0
-            .lock()
-            .map_err(|_| "Failed to lock scan state".to_string())?;
-        if let Some(existing) = cancellations.get(&label) {
-            existing.store(true, Ordering::SeqCst);
-        }
-        cancellations.insert(label.clone(), Arc::clone(&cancel_flag));
-    }
-    let window_for_task = window.clone();
-    let label_for_task = label.clone();
-    let task_id = id.clone();
+// A scan that has been given a slot and should be spawned by the caller.
+struct RemoteScanStart {
+    id: String,
+    roots: Vec<PathBuf>,
+    config: ScanConfig,
+    control: Arc<ScanControl>,
+}
 
-    tauri::async_runtime::spawn(async move {
-        let app_handle = window_for_task.app_handle();
-        let emitter_window = window_for_task.clone();
-        let emitter: ScanEmitter = Arc::new(move |event| emit_to_window(&emitter_window, event));
-        if let Err(error) = run_scan(root, config, Arc::clone(&cancel_flag), emitter, task_id) {
-            let _ = window_for_task.emit("scan-error", error);
-        }
-        let cancellations = app_handle.state::<ScanCancellation>();
-        if let Ok(mut map) = cancellations.0.lock() {
-            map.remove(&label_for_task);
-        };
-    });
+enum RemoteScanSubmission {
+    Started(Box<RemoteScanStart>),
+    Queued { id: String, position: usize },
+}
 
-    Ok(())
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteScanStatus {
+    id: String,
+    state: RemoteScanState,
+    paused: bool,
+    paths: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    position: Option<usize>,
+    file_count: u64,
+    total_bytes: u64,
+    elapsed_ms: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
-#[tauri::command]
-fn cancel_scan(window: tauri::Window, state: tauri::State<ScanCancellation>) -> Result<(), String> {
-    let label = window.label().to_string();
-    let cancellations = state
-        .0
-        .lock()
-        .map_err(|_| "Failed to lock scan state".to_string())?;
-    if let Some(flag) = cancellations.get(&label) {
-        flag.store(true, Ordering::SeqCst);
-    }
-    Ok(())
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScanFilters {
+    include_extensions: Vec<String>,
+    exclude_extensions: Vec<String>,
+    include_names: Vec<String>,
+    exclude_names: Vec<String>,
+    min_size_bytes: Option<u64>,
+    max_size_bytes: Option<u64>,
+    min_modified_timestamp: Option<u64>,
+    max_modified_timestamp: Option<u64>,
+    include_regex: Option<String>,
+    exclude_regex: Option<String>,
+    include_paths: Vec<String>,
+    exclude_paths: Vec<String>,
 }
 
-#[tauri::command]
-fn get_disk_usage(path: String) -> Result<DiskUsageSnapshot, String> {
-    let target = PathBuf::from(&path);
-    compute_disk_usage(&target)
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScanOptions {
+    priority_mode: ScanPriorityMode,
+    throttle_level: ScanThrottleLevel,
+    filters: ScanFilters,
+    #[serde(default)]
+    scan_archives: bool,
+    max_depth: Option<usize>,
+    detail_depth: Option<usize>,
+    adaptive_throttle: Option<AdaptiveThrottleOptions>,
 }
 
-#[tauri::command]
-fn delete_item(path: String) -> Result<(), String> {
-    let target = PathBuf::from(&path);
-    if !target.exists() {
-        return Err("Path does not exist".to_string());
-    }
-    if target.is_file() {
-        fs::remove_file(target).map_err(|e| e.to_string())?;
-    } else {
-        fs::remove_dir_all(target).map_err(|e| e.to_string())?;
-    }
-    Ok(())
+#[derive(Clone, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct AdaptiveThrottleOptions {
+    target_latency_ms: Option<f64>,
+    max_io_pressure: Option<f64>,
+    max_sleep_ms: Option<u64>,
 }
 
-#[tauri::command]
-fn rename_item(path: String, new_path: String) -> Result<(), String> {
-    let target = PathBuf::from(&path);
-    let dest = PathBuf::from(&new_path);
-    if !target.exists() {
-        return Err("Source path does not exist".to_string());
-    }
-    if dest.exists() {
-        return Err("Destination already exists".to_string());
+impl Default for ScanPriorityMode {
+    fn default() -> Self {
+        ScanPriorityMode::Balanced
     }
-    fs::rename(target, dest).map_err(|e| e.to_string())?;
-    Ok(())
 }
 
-#[tauri::command]
-fn create_folder(path: String) -> Result<(), String> {
-    let target = PathBuf::from(&path);
-    if target.exists() {
-        return Err("Path already exists".to_string());
+impl Default for ScanThrottleLevel {
+    fn default() -> Self {
+        ScanThrottleLevel::Off
     }
-    fs::create_dir_all(target).map_err(|e| e.to_string())?;
-    Ok(())
 }
 
-#[tauri::command]
-fn copy_item(path: String, new_path: String) -> Result<(), String> {
-    let source = PathBuf::from(&path);
-    let dest = PathBuf::from(&new_path);
-    if !source.exists() {
-        return Err("Source does not exist".to_string());
-    }
-    if dest.exists() {
-        return Err("Destination exists".to_string());
-    }
-    if source.is_file() {
-        fs::copy(source, dest).map_err(|e| e.to_string())?;
-    } else {
-        copy_dir_recursive(&source, &dest)?;
-    }
+impl Default for ScanFilters {
+    fn default() -> Self {
+        Self {
+            include_extensions: Vec::new(),
+            exclude_extensions: Vec::new(),
+            include_names: Vec::new(),
+            exclude_names: Vec::new(),
+            min_size_bytes: None,
+            max_size_bytes: None,
+            min_modified_timestamp: None,
+            max_modified_timestamp: None,
+            include_regex: None,
+            exclude_regex: None,
+            include_paths: Vec::new(),
+            exclude_paths: Vec::new(),
+        }
+    }
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            priority_mode: ScanPriorityMode::default(),
+            throttle_level: ScanThrottleLevel::default(),
+            filters: ScanFilters::default(),
+            scan_archives: false,
+            max_depth: None,
+            detail_depth: None,
+            adaptive_throttle: None,
+        }
+    }
+}
+
+struct FilterConfig {
+    include_extensions: HashSet<String>,
+    exclude_extensions: HashSet<String>,
+    include_names: Vec<String>,
+    exclude_names: Vec<String>,
+    min_size_bytes: Option<u64>,
+    max_size_bytes: Option<u64>,
+    min_modified_timestamp: Option<u64>,
+    max_modified_timestamp: Option<u64>,
+    include_regex: Option<Regex>,
+    exclude_regex: Option<Regex>,
+    include_paths: Vec<String>,
+    exclude_paths: Vec<String>,
+    flags: FilterFlags,
+}
+
+struct FilterFlags {
+    has_includes: bool,
+    has_file_excludes: bool,
+    has_dir_excludes: bool,
+    needs_path: bool,
+    needs_name: bool,
+    needs_extension: bool,
+}
+
+struct ThrottleConfig {
+    every_entries: u64,
+    sleep_ms: u64,
+}
+
+const ADAPTIVE_WINDOW_ENTRIES: u64 = 200;
+const ADAPTIVE_PRESSURE_INTERVAL: Duration = Duration::from_secs(1);
+
+// Backs off multiplicatively while stat latency or system I/O pressure is above
+// target and recovers gradually once both are back under it.
+struct AdaptiveThrottle {
+    target_latency_us: f64,
+    max_io_pressure: f64,
+    max_sleep_ms: u64,
+    sleep_ms: u64,
+    latency_us: Option<f64>,
+    io_pressure: Option<f64>,
+    pressure_read_at: Option<Instant>,
+    entries_since_check: u64,
+}
+
+struct ScanConfig {
+    filters: FilterConfig,
+    emit_every: u64,
+    emit_interval: Duration,
+    throttle: Option<ThrottleConfig>,
+    adaptive_throttle: Option<AdaptiveThrottleOptions>,
+    parallelism: Parallelism,
+    scan_archives: bool,
+    max_depth: Option<usize>,
+    detail_depth: Option<usize>,
+}
+
+#[derive(Default)]
+struct NodeStats {
+    direct_bytes: u64,
+    direct_files: u64,
+    direct_dirs: u64,
+    collapsed_dirs: u64,
+    archive: Option<ArchiveKind>,
+}
+
+#[derive(Clone, Copy)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+    TarZst,
+}
+
+struct ArchiveEntry {
+    name: String,
+    is_dir: bool,
+    size_bytes: u64,
+    compressed_bytes: Option<u64>,
+    modified: Option<u64>,
+}
+
+struct ArchiveListing {
+    dirs: Vec<PathBuf>,
+    files: Vec<ScanFile>,
+}
+
+impl ArchiveKind {
+    fn detect(path: &Path) -> Option<Self> {
+        let name = get_entry_name_lower(path);
+        if name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Some(ArchiveKind::TarZst)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else {
+            None
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            ArchiveKind::Zip => "zip",
+            ArchiveKind::Tar => "tar",
+            ArchiveKind::TarGz => "tar.gz",
+            ArchiveKind::TarZst => "tar.zst",
+        }
+    }
+}
+
+impl AdaptiveThrottle {
+    fn new(options: &AdaptiveThrottleOptions) -> Self {
+        Self {
+            target_latency_us: options.target_latency_ms.unwrap_or(4.0) * 1000.0,
+            max_io_pressure: options.max_io_pressure.unwrap_or(10.0),
+            max_sleep_ms: options.max_sleep_ms.unwrap_or(500).max(1),
+            sleep_ms: 0,
+            latency_us: None,
+            io_pressure: None,
+            pressure_read_at: None,
+            entries_since_check: 0,
+        }
+    }
+
+    fn record_latency(&mut self, elapsed: Duration) {
+        let sample = elapsed.as_secs_f64() * 1_000_000.0;
+        self.latency_us = Some(match self.latency_us {
+            Some(current) => current * 0.95 + sample * 0.05,
+            None => sample,
+        });
+    }
+
+    fn pace(&mut self) {
+        self.entries_since_check += 1;
+        if self.entries_since_check < ADAPTIVE_WINDOW_ENTRIES {
+            return;
+        }
+        self.entries_since_check = 0;
+        let pressure_stale = match self.pressure_read_at {
+            Some(read_at) => read_at.elapsed() >= ADAPTIVE_PRESSURE_INTERVAL,
+            None => true,
+        };
+        if pressure_stale {
+            self.io_pressure = read_io_pressure();
+            self.pressure_read_at = Some(Instant::now());
+        }
+        let slow = self
+            .latency_us
+            .is_some_and(|latency| latency > self.target_latency_us);
+        let pressured = self
+            .io_pressure
+            .is_some_and(|pressure| pressure > self.max_io_pressure);
+        self.sleep_ms = if slow || pressured {
+            (self.sleep_ms * 2).clamp(1, self.max_sleep_ms)
+        } else {
+            self.sleep_ms.saturating_sub(self.sleep_ms / 4 + 1)
+        };
+        if self.sleep_ms > 0 {
+            thread::sleep(Duration::from_millis(self.sleep_ms));
+        }
+    }
+}
+
+// Share of the last 10 seconds in which some task was stalled on I/O (Linux PSI).
+#[cfg(target_os = "linux")]
+fn read_io_pressure() -> Option<f64> {
+    let contents = fs::read_to_string("/proc/pressure/io").ok()?;
+    let line = contents.lines().find(|line| line.starts_with("some "))?;
+    line.split_whitespace()
+        .find_map(|field| field.strip_prefix("avg10="))
+        .and_then(|value| value.parse().ok())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_io_pressure() -> Option<f64> {
+    None
+}
+
+impl ScanControl {
+    fn new() -> Self {
+        Self {
+            cancelled: AtomicBool::new(false),
+            paused: AtomicBool::new(false),
+            pause_lock: Mutex::new(()),
+            resumed: Condvar::new(),
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    fn cancel(&self) {
+        let _guard = self.pause_lock.lock();
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.resumed.notify_all();
+    }
+
+    fn pause(&self) -> bool {
+        let _guard = self.pause_lock.lock();
+        !self.paused.swap(true, Ordering::SeqCst)
+    }
+
+    fn resume(&self) -> bool {
+        let _guard = self.pause_lock.lock();
+        let was_paused = self.paused.swap(false, Ordering::SeqCst);
+        self.resumed.notify_all();
+        was_paused
+    }
+
+    fn wait_while_paused(&self) {
+        if !self.paused.load(Ordering::Relaxed) {
+            return;
+        }
+        let mut guard = match self.pause_lock.lock() {
+            Ok(value) => value,
+            Err(_) => return,
+        };
+        while self.paused.load(Ordering::SeqCst) && !self.cancelled.load(Ordering::SeqCst) {
+            guard = match self.resumed.wait(guard) {
+                Ok(value) => value,
+                Err(_) => return,
+            };
+        }
+    }
+}
+
+impl RemoteScanQueue {
+    fn take_start(&mut self, id: &str) -> Option<RemoteScanStart> {
+        let entry = self.entries.get_mut(id)?;
+        let config = entry.config.take()?;
+        entry.state = RemoteScanState::Running;
+        self.running += 1;
+        Some(RemoteScanStart {
+            id: id.to_string(),
+            roots: entry.roots.clone(),
+            config,
+            control: Arc::clone(&entry.control),
+        })
+    }
+
+    fn retire(&mut self, id: &str) {
+        self.finished.retain(|existing| existing != id);
+        self.finished.push_back(id.to_string());
+        if let Some(entry) = self.entries.get_mut(id) {
+            entry.finished_at = Some(Instant::now());
+        }
+        while self.finished.len() > MAX_FINISHED_SCANS {
+            if let Some(oldest) = self.finished.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.prune_results();
+    }
+
+    // Keeps the newest finished trees within the node budget and drops expired ones.
+    fn prune_results(&mut self) {
+        let mut retained = 0u64;
+        for id in self.finished.iter().rev() {
+            let Some(entry) = self.entries.get_mut(id) else {
+                continue;
+            };
+            let Some(summary) = &entry.result else {
+                continue;
+            };
+            let expired = entry
+                .finished_at
+                .is_some_and(|at| at.elapsed() > FINISHED_RESULT_TTL);
+            retained += summary.file_count + summary.dir_count;
+            if expired || retained > MAX_RETAINED_RESULT_NODES {
+                entry.result = None;
+            }
+        }
+    }
+
+    fn status(&self, id: &str) -> Option<RemoteScanStatus> {
+        let entry = self.entries.get(id)?;
+        Some(RemoteScanStatus {
+            id: id.to_string(),
+            state: entry.state,
+            paused: entry.control.is_paused(),
+            paths: entry
+                .roots
+                .iter()
+                .map(|root| root.to_string_lossy().to_string())
+                .collect(),
+            position: self.pending.iter().position(|pending| pending == id),
+            file_count: entry.file_count,
+            total_bytes: entry.total_bytes,
+            elapsed_ms: entry.submitted_at.elapsed().as_millis(),
+            error: entry.error.clone(),
+        })
+    }
+}
+
+impl RemoteGrant {
+    fn full(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            scopes: ALL_REMOTE_SCOPES.into_iter().collect(),
+            roots: None,
+        }
+    }
+
+    fn allows(&self, scope: RemoteScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    fn restrict_to(mut self, allowed: Option<&[PathBuf]>) -> Self {
+        if let Some(allowed) = allowed {
+            self.roots = Some(match self.roots.as_deref() {
+                Some(own) => intersect_roots(own, allowed),
+                None => allowed.to_vec(),
+            });
+        }
+        self
+    }
+
+    fn allows_path(&self, path: &Path) -> bool {
+        match self.roots.as_ref() {
+            None => true,
+            Some(_) => match canonicalize_for_check(path) {
+                Some(resolved) => self.allows_resolved(&resolved),
+                None => false,
+            },
+        }
+    }
+
+    // Like `allows_path`, for a path that is already canonical.
+    fn allows_resolved(&self, resolved: &Path) -> bool {
+        self.roots
+            .as_ref()
+            .is_none_or(|roots| roots.iter().any(|root| resolved.starts_with(root)))
+    }
+
+    // Allowed roots and filesystem roots may not be deleted, renamed or moved.
+    fn protects(&self, path: &Path) -> bool {
+        let Some(resolved) = canonicalize_for_check(path) else {
+            return true;
+        };
+        resolved.parent().is_none()
+            || self
+                .roots
+                .as_ref()
+                .is_some_and(|roots| roots.contains(&resolved))
+    }
+}
+
+// Resolves `..` and symlinks. Parts of the path that do not exist yet are resolved through
+// the nearest existing ancestor.
+fn canonicalize_for_check(path: &Path) -> Option<PathBuf> {
+    if let Ok(resolved) = fs::canonicalize(path) {
+        return Some(resolved);
+    }
+    let name = path.file_name()?;
+    if name == ".." {
+        return None;
+    }
+    let parent = match path.parent() {
+        Some(value) if !value.as_os_str().is_empty() => value,
+        _ => Path::new("."),
+    };
+    canonicalize_for_check(parent).map(|resolved| resolved.join(name))
+}
+
+// Empty means unrestricted. Roots that cannot be resolved are dropped rather than widening
+// access, so a list whose roots are all missing allows nothing.
+fn canonical_roots(raw: &[String], owner: &str) -> Option<Vec<PathBuf>> {
+    if raw.is_empty() {
+        return None;
+    }
+    Some(
+        raw.iter()
+            .filter_map(|root| match fs::canonicalize(root) {
+                Ok(value) => Some(value),
+                Err(error) => {
+                    eprintln!("[remote] {owner} root {root:?} unavailable: {error}");
+                    None
+                }
+            })
+            .collect(),
+    )
+}
+
+// Keeps only the parts of both trees that overlap.
+fn intersect_roots(left: &[PathBuf], right: &[PathBuf]) -> Vec<PathBuf> {
+    let mut result: Vec<PathBuf> = Vec::new();
+    for a in left {
+        for b in right {
+            let overlap = if a.starts_with(b) {
+                a
+            } else if b.starts_with(a) {
+                b
+            } else {
+                continue;
+            };
+            if !result.contains(overlap) {
+                result.push(overlap.clone());
+            }
+        }
+    }
+    result
+}
+
+fn random_nonce() -> Result<String, String> {
+    use ring::rand::SecureRandom;
+    let mut bytes = [0u8; 32];
+    ring::rand::SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "System random source unavailable".to_string())?;
+    Ok(encode_hex(&bytes))
+}
+
+fn token_digest(token: &str) -> Vec<u8> {
+    ring::digest::digest(&ring::digest::SHA256, token.as_bytes())
+        .as_ref()
+        .to_vec()
+}
+
+fn build_remote_tokens(shared: Option<&str>, api_tokens: &[ApiTokenSettings]) -> Vec<RemoteToken> {
+    let mut tokens = Vec::new();
+    if let Some(secret) = shared.filter(|value| !value.is_empty()) {
+        tokens.push(RemoteToken {
+            digest: token_digest(secret),
+            secret: secret.as_bytes().to_vec(),
+            grant: RemoteGrant::full("default"),
+        });
+    }
+    for entry in api_tokens {
+        if entry.token.is_empty() {
+            eprintln!(
+                "[remote] skipping api token {:?} with empty secret",
+                entry.name
+            );
+            continue;
+        }
+        let roots = canonical_roots(&entry.roots, &format!("token {:?}", entry.name));
+        tokens.push(RemoteToken {
+            digest: token_digest(&entry.token),
+            secret: entry.token.as_bytes().to_vec(),
+            grant: RemoteGrant {
+                name: entry.name.clone(),
+                scopes: entry.scopes.iter().copied().collect(),
+                roots,
+            },
+        });
+    }
+    tokens
+}
+
+// An action missing from REMOTE_ACTIONS is refused rather than left unscoped.
+fn required_scope(request: &RemoteRequest) -> Result<Option<RemoteScope>, &'static str> {
+    REMOTE_ACTIONS
+        .iter()
+        .find(|(action, _)| *action == request.action())
+        .map(|(_, scope)| *scope)
+        .ok_or("unsupported-action")
+}
+
+impl RemoteHub {
+    fn new(
+        tokens: Vec<RemoteToken>,
+        allowed_roots: Option<Vec<PathBuf>>,
+        shutdown: Option<mpsc::Sender<()>>,
+        max_concurrent_scans: usize,
+        audit: Option<RemoteAuditLog>,
+        file_ops: bool,
+        watch_paths: Vec<PathBuf>,
+    ) -> Self {
+        Self {
+            tokens,
+            allowed_roots,
+            clients: Mutex::new(HashMap::new()),
+            next_client_id: AtomicU64::new(1),
+            watchers: Mutex::new(HashMap::new()),
+            scans: Mutex::new(RemoteScanQueue::default()),
+            max_concurrent_scans: max_concurrent_scans.max(1),
+            auth_failures: AuthRateLimiter::default(),
+            audit,
+            file_ops,
+            legacy_tokens: false,
+            shutdown,
+            watch_paths,
+            metrics: RemoteMetrics::default(),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.clients
+            .lock()
+            .map(|clients| clients.len() >= MAX_CONNECTIONS)
+            .unwrap_or(false)
+    }
+
+    fn register_client(&self, sender: mpsc::Sender<String>) -> u64 {
+        let id = self.next_client_id.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.insert(id, sender);
+        }
+        id
+    }
+
+    fn unregister_client(&self, id: u64) {
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.remove(&id);
+        }
+        if let Ok(mut watchers) = self.watchers.lock() {
+            watchers.remove(&id);
+        }
+    }
+
+    fn send_to_clients(&self, recipients: &HashSet<u64>, message: String) {
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.retain(|id, sender| {
+                !recipients.contains(id) || sender.send(message.clone()).is_ok()
+            });
+        }
+    }
+
+    // The scan's owner, its subscribers and every watcher allowed to see it.
+    fn scan_audience(&self, id: &str) -> HashSet<u64> {
+        let watchers = match self.watchers.lock() {
+            Ok(watchers) => watchers.clone(),
+            Err(_) => HashMap::new(),
+        };
+        let mut audience = HashSet::new();
+        if let Ok(queue) = self.scans.lock() {
+            if let Some(entry) = queue.entries.get(id) {
+                audience.insert(entry.owner);
+                audience.extend(entry.subscribers.iter().copied());
+                audience.extend(
+                    watchers
+                        .iter()
+                        .filter(|(client, grant)| entry.visible_to(**client, grant))
+                        .map(|(client, _)| *client),
+                );
+            }
+        }
+        audience
+    }
+
+    fn send_to_scan_audience(&self, id: &str, message: String) {
+        let audience = self.scan_audience(id);
+        self.send_to_clients(&audience, message);
+    }
+
+    fn send_to_watchers(&self, message: String, allowed: impl Fn(&RemoteGrant) -> bool) {
+        let watchers = match self.watchers.lock() {
+            Ok(watchers) => watchers
+                .iter()
+                .filter(|(_, grant)| allowed(grant))
+                .map(|(client, _)| *client)
+                .collect(),
+            Err(_) => return,
+        };
+        self.send_to_clients(&watchers, message);
+    }
+
+    fn subscribe(&self, client: u64, grant: &RemoteGrant, id: Option<&str>, enable: bool) -> bool {
+        match id {
+            Some(value) => {
+                let mut queue = match self.scans.lock() {
+                    Ok(guard) => guard,
+                    Err(_) => return false,
+                };
+                match queue.entries.get_mut(value) {
+                    Some(entry) if entry.visible_to(client, grant) => {
+                        if enable {
+                            entry.subscribers.insert(client);
+                        } else {
+                            entry.subscribers.remove(&client);
+                        }
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            None => match self.watchers.lock() {
+                Ok(mut watchers) => {
+                    if enable {
+                        watchers.insert(client, grant.clone());
+                    } else {
+                        watchers.remove(&client);
+                    }
+                    true
+                }
+                Err(_) => false,
+            },
+        }
+    }
+
+    fn submit_scan(
+        &self,
+        owner: u64,
+        grant: Option<&RemoteGrant>,
+        id: Option<String>,
+        roots: Vec<PathBuf>,
+        config: ScanConfig,
+    ) -> Result<RemoteScanSubmission, &'static str> {
+        let mut queue = self.scans.lock().map_err(|_| "scan-queue-unavailable")?;
+        let id = match id {
+            Some(value) => value,
+            None => {
+                queue.next_id += 1;
+                format!("{SERVER_SCAN_ID_PREFIX}{}", queue.next_id)
+            }
+        };
+        if let Some(existing) = queue.entries.get(&id) {
+            if matches!(
+                existing.state,
+                RemoteScanState::Queued | RemoteScanState::Running
+            ) {
+                return Err("scan-id-in-use");
+            }
+            // A finished scan's result is only replaced by someone who could read it.
+            if grant.is_some_and(|grant| !existing.visible_to(owner, grant)) {
+                return Err("scan-id-in-use");
+            }
+        }
+        let has_slot = queue.running < self.max_concurrent_scans;
+        if !has_slot && queue.pending.len() >= MAX_QUEUED_SCANS {
+            return Err("scan-queue-full");
+        }
+        queue.finished.retain(|existing| existing != &id);
+        let resolved_roots = roots
+            .iter()
+            .map(|root| canonicalize_for_check(root).unwrap_or_else(|| root.clone()))
+            .collect();
+        queue.entries.insert(
+            id.clone(),
+            RemoteScanEntry {
+                owner,
+                grant: grant.map(|grant| grant.name.clone()),
+                resolved_roots,
+                subscribers: HashSet::new(),
+                control: Arc::new(ScanControl::new()),
+                state: RemoteScanState::Queued,
+                roots,
+                config: Some(config),
+                submitted_at: Instant::now(),
+                finished_at: None,
+                file_count: 0,
+                total_bytes: 0,
+                result: None,
+                error: None,
+            },
+        );
+        if has_slot {
+            if let Some(start) = queue.take_start(&id) {
+                return Ok(RemoteScanSubmission::Started(Box::new(start)));
+            }
+        }
+        queue.pending.push_back(id.clone());
+        Ok(RemoteScanSubmission::Queued {
+            position: queue.pending.len() - 1,
+            id,
+        })
+    }
+
+    fn record_scan_event(&self, id: &str, event: &ScanEvent) {
+        let mut queue = match self.scans.lock() {
+            Ok(value) => value,
+            Err(_) => return,
+        };
+        let entry = match queue.entries.get_mut(id) {
+            Some(value) => value,
+            None => return,
+        };
+        match event {
+            ScanEvent::Progress(summary) => {
+                entry.file_count = summary.file_count;
+                entry.total_bytes = summary.total_bytes;
+            }
+            ScanEvent::Complete(summary) => {
+                entry.file_count = summary.file_count;
+                entry.total_bytes = summary.total_bytes;
+                entry.state = RemoteScanState::Complete;
+                entry.result = Some(summary.clone());
+            }
+            ScanEvent::Error(message) => {
+                entry.state = RemoteScanState::Failed;
+                entry.error = Some(message.clone());
+            }
+            ScanEvent::Cancelled(_) => {
+                entry.state = RemoteScanState::Cancelled;
+            }
+            ScanEvent::Paused(_) | ScanEvent::Resumed(_) => {}
+        }
+    }
+
+    // Frees the scan's slot and hands back whichever queued scans can start now.
+    fn finish_scan(&self, id: &str) -> Vec<RemoteScanStart> {
+        let mut queue = match self.scans.lock() {
+            Ok(value) => value,
+            Err(_) => return Vec::new(),
+        };
+        queue.running = queue.running.saturating_sub(1);
+        if let Some(entry) = queue.entries.get_mut(id) {
+            if entry.state == RemoteScanState::Running {
+                entry.state = if entry.control.is_cancelled() {
+                    RemoteScanState::Cancelled
+                } else {
+                    RemoteScanState::Failed
+                };
+            }
+        }
+        queue.retire(id);
+        let mut starts = Vec::new();
+        while queue.running < self.max_concurrent_scans {
+            let next = match queue.pending.pop_front() {
+                Some(value) => value,
+                None => break,
+            };
+            if let Some(start) = queue.take_start(&next) {
+                starts.push(start);
+            }
+        }
+        starts
+    }
+
+    // Returns the ids that matched; queued scans are dropped immediately, running ones
+    // stop at their next checkpoint. Without an id every scan the client submitted is cancelled.
+    fn cancel_scan(
+        &self,
+        client: u64,
+        grant: &RemoteGrant,
+        id: Option<&str>,
+    ) -> (Vec<String>, Vec<String>) {
+        let mut dropped = Vec::new();
+        let mut signalled = Vec::new();
+        let mut queue = match self.scans.lock() {
+            Ok(value) => value,
+            Err(_) => return (dropped, signalled),
+        };
+        let targets: Vec<String> = match id {
+            Some(value) => vec![value.to_string()],
+            None => queue
+                .entries
+                .iter()
+                .filter(|(_, entry)| entry.owner == client)
+                .map(|(key, _)| key.clone())
+                .collect(),
+        };
+        for target in targets {
+            let state = match queue.entries.get(&target) {
+                Some(entry) if entry.visible_to(client, grant) => entry.state,
+                _ => continue,
+            };
+            match state {
+                RemoteScanState::Queued => {
+                    queue.pending.retain(|pending| pending != &target);
+                    if let Some(entry) = queue.entries.get_mut(&target) {
+                        entry.control.cancel();
+                        entry.config = None;
+                        entry.state = RemoteScanState::Cancelled;
+                    }
+                    queue.retire(&target);
+                    dropped.push(target);
+                }
+                RemoteScanState::Running => {
+                    if let Some(entry) = queue.entries.get(&target) {
+                        entry.control.cancel();
+                    }
+                    signalled.push(target);
+                }
+                _ => {}
+            }
+        }
+        (dropped, signalled)
+    }
+
+    fn active_scan_control(
+        &self,
+        client: u64,
+        grant: &RemoteGrant,
+        id: Option<&str>,
+    ) -> Option<(String, Arc<ScanControl>)> {
+        let queue = self.scans.lock().ok()?;
+        let is_active = |entry: &RemoteScanEntry| {
+            matches!(
+                entry.state,
+                RemoteScanState::Queued | RemoteScanState::Running
+            )
+        };
+        match id {
+            Some(value) => queue
+                .entries
+                .get(value)
+                .filter(|entry| is_active(entry) && entry.visible_to(client, grant))
+                .map(|entry| (value.to_string(), Arc::clone(&entry.control))),
+            // Without an id, only the client's own scan is targeted, if it is unambiguous.
+            None => {
+                let mut active = queue
+                    .entries
+                    .iter()
+                    .filter(|(_, entry)| is_active(entry) && entry.owner == client);
+                match (active.next(), active.next()) {
+                    (Some((key, entry)), None) => Some((key.clone(), Arc::clone(&entry.control))),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    fn scan_statuses(
+        &self,
+        client: u64,
+        grant: &RemoteGrant,
+        id: Option<&str>,
+    ) -> Option<Vec<RemoteScanStatus>> {
+        let queue = self.scans.lock().ok()?;
+        match id {
+            Some(value) => queue
+                .entries
+                .get(value)
+                .filter(|entry| entry.visible_to(client, grant))
+                .and_then(|_| queue.status(value))
+                .map(|status| vec![status]),
+            None => {
+                let mut statuses: Vec<RemoteScanStatus> = queue
+                    .entries
+                    .iter()
+                    .filter(|(_, entry)| entry.visible_to(client, grant))
+                    .filter_map(|(key, _)| queue.status(key))
+                    .collect();
+                statuses.sort_by_key(|status| std::cmp::Reverse(status.elapsed_ms));
+                Some(statuses)
+            }
+        }
+    }
+
+    // `None` once the scan is unknown, e.g. after it was evicted from the finished list.
+    fn scan_state(&self, id: &str) -> Option<RemoteScanState> {
+        let queue = self.scans.lock().ok()?;
+        queue.entries.get(id).map(|entry| entry.state)
+    }
+
+    fn scan_visible(&self, client: u64, grant: &RemoteGrant, id: &str) -> bool {
+        self.scans.lock().is_ok_and(|queue| {
+            queue
+                .entries
+                .get(id)
+                .is_some_and(|entry| entry.visible_to(client, grant))
+        })
+    }
+
+    fn scan_result(&self, id: &str) -> Result<ScanSummary, &'static str> {
+        let mut queue = self.scans.lock().map_err(|_| "scan-queue-unavailable")?;
+        queue.prune_results();
+        let entry = queue.entries.get(id).ok_or("scan-not-found")?;
+        match (&entry.result, entry.state) {
+            (Some(summary), _) => Ok(summary.clone()),
+            (None, RemoteScanState::Queued | RemoteScanState::Running) => Err("scan-not-finished"),
+            (None, _) => Err("scan-has-no-result"),
+        }
+    }
+
+    fn record_scan_duration(&self, id: &str, elapsed: Duration) {
+        let state = match self.scans.lock() {
+            Ok(queue) => queue.entries.get(id).map(|entry| entry.state),
+            Err(_) => None,
+        };
+        let outcome = match state {
+            Some(RemoteScanState::Complete) => "complete",
+            Some(RemoteScanState::Cancelled) => "cancelled",
+            _ => "failed",
+        };
+        if let Ok(mut durations) = self.metrics.scan_durations.lock() {
+            let (count, seconds) = durations.entry(outcome).or_default();
+            *count += 1;
+            *seconds += elapsed.as_secs_f64();
+        }
+    }
+
+    fn record_watched_scan(&self, path: &Path, summary: &ScanSummary) {
+        if let Ok(mut watched) = self.metrics.watched.lock() {
+            let previous_total_bytes = watched.get(path).map(|sample| sample.total_bytes);
+            watched.insert(
+                path.to_path_buf(),
+                WatchedDirSample {
+                    total_bytes: summary.total_bytes,
+                    previous_total_bytes,
+                    file_count: summary.file_count,
+                    dir_count: summary.dir_count,
+                    duration_ms: summary.duration_ms,
+                    finished_at: SystemTime::now(),
+                },
+            );
+        }
+    }
+
+    // Sizes at the last two scheduled scans of a watched directory.
+    fn watched_growth(&self, path: &Path) -> Option<(u64, u64)> {
+        let watched = self.metrics.watched.lock().ok()?;
+        let sample = watched.get(path)?;
+        Some((sample.previous_total_bytes?, sample.total_bytes))
+    }
+
+    // Prometheus text exposition of the server's counters, disk usage of the watched
+    // directories and allowed roots, and the last scheduled scan of each watched directory.
+    fn render_metrics(&self) -> String {
+        let mut output = String::new();
+        let mut family = |name: &str, kind: &str, help: &str, samples: Vec<(String, String)>| {
+            output.push_str(&format!("# HELP {name} {help}\n# TYPE {name} {kind}\n"));
+            for (labels, value) in samples {
+                output.push_str(&format!("{name}{labels} {value}\n"));
+            }
+        };
+        let connections = self
+            .clients
+            .lock()
+            .map(|clients| clients.len())
+            .unwrap_or(0);
+        family(
+            "dragabyte_remote_connections",
+            "gauge",
+            "Open remote connections, including this request.",
+            vec![(String::new(), connections.to_string())],
+        );
+        let requests = match self.metrics.requests.lock() {
+            Ok(requests) => requests
+                .iter()
+                .map(|((action, outcome), count)| {
+                    (
+                        prometheus_labels(&[("action", action), ("outcome", outcome)]),
+                        count.to_string(),
+                    )
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        family(
+            "dragabyte_remote_requests_total",
+            "counter",
+            "Remote requests by action and outcome.",
+            requests,
+        );
+        let (queued, running) = match self.scans.lock() {
+            Ok(queue) => (queue.pending.len(), queue.running),
+            Err(_) => (0, 0),
+        };
+        family(
+            "dragabyte_scans",
+            "gauge",
+            "Remote scans that are queued or running.",
+            vec![
+                (
+                    prometheus_labels(&[("state", "queued")]),
+                    queued.to_string(),
+                ),
+                (
+                    prometheus_labels(&[("state", "running")]),
+                    running.to_string(),
+                ),
+            ],
+        );
+        let mut durations = Vec::new();
+        if let Ok(entries) = self.metrics.scan_durations.lock() {
+            for (outcome, (count, seconds)) in entries.iter() {
+                let labels = prometheus_labels(&[("outcome", outcome)]);
+                durations.push((format!("_sum{labels}"), seconds.to_string()));
+                durations.push((format!("_count{labels}"), count.to_string()));
+            }
+        }
+        family(
+            "dragabyte_scan_duration_seconds",
+            "summary",
+            "Time spent running remote scans, by final state.",
+            durations,
+        );
+        let mut disk_paths: Vec<&PathBuf> = self.watch_paths.iter().collect();
+        disk_paths.extend(self.allowed_roots.iter().flatten());
+        disk_paths.sort();
+        disk_paths.dedup();
+        let mut total = Vec::new();
+        let mut free = Vec::new();
+        for path in disk_paths {
+            let Ok(snapshot) = compute_disk_usage(path) else {
+                continue;
+            };
+            let labels = prometheus_labels(&[("path", &get_path_string(path))]);
+            total.push((labels.clone(), snapshot.total_bytes.to_string()));
+            free.push((labels, snapshot.free_bytes.to_string()));
+        }
+        family(
+            "dragabyte_disk_total_bytes",
+            "gauge",
+            "Size of the volume holding each watched directory and allowed root.",
+            total,
+        );
+        family(
+            "dragabyte_disk_free_bytes",
+            "gauge",
+            "Free space on the volume holding each watched directory and allowed root.",
+            free,
+        );
+        let mut sizes = Vec::new();
+        let mut files = Vec::new();
+        let mut dirs = Vec::new();
+        let mut durations = Vec::new();
+        let mut timestamps = Vec::new();
+        if let Ok(watched) = self.metrics.watched.lock() {
+            for (path, sample) in watched.iter() {
+                let labels = prometheus_labels(&[("path", &get_path_string(path))]);
+                sizes.push((labels.clone(), sample.total_bytes.to_string()));
+                files.push((labels.clone(), sample.file_count.to_string()));
+                dirs.push((labels.clone(), sample.dir_count.to_string()));
+                durations.push((
+                    labels.clone(),
+                    (sample.duration_ms as f64 / 1000.0).to_string(),
+                ));
+                let finished = sample
+                    .finished_at
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .map(|elapsed| elapsed.as_secs())
+                    .unwrap_or(0);
+                timestamps.push((labels, finished.to_string()));
+            }
+        }
+        family(
+            "dragabyte_watched_size_bytes",
+            "gauge",
+            "Size of each watched directory at its last scheduled scan.",
+            sizes,
+        );
+        family(
+            "dragabyte_watched_files",
+            "gauge",
+            "Files in each watched directory at its last scheduled scan.",
+            files,
+        );
+        family(
+            "dragabyte_watched_dirs",
+            "gauge",
+            "Folders in each watched directory at its last scheduled scan.",
+            dirs,
+        );
+        family(
+            "dragabyte_watched_scan_duration_seconds",
+            "gauge",
+            "Duration of the last scheduled scan of each watched directory.",
+            durations,
+        );
+        family(
+            "dragabyte_watched_last_scan_timestamp_seconds",
+            "gauge",
+            "When the last scheduled scan of each watched directory completed.",
+            timestamps,
+        );
+        output
+    }
+
+    // Every configured token is compared, in constant time, so timing does not reveal
+    // which one matched or how much of it was right.
+    fn authenticate(&self, token: Option<&str>) -> Option<RemoteGrant> {
+        let allowed = self.allowed_roots.as_deref();
+        if self.tokens.is_empty() {
+            return Some(RemoteGrant::full("anonymous").restrict_to(allowed));
+        }
+        let digest = token_digest(token?);
+        let mut matched: Option<&RemoteToken> = None;
+        for candidate in &self.tokens {
+            let equal: bool = candidate.digest.ct_eq(&digest).into();
+            if equal && matched.is_none() {
+                matched = Some(candidate);
+            }
+        }
+        matched.map(|candidate| candidate.grant.clone().restrict_to(allowed))
+    }
+
+    fn audit(
+        &self,
+        peer: Option<SocketAddr>,
+        token: Option<&str>,
+        target: &RemoteAuditTarget,
+        outcome: &Result<(), String>,
+        started: Instant,
+    ) {
+        let (status, error) = match outcome {
+            Ok(()) => ("ok", None),
+            Err(code) => ("error", Some(code)),
+        };
+        if let Ok(mut requests) = self.metrics.requests.lock() {
+            *requests.entry((target.action, status)).or_default() += 1;
+        }
+        let Some(log) = &self.audit else {
+            return;
+        };
+        log.append(&serde_json::json!({
+          "timestamp": get_time_millis(Ok(SystemTime::now())),
+          "peer": peer.map(|address| address.to_string()),
+          "token": token,
+          "action": target.action,
+          "id": target.id,
+          "path": target.path,
+          "paths": target.paths,
+          "outcome": status,
+          "error": error,
+          "durationMs": started.elapsed().as_millis() as u64
+        }));
+    }
+
+    fn requires_auth(&self) -> bool {
+        !self.tokens.is_empty()
+    }
+
+    // Checks an HMAC of the session nonce against every token, without stopping at the
+    // first match so timing does not reveal which token was used.
+    fn verify_auth_mac(&self, nonce: &str, mac: &str) -> Option<RemoteGrant> {
+        let mac = decode_hex(mac)?;
+        let message = format!("{AUTH_MAC_CONTEXT}{nonce}");
+        let mut matched: Option<&RemoteToken> = None;
+        for candidate in &self.tokens {
+            let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, &candidate.secret);
+            let valid = ring::hmac::verify(&key, message.as_bytes(), &mac).is_ok();
+            if valid && matched.is_none() {
+                matched = Some(candidate);
+            }
+        }
+        matched.map(|candidate| {
+            candidate
+                .grant
+                .clone()
+                .restrict_to(self.allowed_roots.as_deref())
+        })
+    }
+
+    fn request_shutdown(&self) -> bool {
+        match &self.shutdown {
+            Some(sender) => sender.send(()).is_ok(),
+            None => false,
+        }
+    }
+}
+
+fn emit_to_window(window: &tauri::Window, event: ScanEvent) {
+    match event {
+        ScanEvent::Progress(summary) => {
+            let _ = window.emit("scan-progress", summary);
+        }
+        ScanEvent::Complete(summary) => {
+            let _ = window.emit("scan-complete", summary);
+        }
+        ScanEvent::Error(message) => {
+            let _ = window.emit("scan-error", message);
+        }
+        ScanEvent::Cancelled(message) => {
+            let _ = window.emit("scan-cancelled", message);
+        }
+        ScanEvent::Paused(message) => {
+            let _ = window.emit("scan-paused", message);
+        }
+        ScanEvent::Resumed(message) => {
+            let _ = window.emit("scan-resumed", message);
+        }
+    }
+}
+
+fn emit_to_remote(hub: &RemoteHub, event: ScanEvent, scan_id: &str) {
+    let payload = match event {
+        ScanEvent::Progress(summary) => serde_json::json!({
+          "event": "scan-progress",
+          "id": scan_id,
+          "data": summary
+        }),
+        ScanEvent::Complete(summary) => serde_json::json!({
+          "event": "scan-complete",
+          "id": scan_id,
+          "data": summary
+        }),
+        ScanEvent::Error(message) => serde_json::json!({
+          "event": "scan-error",
+          "id": scan_id,
+          "message": message
+        }),
+        ScanEvent::Cancelled(message) => serde_json::json!({
+          "event": "scan-cancelled",
+          "id": scan_id,
+          "message": message
+        }),
+        ScanEvent::Paused(message) => serde_json::json!({
+          "event": "scan-paused",
+          "id": scan_id,
+          "message": message
+        }),
+        ScanEvent::Resumed(message) => serde_json::json!({
+          "event": "scan-resumed",
+          "id": scan_id,
+          "message": message
+        }),
+    };
+    let line = format!("{}\n", payload);
+    hub.send_to_scan_audience(scan_id, line);
+}
+
+#[derive(Deserialize)]
+struct BatchRenameItem {
+    path: String,
+    new_path: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchRenameResult {
+    success_count: u32,
+    errors: Vec<String>,
+}
+
+#[tauri::command]
+fn batch_rename(items: Vec<BatchRenameItem>) -> Result<BatchRenameResult, String> {
+    let mut success_count = 0;
+    let mut errors = Vec::new();
+
+    for item in items {
+        let source = PathBuf::from(&item.path);
+        let dest = PathBuf::from(&item.new_path);
+
+        if !source.exists() {
+            errors.push(format!("Source does not exist: {}", item.path));
+            continue;
+        }
+
+        // Safety check to prevent accidental overwrites
+        if dest.exists() {
+            // Check if it's a case-only rename (e.g. "a.txt" -> "A.TXT")
+            let source_str = source.to_string_lossy().to_string();
+            let dest_str = dest.to_string_lossy().to_string();
+
+            // If paths are different but look the same in lowercase, it's likely a case rename.
+            // On case-insensitive filesystems (Windows/macOS default), dest.exists() is true for case rename.
+            // On case-sensitive (Linux), it is false unless a file named "A.TXT" actually exists.
+
+            // If it is NOT a case-only rename, fail.
+            if source_str != dest_str && source_str.to_lowercase() != dest_str.to_lowercase() {
+                errors.push(format!("Destination already exists: {}", item.new_path));
+                continue;
+            }
+        }
+
+        match move_path(&source, &dest) {
+            Ok(_) => success_count += 1,
+            Err(e) => errors.push(format!("Failed to rename {}: {}", item.path, e)),
+        }
+    }
+
+    Ok(BatchRenameResult {
+        success_count,
+        errors,
+    })
+}
+
+#[tauri::command]
+fn get_startup_path(state: tauri::State<StartupPath>) -> Option<String> {
+    state.0.lock().unwrap().clone()
+}
+
+#[tauri::command]
+fn get_launch_context(state: tauri::State<LaunchContextState>) -> LaunchContext {
+    state.0.lock().unwrap().0.clone()
+}
+
+#[tauri::command]
+fn scan_path(
+    window: tauri::Window,
+    path: String,
+    paths: Option<Vec<String>>,
+    options: ScanOptions,
+    id: Option<String>,
+    state: tauri::State<ScanCancellation>,
+) -> Result<(), String> {
+    let roots = collect_scan_roots(&path, paths);
+    if roots.is_empty() || roots.iter().any(|root| !root.exists()) {
+        return Err("Path does not exist".to_string());
+    }
+
+    let config = build_scan_config(&options)?;
+    let label = window.label().to_string();
+    let control = Arc::new(ScanControl::new());
+    {
+        let mut cancellations = state
+            .0
+            .lock()
+            .map_err(|_| "Failed to lock scan state".to_string())?;
+        if let Some(existing) = cancellations.get(&label) {
+            existing.cancel();
+        }
+        cancellations.insert(label.clone(), Arc::clone(&control));
+    }
+    let window_for_task = window.clone();
+    let label_for_task = label.clone();
+    let task_id = id.clone();
+
+    tauri::async_runtime::spawn(async move {
+        let app_handle = window_for_task.app_handle();
+        let emitter_window = window_for_task.clone();
+        let emitter: ScanEmitter = Arc::new(move |event| emit_to_window(&emitter_window, event));
+        if let Err(error) = run_scan(roots, config, Arc::clone(&control), emitter, task_id) {
+            let _ = window_for_task.emit("scan-error", error);
+        }
+        let cancellations = app_handle.state::<ScanCancellation>();
+        if let Ok(mut map) = cancellations.0.lock() {
+            map.remove(&label_for_task);
+        };
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+fn cancel_scan(window: tauri::Window, state: tauri::State<ScanCancellation>) -> Result<(), String> {
+    let label = window.label().to_string();
+    let cancellations = state
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock scan state".to_string())?;
+    if let Some(control) = cancellations.get(&label) {
+        control.cancel();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn pause_scan(window: tauri::Window, state: tauri::State<ScanCancellation>) -> Result<(), String> {
+    let label = window.label().to_string();
+    let cancellations = state
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock scan state".to_string())?;
+    let control = cancellations
+        .get(&label)
+        .ok_or_else(|| "No active scan".to_string())?;
+    if control.pause() {
+        emit_to_window(&window, ScanEvent::Paused("Scan paused".to_string()));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn resume_scan(window: tauri::Window, state: tauri::State<ScanCancellation>) -> Result<(), String> {
+    let label = window.label().to_string();
+    let cancellations = state
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock scan state".to_string())?;
+    let control = cancellations
+        .get(&label)
+        .ok_or_else(|| "No active scan".to_string())?;
+    if control.resume() {
+        emit_to_window(&window, ScanEvent::Resumed("Scan resumed".to_string()));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn get_disk_usage(path: String) -> Result<DiskUsageSnapshot, String> {
+    let target = PathBuf::from(&path);
+    compute_disk_usage(&target)
+}
+
+#[tauri::command]
+fn delete_item(path: String) -> Result<(), String> {
+    remove_path(Path::new(&path))
+}
+
+#[tauri::command]
+fn rename_item(path: String, new_path: String) -> Result<(), String> {
+    rename_path(Path::new(&path), Path::new(&new_path))
+}
+
+#[tauri::command]
+fn create_folder(path: String) -> Result<(), String> {
+    create_folder_path(Path::new(&path))
+}
+
+#[tauri::command]
+fn copy_item(path: String, new_path: String) -> Result<(), String> {
+    copy_path(Path::new(&path), Path::new(&new_path))
+}
+
+fn remove_path(target: &Path) -> Result<(), String> {
+    if !target.exists() {
+        return Err("Path does not exist".to_string());
+    }
+    if target.is_file() {
+        fs::remove_file(target).map_err(|e| e.to_string())?;
+    } else {
+        fs::remove_dir_all(target).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn rename_path(target: &Path, dest: &Path) -> Result<(), String> {
+    if !target.exists() {
+        return Err("Source path does not exist".to_string());
+    }
+    if dest.exists() {
+        return Err("Destination already exists".to_string());
+    }
+    move_path(target, dest)
+}
+
+fn move_path(target: &Path, dest: &Path) -> Result<(), String> {
+    match fs::rename(target, dest) {
+        Ok(()) => Ok(()),
+        // Moves to another filesystem or drive copy the data, then remove the source.
+        Err(error) if error.kind() == std::io::ErrorKind::CrossesDevices => {
+            if let Err(error) = copy_path(target, dest) {
+                let _ = remove_path(dest);
+                return Err(error);
+            }
+            remove_path(target)
+        }
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+fn create_folder_path(target: &Path) -> Result<(), String> {
+    if target.exists() {
+        return Err("Path already exists".to_string());
+    }
+    fs::create_dir_all(target).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn copy_path(source: &Path, dest: &Path) -> Result<(), String> {
+    if !source.exists() {
+        return Err("Source does not exist".to_string());
+    }
+    if dest.exists() {
+        return Err("Destination exists".to_string());
+    }
+    if source.is_file() {
+        fs::copy(source, dest).map_err(|e| e.to_string())?;
+    } else {
+        copy_dir_recursive(source, dest)?;
+    }
+    Ok(())
+}
+
+fn copy_dir_recursive(source: &Path, dest: &Path) -> Result<(), String> {
+    fs::create_dir_all(dest).map_err(|e| e.to_string())?;
+    for entry in fs::read_dir(source).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let file_type = entry.file_type().map_err(|e| e.to_string())?;
+        let dest_path = dest.join(entry.file_name());
+        if file_type.is_symlink() {
+            copy_symlink(&entry.path(), &dest_path)?;
+        } else if file_type.is_dir() {
+            copy_dir_recursive(&entry.path(), &dest_path)?;
+        } else {
+            fs::copy(entry.path(), dest_path).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+// Links inside a copied tree are recreated as links rather than followed, so copying a
+// folder never pulls in files from outside it.
+fn copy_symlink(source: &Path, dest: &Path) -> Result<(), String> {
+    let link = fs::read_link(source).map_err(|e| e.to_string())?;
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&link, dest).map_err(|e| e.to_string())?;
+    #[cfg(windows)]
+    {
+        // Windows needs to know whether the link points at a folder.
+        let created = if source.is_dir() {
+            std::os::windows::fs::symlink_dir(&link, dest)
+        } else {
+            std::os::windows::fs::symlink_file(&link, dest)
+        };
+        created.map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn get_time_millis(time: std::io::Result<SystemTime>) -> Option<u64> {
+    time.ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+}
+
+fn run_scan(
+    roots: Vec<PathBuf>,
+    config: ScanConfig,
+    control: Arc<ScanControl>,
+    emit: ScanEmitter,
+    scan_id: Option<String>,
+) -> Result<(), String> {
+    let start = Instant::now();
+    let mut stats: HashMap<PathBuf, NodeStats> = HashMap::new();
+    let mut children: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+    let mut files_by_parent: HashMap<PathBuf, Vec<ScanFile>> = HashMap::new();
+    let mut largest_files: Vec<ScanFile> = Vec::new();
+    let mut last_emit = Instant::now();
+    let mut last_emitted_bytes: u64 = 0;
+    let mut processed: u64 = 0;
+    let mut skipped_dirs: HashSet<PathBuf> = HashSet::new();
+    let mut adaptive = config.adaptive_throttle.as_ref().map(AdaptiveThrottle::new);
+
+    let parallelism = share_parallelism(&config.parallelism);
+    if roots.len() > 1 {
+        children.insert(PathBuf::new(), roots.clone());
+        stats.entry(PathBuf::new()).or_default();
+    }
+
+    for root in &roots {
+        let walk_control = Arc::clone(&control);
+        // Blocking here while paused stops the walker threads from reading further directories.
+        let mut walk = WalkDir::new(root)
+            .parallelism(parallelism.clone())
+            .process_read_dir(move |_, _, _, _| walk_control.wait_while_paused());
+        if let Some(max_depth) = config.max_depth {
+            walk = walk.max_depth(max_depth);
+        }
+        for entry in walk {
+            control.wait_while_paused();
+            if control.is_cancelled() {
+                emit(ScanEvent::Cancelled("Scan cancelled".to_string()));
+                return Ok(());
+            }
+            let entry = match entry {
+                Ok(item) => item,
+                Err(_) => continue,
+            };
+            let entry_path = entry.path();
+            let entry_type = entry.file_type();
+            processed += 1;
+
+            // Entries below the detail depth are only counted towards their ancestor at that depth.
+            let anchor = detail_anchor(&entry_path, entry.depth(), config.detail_depth);
+            if let Some(anchor) = anchor.as_deref() {
+                if has_skipped_ancestor(&entry_path, anchor, &skipped_dirs) {
+                    continue;
+                }
+            }
+
+            if entry_type.is_dir() && entry.depth() == 0 {
+                // Roots are linked to the summary root, not to their parent on disk.
+                stats.entry(entry_path.to_path_buf()).or_default();
+            } else if entry_type.is_dir() {
+                if should_skip_dir(root, &entry_path, &config.filters) {
+                    if anchor.is_some() {
+                        skipped_dirs.insert(entry_path.to_path_buf());
+                    }
+                    continue;
+                }
+                match anchor {
+                    Some(anchor) => stats.entry(anchor).or_default().collapsed_dirs += 1,
+                    None => register_dir(&entry_path, &mut stats, &mut children),
+                }
+            } else if entry_type.is_file() {
+                let stat_start = Instant::now();
+                let metadata = entry.metadata();
+                if let Some(throttle) = adaptive.as_mut() {
+                    throttle.record_latency(stat_start.elapsed());
+                }
+                let size = metadata.as_ref().map(|meta| meta.len()).unwrap_or(0);
+                let modified = metadata
+                    .as_ref()
+                    .ok()
+                    .and_then(|m| get_time_millis(m.modified()));
+
+                let archive = if config.scan_archives && anchor.is_none() {
+                    // An archive is only opened when both the directory and the file filters
+                    // would keep it.
+                    ArchiveKind::detect(&entry_path)
+                        .filter(|_| !should_skip_dir(root, &entry_path, &config.filters))
+                        .filter(|_| {
+                            should_include_file(&entry_path, size, modified, &config.filters)
+                        })
+                        .and_then(|kind| {
+                            match read_archive_entries(&entry_path, kind, size, &control) {
+                                Ok(entries) => Some((kind, entries)),
+                                Err(error) => {
+                                    eprintln!("[scan] archive skipped {:?}: {error}", entry_path);
+                                    None
+                                }
+                            }
+                        })
+                } else {
+                    None
+                };
+
+                if let Some((kind, archive_entries)) = archive {
+                    register_dir(&entry_path, &mut stats, &mut children);
+                    stats.entry(entry_path.to_path_buf()).or_default().archive = Some(kind);
+                    let listing = expand_archive(&entry_path, archive_entries, &config.filters);
+                    for dir in listing.dirs {
+                        register_dir(&dir, &mut stats, &mut children);
+                    }
+                    for file in listing.files {
+                        register_file(file, &mut stats, &mut files_by_parent, &mut largest_files);
+                    }
+                } else {
+                    if !should_include_file(&entry_path, size, modified, &config.filters) {
+                        continue;
+                    }
+                    let file = ScanFile {
+                        path: get_path_string(&entry_path),
+                        name: get_entry_name_string(&entry_path),
+                        size_bytes: size,
+                        modified,
+                        uncompressed_bytes: None,
+                    };
+                    match anchor {
+                        Some(anchor) => {
+                            collapse_file(anchor, &file, &mut stats, &mut largest_files);
+                        }
+                        None => {
+                            register_file(
+                                file,
+                                &mut stats,
+                                &mut files_by_parent,
+                                &mut largest_files,
+                            );
+                        }
+                    }
+                }
+            }
+
+            if let Some(throttle) = &config.throttle {
+                if throttle.sleep_ms > 0 && processed % throttle.every_entries == 0 {
+                    thread::sleep(Duration::from_millis(throttle.sleep_ms));
+                }
+            }
+            if let Some(throttle) = adaptive.as_mut() {
+                throttle.pace();
+            }
+
+            if should_emit_progress(processed, &last_emit, &config) {
+                let summary = build_summary(
+                    &roots,
+                    &children,
+                    &files_by_parent,
+                    &stats,
+                    &largest_files,
+                    start,
+                    scan_id.clone(),
+                    true,      // compact mode
+                    false,     // sort by name for stability
+                    Some(400), // cap children to avoid UI overload
+                );
+
+                // Ensure we don't emit a summary that shows "less" size than before
+                if summary.total_bytes >= last_emitted_bytes {
+                    last_emitted_bytes = summary.total_bytes;
+                    emit(ScanEvent::Progress(summary));
+                    last_emit = Instant::now();
+                }
+            }
+        }
+    }
+
+    let summary = build_summary(
+        &roots,
+        &children,
+        &files_by_parent,
+        &stats,
+        &largest_files,
+        start,
+        scan_id,
+        false, // full mode
+        true,  // sort by size for final view
+        None,
+    );
+    emit(ScanEvent::Complete(summary));
+    Ok(())
+}
+
+fn collect_scan_roots(path: &str, paths: Option<Vec<String>>) -> Vec<PathBuf> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    for value in std::iter::once(path.to_string()).chain(paths.unwrap_or_default()) {
+        let trimmed = value.trim();
+        if !trimmed.is_empty() {
+            candidates.push(PathBuf::from(trimmed));
+        }
+    }
+    dedupe_scan_roots(candidates)
+}
+
+// Drops roots that repeat or sit inside another root, keeping the caller's order.
+fn dedupe_scan_roots(candidates: Vec<PathBuf>) -> Vec<PathBuf> {
+    let resolved: Vec<PathBuf> = candidates
+        .iter()
+        .map(|path| fs::canonicalize(path).unwrap_or_else(|_| path.clone()))
+        .collect();
+    candidates
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| {
+            let current = &resolved[*index];
+            !resolved.iter().enumerate().any(|(other_index, other)| {
+                other_index != *index
+                    && current.starts_with(other)
+                    && (current != other || other_index < *index)
+            })
+        })
+        .map(|(_, path)| path)
+        .collect()
+}
+
+fn share_parallelism(parallelism: &Parallelism) -> Parallelism {
+    match parallelism {
+        Parallelism::RayonNewPool(threads) => {
+            match rayon::ThreadPoolBuilder::new()
+                .num_threads(*threads)
+                .build()
+            {
+                Ok(pool) => Parallelism::RayonExistingPool {
+                    pool: Arc::new(pool),
+                    busy_timeout: None,
+                },
+                Err(_) => parallelism.clone(),
+            }
+        }
+        other => other.clone(),
+    }
+}
+
+fn register_dir(
+    path: &Path,
+    stats: &mut HashMap<PathBuf, NodeStats>,
+    children: &mut HashMap<PathBuf, Vec<PathBuf>>,
+) {
+    stats.entry(path.to_path_buf()).or_default();
+    if let Some(parent) = path.parent() {
+        let parent_buf = parent.to_path_buf();
+        children
+            .entry(parent_buf.clone())
+            .or_default()
+            .push(path.to_path_buf());
+        stats.entry(parent_buf).or_default().direct_dirs += 1;
+    }
+}
+
+fn register_file(
+    file: ScanFile,
+    stats: &mut HashMap<PathBuf, NodeStats>,
+    files_by_parent: &mut HashMap<PathBuf, Vec<ScanFile>>,
+    largest_files: &mut Vec<ScanFile>,
+) {
+    let parent = match Path::new(&file.path).parent() {
+        Some(value) => value.to_path_buf(),
+        None => return,
+    };
+    update_largest_files(largest_files, &file, 100);
+    let parent_stats = stats.entry(parent.clone()).or_default();
+    parent_stats.direct_bytes += file.size_bytes;
+    parent_stats.direct_files += 1;
+    files_by_parent.entry(parent).or_default().push(file);
+}
+
+fn collapse_file(
+    anchor: PathBuf,
+    file: &ScanFile,
+    stats: &mut HashMap<PathBuf, NodeStats>,
+    largest_files: &mut Vec<ScanFile>,
+) {
+    update_largest_files(largest_files, file, 100);
+    let anchor_stats = stats.entry(anchor).or_default();
+    anchor_stats.direct_bytes += file.size_bytes;
+    anchor_stats.direct_files += 1;
+}
+
+fn detail_anchor(path: &Path, depth: usize, detail_depth: Option<usize>) -> Option<PathBuf> {
+    let limit = detail_depth?;
+    if depth <= limit {
+        return None;
+    }
+    path.ancestors().nth(depth - limit).map(Path::to_path_buf)
+}
+
+fn has_skipped_ancestor(path: &Path, anchor: &Path, skipped_dirs: &HashSet<PathBuf>) -> bool {
+    if skipped_dirs.is_empty() {
+        return false;
+    }
+    path.ancestors()
+        .skip(1)
+        .take_while(|ancestor| *ancestor != anchor)
+        .any(|ancestor| skipped_dirs.contains(ancestor))
+}
+
+fn read_archive_entries(
+    path: &Path,
+    kind: ArchiveKind,
+    archive_size: u64,
+    control: &ScanControl,
+) -> Result<Vec<ArchiveEntry>, String> {
+    let file = fs::File::open(path).map_err(|error| format!("archive-open-failed: {error}"))?;
+    let reader = BufReader::new(file);
+    let mut entries = match kind {
+        ArchiveKind::Zip => read_zip_entries(reader, control)?,
+        // A plain tarball can seek past entry data instead of reading through it.
+        ArchiveKind::Tar => {
+            let mut archive = tar::Archive::new(reader);
+            let iter = archive
+                .entries_with_seek()
+                .map_err(|error| format!("archive-read-failed: {error}"))?;
+            read_tar_entries(iter, control)?
+        }
+        ArchiveKind::TarGz => {
+            let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(reader));
+            let iter = archive
+                .entries()
+                .map_err(|error| format!("archive-read-failed: {error}"))?;
+            read_tar_entries(iter, control)?
+        }
+        ArchiveKind::TarZst => {
+            let decoder = zstd::stream::read::Decoder::with_buffer(reader)
+                .map_err(|error| format!("archive-open-failed: {error}"))?;
+            let mut archive = tar::Archive::new(decoder);
+            let iter = archive
+                .entries()
+                .map_err(|error| format!("archive-read-failed: {error}"))?;
+            read_tar_entries(iter, control)?
+        }
+    };
+    if matches!(kind, ArchiveKind::TarGz | ArchiveKind::TarZst) {
+        apportion_compressed_sizes(&mut entries, archive_size);
+    }
+    Ok(entries)
+}
+
+fn read_zip_entries<R: std::io::Read + std::io::Seek>(
+    reader: R,
+    control: &ScanControl,
+) -> Result<Vec<ArchiveEntry>, String> {
+    let mut archive =
+        zip::ZipArchive::new(reader).map_err(|error| format!("archive-read-failed: {error}"))?;
+    if archive.len() > MAX_ARCHIVE_ENTRIES {
+        return Err("archive-too-many-entries".to_string());
+    }
+    let mut entries = Vec::with_capacity(archive.len());
+    for index in 0..archive.len() {
+        control.wait_while_paused();
+        if control.is_cancelled() {
+            break;
+        }
+        // Raw access reads the central directory record without decompressing the entry.
+        let entry = archive
+            .by_index_raw(index)
+            .map_err(|error| format!("archive-read-failed: {error}"))?;
+        let modified = entry.last_modified().and_then(|time| {
+            civil_to_unix_millis(
+                time.year(),
+                time.month(),
+                time.day(),
+                time.hour(),
+                time.minute(),
+                time.second(),
+            )
+        });
+        entries.push(ArchiveEntry {
+            name: entry.name().to_string(),
+            is_dir: entry.is_dir(),
+            size_bytes: entry.size(),
+            compressed_bytes: Some(entry.compressed_size()),
+            modified,
+        });
+    }
+    Ok(entries)
+}
+
+fn read_tar_entries<R: std::io::Read>(
+    iter: tar::Entries<'_, R>,
+    control: &ScanControl,
+) -> Result<Vec<ArchiveEntry>, String> {
+    let mut entries = Vec::new();
+    for entry in iter {
+        control.wait_while_paused();
+        if control.is_cancelled() {
+            break;
+        }
+        let entry = entry.map_err(|error| format!("archive-read-failed: {error}"))?;
+        let header = entry.header();
+        let entry_type = header.entry_type();
+        if !entry_type.is_file() && !entry_type.is_dir() {
+            continue;
+        }
+        if entries.len() >= MAX_ARCHIVE_ENTRIES {
+            return Err("archive-too-many-entries".to_string());
+        }
+        let size_bytes = entry.size();
+        entries.push(ArchiveEntry {
+            name: String::from_utf8_lossy(&entry.path_bytes()).to_string(),
+            is_dir: entry_type.is_dir(),
+            size_bytes,
+            compressed_bytes: Some(size_bytes),
+            modified: header.mtime().ok().map(|secs| secs.saturating_mul(1000)),
+        });
+    }
+    Ok(entries)
+}
+
+// Compressed tarballs have no per-entry compressed size, so the archive size is
+// spread across entries in proportion to their uncompressed size.
+fn apportion_compressed_sizes(entries: &mut [ArchiveEntry], archive_size: u64) {
+    let total: u64 = entries.iter().map(|entry| entry.size_bytes).sum();
+    if total == 0 {
+        return;
+    }
+    for entry in entries.iter_mut() {
+        let share = entry.size_bytes as u128 * archive_size as u128 / total as u128;
+        entry.compressed_bytes = Some(share as u64);
+    }
+}
+
+fn expand_archive(
+    archive_path: &Path,
+    entries: Vec<ArchiveEntry>,
+    filters: &FilterConfig,
+) -> ArchiveListing {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    let mut files: Vec<ScanFile> = Vec::new();
+    let mut known_dirs: HashSet<PathBuf> = HashSet::new();
+    let mut skipped_dirs: HashSet<PathBuf> = HashSet::new();
+
+    for entry in entries {
+        let mut parts: Vec<&str> = entry
+            .name
+            .split(['/', '\\'])
+            .filter(|part| !part.is_empty() && *part != ".")
+            .collect();
+        if parts.is_empty() || parts.contains(&"..") {
+            continue;
+        }
+        let file_name = if entry.is_dir { None } else { parts.pop() };
+
+        let mut current = archive_path.to_path_buf();
+        let mut skipped = false;
+        for part in parts {
+            current.push(part);
+            if skipped_dirs.contains(&current) {
+                skipped = true;
+                break;
+            }
+            if known_dirs.insert(current.clone()) {
+                if should_skip_dir(archive_path, &current, filters) {
+                    skipped_dirs.insert(current.clone());
+                    skipped = true;
+                    break;
+                }
+                dirs.push(current.clone());
+            }
+        }
+        if skipped {
+            continue;
+        }
+
+        if let Some(name) = file_name {
+            let path = current.join(name);
+            if !should_include_file(&path, entry.size_bytes, entry.modified, filters) {
+                continue;
+            }
+            files.push(ScanFile {
+                path: get_path_string(&path),
+                name: name.to_string(),
+                size_bytes: entry.compressed_bytes.unwrap_or(entry.size_bytes),
+                modified: entry.modified,
+                uncompressed_bytes: Some(entry.size_bytes),
+            });
+        }
+    }
+
+    ArchiveListing { dirs, files }
+}
+
+fn civil_to_unix_millis(
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+) -> Option<u64> {
+    if !(1..=12).contains(&month) || day == 0 {
+        return None;
+    }
+    // Days from civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (i64::from(month) + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+    let seconds =
+        days * 86400 + i64::from(hour) * 3600 + i64::from(minute) * 60 + i64::from(second);
+    u64::try_from(seconds).ok().map(|value| value * 1000)
+}
+
+fn build_scan_config(options: &ScanOptions) -> Result<ScanConfig, String> {
+    let filters = build_filter_config(&options.filters)?;
+    let parallelism = resolve_parallelism(&options.priority_mode);
+    let (emit_every, emit_interval) = match options.priority_mode {
+        ScanPriorityMode::Performance => (5000, Duration::from_millis(500)),
+        ScanPriorityMode::Balanced => (10000, Duration::from_millis(1000)),
+        ScanPriorityMode::Low => (20000, Duration::from_millis(2000)),
+    };
+    let throttle = match options.throttle_level {
+        ScanThrottleLevel::Off => None,
+        ScanThrottleLevel::Low => Some(ThrottleConfig {
+            every_entries: 1200,
+            sleep_ms: 1,
+        }),
+        ScanThrottleLevel::Medium => Some(ThrottleConfig {
+            every_entries: 600,
+            sleep_ms: 3,
+        }),
+        ScanThrottleLevel::High => Some(ThrottleConfig {
+            every_entries: 250,
+            sleep_ms: 6,
+        }),
+        ScanThrottleLevel::Adaptive => None,
+    };
+    let adaptive_throttle = match options.throttle_level {
+        ScanThrottleLevel::Adaptive => {
+            let adaptive = options.adaptive_throttle.clone().unwrap_or_default();
+            if adaptive.target_latency_ms.is_some_and(|value| value <= 0.0) {
+                return Err("Adaptive throttle target latency must be positive".to_string());
+            }
+            Some(adaptive)
+        }
+        _ => None,
+    };
+    Ok(ScanConfig {
+        filters,
+        emit_every,
+        emit_interval,
+        throttle,
+        adaptive_throttle,
+        parallelism,
+        scan_archives: options.scan_archives,
+        max_depth: options.max_depth,
+        detail_depth: options.detail_depth,
+    })
+}
+
+fn build_filter_config(filters: &ScanFilters) -> Result<FilterConfig, String> {
+    if let (Some(min), Some(max)) = (filters.min_size_bytes, filters.max_size_bytes) {
+        if min > max {
+            return Err("Min size cannot exceed max size".to_string());
+        }
+    }
+    if let (Some(min), Some(max)) = (
+        filters.min_modified_timestamp,
+        filters.max_modified_timestamp,
+    ) {
+        if min > max {
+            return Err("Min modified timestamp cannot exceed max modified timestamp".to_string());
+        }
+    }
+    let include_regex = match &filters.include_regex {
+        Some(pattern) => Some(Regex::new(pattern).map_err(|err| err.to_string())?),
+        None => None,
+    };
+    let exclude_regex = match &filters.exclude_regex {
+        Some(pattern) => Some(Regex::new(pattern).map_err(|err| err.to_string())?),
+        None => None,
+    };
+    let include_extensions = normalize_extensions(&filters.include_extensions);
+    let exclude_extensions = normalize_extensions(&filters.exclude_extensions);
+    let include_names = normalize_list(&filters.include_names);
+    let exclude_names = normalize_list(&filters.exclude_names);
+    let include_paths = normalize_list(&filters.include_paths);
+    let exclude_paths = normalize_list(&filters.exclude_paths);
+    let has_include_extensions = !include_extensions.is_empty();
+    let has_exclude_extensions = !exclude_extensions.is_empty();
+    let has_include_names = !include_names.is_empty();
+    let has_exclude_names = !exclude_names.is_empty();
+    let has_include_paths = !include_paths.is_empty();
+    let has_exclude_paths = !exclude_paths.is_empty();
+    let has_include_regex = include_regex.is_some();
+    let has_exclude_regex = exclude_regex.is_some();
+    let has_includes =
+        has_include_extensions || has_include_names || has_include_paths || has_include_regex;
+    let has_dir_excludes = has_exclude_paths || has_exclude_names || has_exclude_regex;
+    let has_file_excludes = has_dir_excludes || has_exclude_extensions;
+    let needs_path =
+        has_exclude_paths || has_include_paths || has_include_regex || has_exclude_regex;
+    let needs_name = has_exclude_names || has_include_names;
+    let needs_extension = has_include_extensions || has_exclude_extensions;
+    Ok(FilterConfig {
+        include_extensions,
+        exclude_extensions,
+        include_names,
+        exclude_names,
+        min_size_bytes: filters.min_size_bytes,
+        max_size_bytes: filters.max_size_bytes,
+        min_modified_timestamp: filters.min_modified_timestamp,
+        max_modified_timestamp: filters.max_modified_timestamp,
+        include_regex,
+        exclude_regex,
+        include_paths,
+        exclude_paths,
+        flags: FilterFlags {
+            has_includes,
+            has_file_excludes,
+            has_dir_excludes,
+            needs_path,
+            needs_name,
+            needs_extension,
+        },
+    })
+}
+
+fn normalize_extensions(values: &[String]) -> HashSet<String> {
+    let mut set = HashSet::new();
+    for value in values {
+        let cleaned = value.trim().trim_start_matches('.').to_lowercase();
+        if !cleaned.is_empty() {
+            set.insert(cleaned);
+        }
+    }
+    set
+}
+
+fn normalize_list(values: &[String]) -> Vec<String> {
+    let mut list = Vec::new();
+    for value in values {
+        let cleaned = value.trim().to_lowercase();
+        if !cleaned.is_empty() {
+            list.push(cleaned);
+        }
+    }
+    list
+}
+
+fn should_emit_progress(processed: u64, last_emit: &Instant, config: &ScanConfig) -> bool {
+    if processed % config.emit_every == 0 {
+        return true;
+    }
+    last_emit.elapsed() >= config.emit_interval
+}
+
+fn get_path_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+fn compute_disk_usage(path: &Path) -> Result<DiskUsageSnapshot, String> {
+    if !path.exists() {
+        return Err("path-not-found".to_string());
+    }
+    let total_bytes =
+        fs2::total_space(path).map_err(|error| format!("disk-usage-failed: {error}"))?;
+    let free_bytes =
+        fs2::available_space(path).map_err(|error| format!("disk-usage-failed: {error}"))?;
+    Ok(DiskUsageSnapshot {
+        path: get_path_string(path),
+        total_bytes,
+        free_bytes,
+    })
+}
+
+fn get_entry_name_string(path: &Path) -> String {
+    path.file_name()
+        .map(|value| value.to_string_lossy().to_string())
+        .unwrap_or_else(|| get_path_string(path))
+}
+
+fn should_skip_dir(root: &Path, path: &Path, filters: &FilterConfig) -> bool {
+    if path == root {
+        return false;
+    }
+    if !filters.flags.has_dir_excludes {
+        return false;
+    }
+    let path_str = if filters.flags.needs_path {
+        Some(path.to_string_lossy().to_lowercase())
+    } else {
+        None
+    };
+    let name_str = if filters.flags.needs_name {
+        Some(get_entry_name_lower(path))
+    } else {
+        None
+    };
+    if let Some(path_value) = path_str.as_deref() {
+        if matches_regex(path_value, &filters.exclude_regex) {
+            return true;
+        }
+        if path_contains_any(path_value, &filters.exclude_paths) {
+            return true;
+        }
+    }
+    if let Some(name_value) = name_str.as_deref() {
+        return path_contains_any(name_value, &filters.exclude_names);
+    }
+    false
+}
+
+fn should_include_file(
+    path: &Path,
+    size_bytes: u64,
+    modified: Option<u64>,
+    filters: &FilterConfig,
+) -> bool {
+    if let Some(min_size) = filters.min_size_bytes {
+        if size_bytes < min_size {
+            return false;
+        }
+    }
+    if let Some(max_size) = filters.max_size_bytes {
+        if size_bytes > max_size {
+            return false;
+        }
+    }
+    if let Some(min_ts) = filters.min_modified_timestamp {
+        if modified.map_or(false, |ts| ts < min_ts) {
+            return false;
+        }
+    }
+    if let Some(max_ts) = filters.max_modified_timestamp {
+        if modified.map_or(false, |ts| ts > max_ts) {
+            return false;
+        }
+    }
+    let path_str = if filters.flags.needs_path {
+        Some(path.to_string_lossy().to_lowercase())
+    } else {
+        None
+    };
+    let name_str = if filters.flags.needs_name {
+        Some(get_entry_name_lower(path))
+    } else {
+        None
+    };
+    let ext = if filters.flags.needs_extension {
+        path.extension()
+            .and_then(|value| value.to_str())
+            .map(|value| value.to_lowercase())
+    } else {
+        None
+    };
+    if filters.flags.has_file_excludes {
+        if let Some(path_value) = path_str.as_deref() {
+            if matches_regex(path_value, &filters.exclude_regex) {
+                return false;
+            }
+            if path_contains_any(path_value, &filters.exclude_paths) {
+                return false;
+            }
+        }
+        if let Some(name_value) = name_str.as_deref() {
+            if path_contains_any(name_value, &filters.exclude_names) {
+                return false;
+            }
+        }
+        if let Some(ext_value) = ext.as_ref() {
+            if filters.exclude_extensions.contains(ext_value) {
+                return false;
+            }
+        }
+    }
+
+    if !filters.flags.has_includes {
+        return true;
+    }
+
+    if let Some(path_value) = path_str.as_deref() {
+        if matches_regex(path_value, &filters.include_regex) {
+            return true;
+        }
+        if path_contains_any(path_value, &filters.include_paths) {
+            return true;
+        }
+    }
+    if let Some(name_value) = name_str.as_deref() {
+        if path_contains_any(name_value, &filters.include_names) {
+            return true;
+        }
+    }
+    if let Some(ext_value) = ext.as_ref() {
+        return filters.include_extensions.contains(ext_value);
+    }
+
+    false
+}
+
+fn resolve_parallelism(priority_mode: &ScanPriorityMode) -> Parallelism {
+    let available = thread::available_parallelism()
+        .map(|value| value.get())
+        .unwrap_or(1);
+    let threads = match priority_mode {
+        ScanPriorityMode::Performance => available,
+        ScanPriorityMode::Balanced => (available + 1) / 2,
+        ScanPriorityMode::Low => 1,
+    };
+    if threads <= 1 {
+        return Parallelism::Serial;
+    }
+    Parallelism::RayonNewPool(threads)
+}
+
+fn matches_regex(value: &str, regex: &Option<Regex>) -> bool {
+    regex
+        .as_ref()
+        .map_or(false, |pattern| pattern.is_match(value))
+}
+
+fn path_contains_any(path: &str, values: &[String]) -> bool {
+    for value in values {
+        if value.is_empty() {
+            continue;
+        }
+        if path.contains(value) {
+            return true;
+        }
+    }
+    false
+}
+
+fn parse_runtime_options(
+    args: &[String],
+    startup_path: Option<String>,
+    settings: &AppSettings,
+) -> Result<RuntimeOptions, String> {
+    let headless = has_flag(args, "--headless")
+        || env_flag("DRAGABYTE_HEADLESS")
+        || settings.headless.unwrap_or(false);
+    let tcp = parse_tcp_config(args, settings)?;
+    let updater_enabled = resolve_updater_enabled(args, settings);
+    Ok(RuntimeOptions {
+        headless,
+        tcp,
+        startup_path,
+        updater_enabled,
+    })
+}
+
+fn parse_tcp_config(args: &[String], settings: &AppSettings) -> Result<Option<TcpConfig>, String> {
+    let bind_arg = get_arg_value(args, "--tcp-bind");
+    let env_bind = std::env::var("DRAGABYTE_TCP_BIND").ok();
+    let token = get_arg_value(args, "--tcp-token")
+        .or_else(|| std::env::var("DRAGABYTE_TCP_TOKEN").ok())
+        .or_else(|| settings.local_token.clone());
+    let api_tokens = settings.api_tokens.clone().unwrap_or_default();
+    let ws_bind_raw = get_arg_value(args, "--ws-bind")
+        .or_else(|| std::env::var("DRAGABYTE_WS_BIND").ok())
+        .or_else(|| settings.ws_bind.clone())
+        .filter(|value| !value.trim().is_empty());
+    let http_bind_raw = get_arg_value(args, "--http-bind")
+        .or_else(|| std::env::var("DRAGABYTE_HTTP_BIND").ok())
+        .or_else(|| settings.http_bind.clone())
+        .filter(|value| !value.trim().is_empty());
+    let socket_path = get_arg_value(args, "--socket")
+        .or_else(|| std::env::var("DRAGABYTE_SOCKET").ok())
+        .or_else(|| settings.socket_path.clone())
+        .filter(|value| !value.trim().is_empty())
+        .map(PathBuf::from);
+    let tcp_requested = has_flag(args, "--tcp")
+        || bind_arg.is_some()
+        || env_bind.is_some()
+        || settings.tcp_bind.is_some()
+        || ws_bind_raw.is_some()
+        || http_bind_raw.is_some()
+        || settings.local_token.is_some()
+        || !api_tokens.is_empty();
+    if !tcp_requested && socket_path.is_none() {
+        return Ok(None);
+    }
+    let bind_addr = if tcp_requested {
+        let bind_raw = bind_arg
+            .or_else(|| env_bind)
+            .or_else(|| settings.tcp_bind.clone())
+            .unwrap_or_else(|| "127.0.0.1:4799".to_string());
+        Some(
+            bind_raw
+                .parse::<SocketAddr>()
+                .map_err(|_| "Invalid TCP bind address".to_string())?,
+        )
+    } else {
+        None
+    };
+    let ws_bind = match ws_bind_raw {
+        Some(raw) => Some(
+            raw.trim()
+                .parse::<SocketAddr>()
+                .map_err(|_| "Invalid WebSocket bind address".to_string())?,
+        ),
+        None => None,
+    };
+    let http_bind = match http_bind_raw {
+        Some(raw) => Some(
+            raw.trim()
+                .parse::<SocketAddr>()
+                .map_err(|_| "Invalid HTTP bind address".to_string())?,
+        ),
+        None => None,
+    };
+    let exposed = [bind_addr, ws_bind, http_bind]
+        .into_iter()
+        .flatten()
+        .any(|address| !address.ip().is_loopback());
+    if exposed && token.is_none() && api_tokens.is_empty() {
+        return Err("DRAGABYTE_TCP_TOKEN is required when binding to non-loopback".to_string());
+    }
+    let mdns =
+        has_flag(args, "--mdns") || env_flag("DRAGABYTE_MDNS") || settings.mdns.unwrap_or(false);
+    if mdns && bind_addr.is_none_or(|address| address.ip().is_loopback()) {
+        return Err("mDNS advertising requires a non-loopback TCP bind address".to_string());
+    }
+    let ws_allowed_origins = match get_arg_value(args, "--ws-allowed-origins")
+        .or_else(|| std::env::var("DRAGABYTE_WS_ALLOWED_ORIGINS").ok())
+    {
+        Some(raw) => raw
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .collect(),
+        None => settings.ws_allowed_origins.clone().unwrap_or_default(),
+    };
+    let max_concurrent_scans = match get_arg_value(args, "--max-scans")
+        .or_else(|| std::env::var("DRAGABYTE_MAX_SCANS").ok())
+    {
+        Some(raw) => raw
+            .parse::<usize>()
+            .ok()
+            .filter(|value| *value > 0)
+            .ok_or_else(|| "Invalid maximum concurrent scans".to_string())?,
+        None => settings
+            .max_concurrent_scans
+            .filter(|value| *value > 0)
+            .unwrap_or(DEFAULT_MAX_CONCURRENT_SCANS),
+    };
+    let tls = parse_tls_server_paths(args, settings)?;
+    let allowed_roots = match get_arg_value(args, "--allowed-roots")
+        .or_else(|| std::env::var("DRAGABYTE_ALLOWED_ROOTS").ok())
+    {
+        Some(raw) => std::env::split_paths(&raw)
+            .filter(|path| !path.as_os_str().is_empty())
+            .map(|path| path.to_string_lossy().to_string())
+            .collect(),
+        None => settings.allowed_roots.clone().unwrap_or_default(),
+    };
+    let file_ops = has_flag(args, "--remote-file-ops")
+        || env_flag("DRAGABYTE_REMOTE_FILE_OPS")
+        || settings.remote_file_ops.unwrap_or(false);
+    let legacy_tokens = has_flag(args, "--allow-legacy-tokens")
+        || env_flag("DRAGABYTE_ALLOW_LEGACY_TOKENS")
+        || settings.allow_legacy_tokens.unwrap_or(false);
+    let audit_log = get_arg_value(args, "--audit-log")
+        .or_else(|| std::env::var("DRAGABYTE_AUDIT_LOG").ok())
+        .or_else(|| settings.audit_log_path.clone())
+        .filter(|value| !value.trim().is_empty())
+        .map(PathBuf::from);
+    let mut watch_paths: Vec<PathBuf> =
+        match get_arg_value(args, "--watch").or_else(|| std::env::var("DRAGABYTE_WATCH").ok()) {
+            Some(raw) => std::env::split_paths(&raw)
+                .filter(|path| !path.as_os_str().is_empty())
+                .collect(),
+            None => settings
+                .watch_paths
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .map(PathBuf::from)
+                .collect(),
+        };
+    let watch_interval = match get_arg_value(args, "--watch-interval")
+        .or_else(|| std::env::var("DRAGABYTE_WATCH_INTERVAL").ok())
+    {
+        Some(raw) => raw
+            .parse::<u64>()
+            .ok()
+            .filter(|value| *value > 0)
+            .map(Duration::from_secs)
+            .ok_or_else(|| "Invalid watch interval".to_string())?,
+        None => settings
+            .watch_interval_secs
+            .filter(|value| *value > 0)
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_WATCH_INTERVAL),
+    };
+    let alerts = parse_alert_config(args, settings)?;
+    // Growth is measured between scheduled scans, so those paths are watched too.
+    for rule in alerts.iter().flat_map(|config| &config.rules) {
+        let path = PathBuf::from(&rule.path);
+        if rule.max_growth_bytes.is_some() && !watch_paths.contains(&path) {
+            watch_paths.push(path);
+        }
+    }
+    Ok(Some(TcpConfig {
+        bind_addr,
+        token,
+        api_tokens,
+        allowed_roots,
+        audit_log,
+        file_ops,
+        legacy_tokens,
+        max_concurrent_scans,
+        tls,
+        ws_bind,
+        ws_allowed_origins,
+        http_bind,
+        socket_path,
+        mdns,
+        watch_paths,
+        watch_interval,
+        alerts,
+    }))
+}
+
+fn parse_alert_config(
+    args: &[String],
+    settings: &AppSettings,
+) -> Result<Option<AlertConfig>, String> {
+    let rules = settings.alert_rules.clone().unwrap_or_default();
+    if rules.is_empty() {
+        return Ok(None);
+    }
+    for rule in &rules {
+        if rule.path.trim().is_empty() {
+            return Err(format!("Alert rule {:?} has no path", rule.name));
+        }
+        if rule.min_free_percent.is_none() && rule.max_growth_bytes.is_none() {
+            return Err(format!(
+                "Alert rule {:?} needs minFreePercent or maxGrowthBytes",
+                rule.name
+            ));
+        }
+    }
+    let webhook = get_arg_value(args, "--alert-webhook")
+        .or_else(|| std::env::var("DRAGABYTE_ALERT_WEBHOOK").ok())
+        .or_else(|| settings.alert_webhook.clone())
+        .filter(|value| !value.trim().is_empty());
+    if let Some(url) = webhook.as_deref() {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err("Alert webhook must be an http or https URL".to_string());
+        }
+    }
+    let webhook_ca = get_arg_value(args, "--alert-webhook-ca")
+        .or_else(|| std::env::var("DRAGABYTE_ALERT_WEBHOOK_CA").ok())
+        .or_else(|| settings.alert_webhook_ca_path.clone())
+        .filter(|value| !value.trim().is_empty())
+        .map(PathBuf::from);
+    let command = get_arg_value(args, "--alert-command")
+        .or_else(|| std::env::var("DRAGABYTE_ALERT_COMMAND").ok())
+        .or_else(|| settings.alert_command.clone())
+        .filter(|value| !value.trim().is_empty());
+    let interval = match get_arg_value(args, "--alert-interval")
+        .or_else(|| std::env::var("DRAGABYTE_ALERT_INTERVAL").ok())
+    {
+        Some(raw) => raw
+            .parse::<u64>()
+            .ok()
+            .filter(|value| *value > 0)
+            .map(Duration::from_secs)
+            .ok_or_else(|| "Invalid alert interval".to_string())?,
+        None => settings
+            .alert_interval_secs
+            .filter(|value| *value > 0)
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_ALERT_INTERVAL),
+    };
+    Ok(Some(AlertConfig {
+        rules,
+        webhook,
+        webhook_ca,
+        command,
+        interval,
+    }))
+}
+
+fn parse_tls_server_paths(
+    args: &[String],
+    settings: &AppSettings,
+) -> Result<Option<TlsServerPaths>, String> {
+    let cert = get_arg_value(args, "--tls-cert")
+        .or_else(|| std::env::var("DRAGABYTE_TLS_CERT").ok())
+        .or_else(|| settings.tls_cert_path.clone());
+    let key = get_arg_value(args, "--tls-key")
+        .or_else(|| std::env::var("DRAGABYTE_TLS_KEY").ok())
+        .or_else(|| settings.tls_key_path.clone());
+    let client_ca = get_arg_value(args, "--tls-client-ca")
+        .or_else(|| std::env::var("DRAGABYTE_TLS_CLIENT_CA").ok())
+        .or_else(|| settings.tls_client_ca_path.clone());
+    match (cert, key) {
+        (Some(cert), Some(key)) => Ok(Some(TlsServerPaths {
+            cert: PathBuf::from(cert),
+            key: PathBuf::from(key),
+            client_ca: client_ca.map(PathBuf::from),
+        })),
+        (None, None) if client_ca.is_none() => Ok(None),
+        _ => Err("TLS requires both a certificate and a private key".to_string()),
+    }
+}
+
+fn resolve_updater_enabled(args: &[String], settings: &AppSettings) -> bool {
+    if has_flag(args, "--disable-updater") || env_flag("DRAGABYTE_DISABLE_UPDATER") {
+        return false;
+    }
+    settings.auto_update.unwrap_or(true)
+}
+
+fn env_flag(name: &str) -> bool {
+    match std::env::var(name) {
+        Ok(value) => matches!(value.to_lowercase().as_str(), "1" | "true" | "yes"),
+        Err(_) => false,
+    }
+}
+
+fn has_flag(args: &[String], flag: &str) -> bool {
+    args.iter().any(|value| value == flag)
+}
+
+fn get_arg_value(args: &[String], prefix: &str) -> Option<String> {
+    for value in args {
+        if let Some(stripped) = value.strip_prefix(&format!("{}=", prefix)) {
+            return Some(stripped.to_string());
+        }
+    }
+    None
+}
+
+struct RemoteServerHandle {
+    shutdown: mpsc::Sender<()>,
+    join: thread::JoinHandle<()>,
+}
+
+struct RemoteClientHandle {
+    // Swapped for a fresh writer each time the connection is re-established.
+    sender: Arc<Mutex<mpsc::Sender<String>>>,
+    shutdown: mpsc::Sender<()>,
+    join: thread::JoinHandle<()>,
+    address: String,
+    connected: Arc<AtomicBool>,
+    requests: Arc<RemoteRequestTracker>,
+}
+
+// Replies awaited by `remote_request`, keyed by the id assigned to each request, and the
+// partial files that resumed downloads append to.
+#[derive(Default)]
+struct RemoteRequestTracker {
+    pending: Mutex<HashMap<String, mpsc::Sender<JsonValue>>>,
+    resumes: Mutex<HashMap<String, PathBuf>>,
+}
+
+impl RemoteRequestTracker {
+    // Keeps the caller's id, or picks a random one so requests from several dashboards
+    // sharing a server never answer each other.
+    fn register(&self, id: Option<String>) -> Result<(String, mpsc::Receiver<JsonValue>), String> {
+        let id = match id {
+            Some(value) => value,
+            None => format!("rq-{}", &random_nonce()?[..16]),
+        };
+        let mut pending = self
+            .pending
+            .lock()
+            .map_err(|_| "Failed to lock pending requests".to_string())?;
+        if pending.contains_key(&id) {
+            return Err(format!("A request with id {id} is already pending"));
+        }
+        let (sender, receiver) = mpsc::channel();
+        pending.insert(id.clone(), sender);
+        Ok((id, receiver))
+    }
+
+    fn forget(&self, id: &str) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(id);
+        }
+    }
+
+    // Hands the first event carrying a pending id to its waiter. Later events with the
+    // same id, such as scan progress, are not claimed.
+    fn resolve(&self, value: &JsonValue) -> bool {
+        let Some(id) = value.get("id").and_then(|id| id.as_str()) else {
+            return false;
+        };
+        let Ok(mut pending) = self.pending.lock() else {
+            return false;
+        };
+        match pending.remove(id) {
+            Some(sender) => sender.send(value.clone()).is_ok(),
+            None => false,
+        }
+    }
+
+    // Waiters see their channel close and fail straight away.
+    fn abandon(&self) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.clear();
+        }
+        if let Ok(mut resumes) = self.resumes.lock() {
+            resumes.clear();
+        }
+    }
+
+    fn resume(&self, id: &str, path: PathBuf) -> Result<(), String> {
+        let mut resumes = self
+            .resumes
+            .lock()
+            .map_err(|_| "Failed to lock resumed downloads".to_string())?;
+        resumes.insert(id.to_string(), path);
+        Ok(())
+    }
+
+    fn take_resume(&self, id: &str) -> Option<PathBuf> {
+        self.resumes.lock().ok()?.remove(id)
+    }
+}
+
+// Everything needed to dial the same server again after the connection drops.
+struct RemoteClientTarget {
+    host: String,
+    address: String,
+    token: Option<String>,
+    tls: Option<RemoteTlsOptions>,
+    reconnect: bool,
+}
+
+type RemoteClientConnection = (BufReader<RemoteReadHalf>, RemoteWriteHalf);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteListEntry {
+    name: String,
+    path: String,
+    is_dir: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modified: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    permissions: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    readonly: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    symlink_target: Option<String>,
+}
+
+impl RemoteListEntry {
+    fn new(name: String, path: String, is_dir: bool) -> Self {
+        Self {
+            name,
+            path,
+            is_dir,
+            size_bytes: None,
+            modified: None,
+            permissions: None,
+            readonly: None,
+            symlink_target: None,
+        }
+    }
+
+    // Fills in the metadata without following symlinks.
+    fn with_details(mut self, path: &Path) -> Self {
+        let Ok(metadata) = fs::symlink_metadata(path) else {
+            return self;
+        };
+        if !self.is_dir {
+            self.size_bytes = Some(metadata.len());
+        }
+        self.modified = get_time_millis(metadata.modified());
+        self.permissions = format_permissions(&metadata);
+        self.readonly = Some(metadata.permissions().readonly());
+        if metadata.file_type().is_symlink() {
+            self.symlink_target = fs::read_link(path)
+                .ok()
+                .map(|target| target.to_string_lossy().to_string());
+        }
+        self
+    }
+}
+
+fn start_remote_server(config: TcpConfig, headless: bool) -> Result<RemoteServerHandle, String> {
+    let tls = match config.tls.as_ref() {
+        Some(paths) => Some(build_tls_server_config(paths)?),
+        None => None,
+    };
+    let mut listeners = Vec::new();
+    if let Some(address) = config.bind_addr {
+        eprintln!("[remote] starting tcp server on {address}");
+        let listener = TcpListener::bind(address)
+            .map_err(|error| format!("Failed to bind TCP server: {error}"))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|error| format!("Failed to configure TCP listener: {error}"))?;
+        listeners.push((listener, RemoteTransport::Ndjson));
+    }
+    if let Some(address) = config.ws_bind {
+        eprintln!("[remote] starting websocket server on {address}");
+        let ws_listener = TcpListener::bind(address)
+            .map_err(|error| format!("Failed to bind WebSocket server: {error}"))?;
+        ws_listener
+            .set_nonblocking(true)
+            .map_err(|error| format!("Failed to configure WebSocket listener: {error}"))?;
+        let origins = Arc::new(config.ws_allowed_origins.clone());
+        listeners.push((ws_listener, RemoteTransport::WebSocket(origins)));
+    }
+    if let Some(address) = config.http_bind {
+        eprintln!("[remote] starting http server on {address}");
+        let http_listener = TcpListener::bind(address)
+            .map_err(|error| format!("Failed to bind HTTP server: {error}"))?;
+        http_listener
+            .set_nonblocking(true)
+            .map_err(|error| format!("Failed to configure HTTP listener: {error}"))?;
+        listeners.push((http_listener, RemoteTransport::Http));
+    }
+    #[cfg(unix)]
+    let unix_listener = match config.socket_path.as_deref() {
+        Some(path) => {
+            eprintln!("[remote] starting unix socket server on {}", path.display());
+            Some(bind_unix_socket(path)?)
+        }
+        None => None,
+    };
+    #[cfg(not(unix))]
+    if config.socket_path.is_some() {
+        return Err("Unix domain sockets are not supported on this platform".to_string());
+    }
+    let audit = match config.audit_log.as_deref() {
+        Some(path) => Some(RemoteAuditLog::open(path)?),
+        None => None,
+    };
+    let (shutdown_tx, shutdown_rx) = mpsc::channel();
+    let mut hub = RemoteHub::new(
+        build_remote_tokens(config.token.as_deref(), &config.api_tokens),
+        canonical_roots(&config.allowed_roots, "sandbox"),
+        Some(shutdown_tx.clone()),
+        config.max_concurrent_scans,
+        audit,
+        config.file_ops,
+        config.watch_paths.clone(),
+    );
+    hub.legacy_tokens = config.legacy_tokens;
+    let hub = Arc::new(hub);
+    if !config.watch_paths.is_empty() {
+        spawn_watch_scheduler(Arc::clone(&hub), config.watch_interval);
+    }
+    if let Some(alerts) = config.alerts.clone() {
+        spawn_alert_evaluator(Arc::clone(&hub), alerts);
+    }
+    let advertiser = if config.mdns {
+        Some(advertise_remote_server(&config)?)
+    } else {
+        None
+    };
+    let join = thread::spawn(move || {
+        'accept: loop {
+            if shutdown_rx.try_recv().is_ok() {
+                break;
+            }
+            let mut idle = true;
+            for (listener, transport) in &listeners {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        idle = false;
+                        if hub.is_full() {
+                            eprintln!("[remote] connection limit reached, rejecting");
+                            continue;
+                        }
+                        eprintln!("[remote] tcp client accepted");
+                        let hub_clone = Arc::clone(&hub);
+                        let tls_clone = tls.clone();
+                        let transport = transport.clone();
+                        thread::spawn(move || {
+                            handle_client(stream, hub_clone, tls_clone, transport, headless)
+                        });
+                    }
+                    Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => {}
+                    Err(_) => break 'accept,
+                }
+            }
+            #[cfg(unix)]
+            if let Some(listener) = unix_listener.as_ref() {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        idle = false;
+                        if hub.is_full() {
+                            eprintln!("[remote] connection limit reached, rejecting");
+                        } else {
+                            let hub_clone = Arc::clone(&hub);
+                            thread::spawn(move || handle_unix_client(stream, hub_clone, headless));
+                        }
+                    }
+                    Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => {}
+                    Err(_) => break 'accept,
+                }
+            }
+            if idle {
+                thread::sleep(Duration::from_millis(50));
+            }
+        }
+        hub.closed.store(true, Ordering::Relaxed);
+        if let Some((daemon, fullname)) = advertiser {
+            withdraw_remote_advertisement(&daemon, &fullname);
+        }
+        #[cfg(unix)]
+        if let Some(path) = config.socket_path.as_deref() {
+            let _ = fs::remove_file(path);
+        }
+    });
+    Ok(RemoteServerHandle {
+        shutdown: shutdown_tx,
+        join,
+    })
+}
+
+// Publishes the TCP listener so dashboards on the same network can find it. The TXT
+// record tells clients what they will need to connect, never the token itself.
+fn advertise_remote_server(config: &TcpConfig) -> Result<(ServiceDaemon, String), String> {
+    let address = config
+        .bind_addr
+        .ok_or_else(|| "mDNS advertising requires a TCP listener".to_string())?;
+    let hostname = local_hostname();
+    let instance = hostname.split('.').next().unwrap_or(&hostname).to_string();
+    let label: String = instance
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '-' })
+        .collect();
+    let token_required = config.token.is_some() || !config.api_tokens.is_empty();
+    let mut properties = vec![
+        ("version", env!("CARGO_PKG_VERSION").to_string()),
+        ("protocol", REMOTE_PROTOCOL_VERSION.to_string()),
+        ("tls", config.tls.is_some().to_string()),
+        ("token", token_required.to_string()),
+    ];
+    if let Some(ws) = config.ws_bind {
+        properties.push(("ws", ws.port().to_string()));
+    }
+    if let Some(http) = config.http_bind {
+        properties.push(("http", http.port().to_string()));
+    }
+    let host_name = format!("{label}.local.");
+    let info = if address.ip().is_unspecified() {
+        ServiceInfo::new(
+            MDNS_SERVICE_TYPE,
+            &instance,
+            &host_name,
+            (),
+            address.port(),
+            &properties[..],
+        )
+        .map(ServiceInfo::enable_addr_auto)
+    } else {
+        ServiceInfo::new(
+            MDNS_SERVICE_TYPE,
+            &instance,
+            &host_name,
+            address.ip(),
+            address.port(),
+            &properties[..],
+        )
+    }
+    .map_err(|error| format!("Invalid mDNS service: {error}"))?;
+    let fullname = info.get_fullname().to_string();
+    let daemon =
+        ServiceDaemon::new().map_err(|error| format!("Failed to start mDNS responder: {error}"))?;
+    daemon
+        .register(info)
+        .map_err(|error| format!("Failed to advertise over mDNS: {error}"))?;
+    eprintln!("[remote] advertising {fullname} over mdns");
+    Ok((daemon, fullname))
+}
+
+// Sends a goodbye so browsers drop the host right away instead of waiting for the TTL.
+fn withdraw_remote_advertisement(daemon: &ServiceDaemon, fullname: &str) {
+    if let Ok(status) = daemon.unregister(fullname) {
+        let _ = status.recv_timeout(Duration::from_secs(1));
+    }
+    let _ = daemon.shutdown();
+}
+
+fn local_hostname() -> String {
+    #[cfg(unix)]
+    {
+        let mut buffer = [0u8; 256];
+        // SAFETY: the pointer and length describe `buffer`, which outlives the call, and
+        // gethostname writes at most that many bytes.
+        if unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) } == 0 {
+            let end = buffer
+                .iter()
+                .position(|byte| *byte == 0)
+                .unwrap_or(buffer.len());
+            let name = String::from_utf8_lossy(&buffer[..end]).trim().to_string();
+            if !name.is_empty() {
+                return name;
+            }
+        }
+    }
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| "dragabyte".to_string())
+}
+
+fn handle_client(
+    stream: TcpStream,
+    hub: Arc<RemoteHub>,
+    tls: Option<Arc<rustls::ServerConfig>>,
+    transport: RemoteTransport,
+    headless: bool,
+) {
+    eprintln!("[remote] tcp client connected");
+    let peer = stream.peer_addr().ok();
+    if let Err(error) = stream.set_read_timeout(Some(Duration::from_millis(200))) {
+        eprintln!("[remote] set read timeout failed: {error}");
+    }
+    let tls_session = match tls {
+        Some(config) => match rustls::ServerConnection::new(config) {
+            Ok(value) => Some(rustls::Connection::from(value)),
+            Err(error) => {
+                eprintln!("[remote] tls setup failed: {error}");
+                return;
+            }
+        },
+        None => None,
+    };
+    let (read_half, write_half) = match open_remote_stream(stream, tls_session) {
+        Ok(value) => value,
+        Err(error) => {
+            eprintln!("[remote] tls handshake failed: {error}");
+            return;
+        }
+    };
+    let (reader, write_half) = match transport {
+        RemoteTransport::Ndjson => (
+            RemoteLineReader::Ndjson(BufReader::new(read_half)),
+            write_half,
+        ),
+        RemoteTransport::WebSocket(origins) => {
+            match accept_websocket(read_half, write_half, &origins) {
+                Ok(value) => value,
+                Err(error) => {
+                    eprintln!("[remote] websocket handshake failed: {error}");
+                    return;
+                }
+            }
+        }
+        RemoteTransport::Http => {
+            serve_http_request(read_half, write_half, &hub, peer, headless);
+            return;
+        }
+    };
+    serve_remote_connection(reader, write_half, hub, peer, None, headless);
+}
+
+// Runs the protocol on an established connection. A `grant` authenticates the
+// connection up front, as for Unix socket peers.
+fn serve_remote_connection(
+    mut reader: RemoteLineReader,
+    mut write_half: RemoteWriteHalf,
+    hub: Arc<RemoteHub>,
+    peer: Option<SocketAddr>,
+    grant: Option<RemoteGrant>,
+    headless: bool,
+) {
+    if grant.is_none() && hub.auth_failures.is_blocked(peer) {
+        eprintln!("[remote] refusing rate-limited peer");
+        hub.audit(
+            peer,
+            None,
+            &RemoteAuditTarget::connection(),
+            &Err("rate-limited".to_string()),
+            Instant::now(),
+        );
+        let line = serde_json::json!({ "event": "error", "id": null, "message": "rate-limited" });
+        let _ = write_half.write_all(format!("{line}\n").as_bytes());
+        let _ = write_half.flush();
+        return;
+    }
+    let (session, receiver) = match RemoteSession::open(&hub, peer) {
+        Ok(value) => value,
+        Err(error) => {
+            eprintln!("[remote] dropping connection: {error}");
+            return;
+        }
+    };
+    let authenticated = grant.is_some();
+    if let Ok(mut slot) = session.grant.lock() {
+        *slot = grant;
+    }
+    let flow = Arc::clone(&session.flow);
+    thread::spawn(move || write_remote_lines(write_half, receiver, Some(flow)));
+    send_remote_event(
+        &session.sender,
+        serde_json::json!({
+            "event": "auth-challenge",
+            "nonce": session.nonce,
+            "required": !authenticated && hub.requires_auth(),
+        }),
+    );
+    loop {
+        let line = match reader.next_line() {
+            Ok(Some(value)) => {
+                eprintln!("[remote] read line bytes={}", value.len());
+                value
+            }
+            Ok(None) => break,
+            Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => continue,
+            Err(error) if error.kind() == std::io::ErrorKind::TimedOut => continue,
+            Err(error) => {
+                eprintln!("[remote] read line error: {error}");
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            eprintln!("[remote] read empty line");
+            continue;
+        }
+        handle_remote_line(&line, Arc::clone(&hub), &session, headless);
+    }
+    hub.unregister_client(session.id);
+}
+
+#[cfg(unix)]
+fn bind_unix_socket(path: &Path) -> Result<std::os::unix::net::UnixListener, String> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    // A socket left behind by an earlier run would make bind fail.
+    if let Ok(metadata) = fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(format!("{} exists and is not a socket", path.display()));
+        }
+        if UnixStream::connect(path).is_ok() {
+            return Err(format!("{} is already in use", path.display()));
+        }
+        fs::remove_file(path).map_err(|error| {
+            format!("Failed to remove stale socket {}: {error}", path.display())
+        })?;
+    }
+    let listener = UnixListener::bind(path)
+        .map_err(|error| format!("Failed to bind Unix socket {}: {error}", path.display()))?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+        .map_err(|error| format!("Failed to restrict Unix socket {}: {error}", path.display()))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|error| format!("Failed to configure Unix socket: {error}"))?;
+    Ok(listener)
+}
+
+// Only the user running Dragabyte (or root) may connect; no token is needed.
+#[cfg(unix)]
+fn handle_unix_client(stream: std::os::unix::net::UnixStream, hub: Arc<RemoteHub>, headless: bool) {
+    eprintln!("[remote] unix client connected");
+    let uid = match unix_peer_uid(&stream) {
+        Ok(value) => value,
+        Err(error) => {
+            eprintln!("[remote] peer credentials unavailable: {error}");
+            return;
+        }
+    };
+    // SAFETY: geteuid has no preconditions and cannot fail.
+    let own_uid = unsafe { libc::geteuid() };
+    if uid != own_uid && uid != 0 {
+        eprintln!("[remote] refusing unix peer uid {uid}");
+        hub.audit(
+            None,
+            Some(&format!("uid:{uid}")),
+            &RemoteAuditTarget::connection(),
+            &Err("unauthorized".to_string()),
+            Instant::now(),
+        );
+        return;
+    }
+    if let Err(error) = stream.set_read_timeout(Some(Duration::from_millis(200))) {
+        eprintln!("[remote] set read timeout failed: {error}");
+    }
+    let writer = match stream.try_clone() {
+        Ok(value) => value,
+        Err(error) => {
+            eprintln!("[remote] unix socket clone failed: {error}");
+            return;
+        }
+    };
+    let grant = RemoteGrant::full(&format!("uid:{uid}")).restrict_to(hub.allowed_roots.as_deref());
+    serve_remote_connection(
+        RemoteLineReader::Ndjson(BufReader::new(Box::new(stream))),
+        Box::new(writer),
+        hub,
+        None,
+        Some(grant),
+        headless,
+    );
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn unix_peer_uid(stream: &std::os::unix::net::UnixStream) -> std::io::Result<u32> {
+    use std::os::unix::io::AsRawFd;
+    let mut credentials = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut length = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    // SAFETY: the buffer and its length describe a valid `ucred` for SO_PEERCRED.
+    let result = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut credentials as *mut libc::ucred as *mut libc::c_void,
+            &mut length,
+        )
+    };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(credentials.uid)
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+fn unix_peer_uid(stream: &std::os::unix::net::UnixStream) -> std::io::Result<u32> {
+    use std::os::unix::io::AsRawFd;
+    let mut uid: libc::uid_t = 0;
+    let mut gid: libc::gid_t = 0;
+    // SAFETY: both out-pointers are valid for the duration of the call.
+    let result = unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(uid)
+}
+
+fn write_remote_lines<W: Write>(
+    mut stream: W,
+    receiver: mpsc::Receiver<String>,
+    flow: Option<Arc<RemoteFlowControl>>,
+) {
+    for line in receiver {
+        eprintln!("[remote] sending line bytes={}", line.len());
+        if let Err(error) = stream.write_all(line.as_bytes()) {
+            eprintln!("[remote] write failed: {error}");
+            break;
+        }
+        if let Err(error) = stream.flush() {
+            eprintln!("[remote] flush failed: {error}");
+            break;
+        }
+        if let Some(flow) = flow.as_ref() {
+            flow.written.fetch_add(line.len() as u64, Ordering::Relaxed);
+        }
+    }
+    if let Some(flow) = flow {
+        flow.closed.store(true, Ordering::Relaxed);
+    }
+}
+
+type RemoteReadHalf = Box<dyn Read + Send>;
+type RemoteWriteHalf = Box<dyn Write + Send>;
+
+// Splits a connection into independently owned read and write halves. With TLS the
+// handshake is completed up front and both halves share the session, each doing its own
+// socket I/O so a blocked read never holds up outgoing lines.
+fn open_remote_stream(
+    stream: TcpStream,
+    tls: Option<rustls::Connection>,
+) -> std::io::Result<(RemoteReadHalf, RemoteWriteHalf)> {
+    let writer_stream = stream.try_clone()?;
+    let mut session = match tls {
+        Some(value) => value,
+        None => return Ok((Box::new(stream), Box::new(writer_stream))),
+    };
+    let read_timeout = stream.read_timeout()?;
+    stream.set_read_timeout(Some(TLS_HANDSHAKE_TIMEOUT))?;
+    let mut handshake_stream = &stream;
+    while session.is_handshaking() {
+        session.complete_io(&mut handshake_stream)?;
+    }
+    stream.set_read_timeout(read_timeout)?;
+    session.set_buffer_limit(None);
+    let session = Arc::new(Mutex::new(session));
+    let reader = TlsStreamHalf {
+        socket: stream,
+        session: Arc::clone(&session),
+        backlog: Vec::new(),
+    };
+    let writer = TlsStreamHalf {
+        socket: writer_stream,
+        session,
+        backlog: Vec::new(),
+    };
+    Ok((Box::new(reader), Box::new(writer)))
+}
+
+struct TlsStreamHalf {
+    socket: TcpStream,
+    session: Arc<Mutex<rustls::Connection>>,
+    // Ciphertext read from the socket that the session has not accepted yet.
+    backlog: Vec<u8>,
+}
+
+fn lock_tls_session(
+    session: &Mutex<rustls::Connection>,
+) -> std::io::Result<std::sync::MutexGuard<'_, rustls::Connection>> {
+    session
+        .lock()
+        .map_err(|_| std::io::Error::other("TLS session lock poisoned"))
+}
+
+fn flush_tls(session: &mut rustls::Connection, socket: &mut TcpStream) -> std::io::Result<()> {
+    while session.wants_write() {
+        session.write_tls(socket)?;
+    }
+    Ok(())
+}
+
+impl Read for TlsStreamHalf {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            {
+                let mut socket = self.socket.try_clone()?;
+                let shared = Arc::clone(&self.session);
+                let mut session = lock_tls_session(&shared)?;
+                match session.reader().read(buf) {
+                    Ok(count) => return Ok(count),
+                    Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => {}
+                    Err(error) => return Err(error),
+                }
+                if !self.backlog.is_empty() {
+                    let mut pending = self.backlog.as_slice();
+                    let accepted = session.read_tls(&mut pending)?;
+                    self.backlog.drain(..accepted);
+                    session.process_new_packets().map_err(|error| {
+                        std::io::Error::new(std::io::ErrorKind::InvalidData, error)
+                    })?;
+                    flush_tls(&mut session, &mut socket)?;
+                    continue;
+                }
+            }
+            let mut chunk = [0u8; 16 * 1024];
+            let count = self.socket.read(&mut chunk)?;
+            if count == 0 {
+                return Ok(0);
+            }
+            self.backlog.extend_from_slice(&chunk[..count]);
+        }
+    }
+}
+
+impl Write for TlsStreamHalf {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut socket = self.socket.try_clone()?;
+        let mut session = lock_tls_session(&self.session)?;
+        session.writer().write_all(buf)?;
+        flush_tls(&mut session, &mut socket)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let mut socket = self.socket.try_clone()?;
+        let mut session = lock_tls_session(&self.session)?;
+        flush_tls(&mut session, &mut socket)
+    }
+}
+
+enum RemoteLineReader {
+    Ndjson(BufReader<RemoteReadHalf>),
+    WebSocket(WebSocketReader),
+}
+
+impl RemoteLineReader {
+    fn next_line(&mut self) -> std::io::Result<Option<String>> {
+        match self {
+            RemoteLineReader::Ndjson(reader) => read_secure_line(reader, MAX_LINE_LENGTH),
+            RemoteLineReader::WebSocket(reader) => reader.next_message(),
+        }
+    }
+}
+
+// Completes the HTTP upgrade and wraps both halves so each protocol line travels as
+// one WebSocket message.
+fn accept_websocket(
+    mut read_half: RemoteReadHalf,
+    mut write_half: RemoteWriteHalf,
+    allowed_origins: &[String],
+) -> std::io::Result<(RemoteLineReader, RemoteWriteHalf)> {
+    let (request, leftover) = read_http_head(&mut read_half)?;
+    let response = match websocket_accept_key(&request, allowed_origins) {
+        Ok(accept) => format!(
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {accept}\r\n\r\n"
+        ),
+        Err(status) => {
+            let response = format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+            let _ = write_half.write_all(response.as_bytes());
+            let _ = write_half.flush();
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, status));
+        }
+    };
+    write_half.write_all(response.as_bytes())?;
+    write_half.flush()?;
+    let writer = Arc::new(Mutex::new(write_half));
+    let reader = WebSocketReader {
+        stream: read_half,
+        writer: Arc::clone(&writer),
+        buffer: leftover,
+        message: Vec::new(),
+    };
+    Ok((
+        RemoteLineReader::WebSocket(reader),
+        Box::new(WebSocketWriter { inner: writer }),
+    ))
+}
+
+// Reads up to the blank line ending an HTTP request head, returning the head and any
+// bytes received after it.
+fn read_http_head(reader: &mut RemoteReadHalf) -> std::io::Result<(String, Vec<u8>)> {
+    let deadline = Instant::now() + TLS_HANDSHAKE_TIMEOUT;
+    let mut buffer = Vec::new();
+    loop {
+        if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            let leftover = buffer.split_off(end + 4);
+            let head = String::from_utf8(buffer)
+                .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+            return Ok((head, leftover));
+        }
+        if buffer.len() > MAX_WS_HANDSHAKE_BYTES {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "HTTP request too large",
+            ));
+        }
+        let mut chunk = [0u8; 1024];
+        match reader.read(&mut chunk) {
+            Ok(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+            Ok(count) => buffer.extend_from_slice(&chunk[..count]),
+            Err(error)
+                if matches!(
+                    error.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) && Instant::now() < deadline => {}
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+// Validates an upgrade request and returns the `Sec-WebSocket-Accept` value, or the
+// HTTP status to refuse it with.
+fn websocket_accept_key(request: &str, allowed_origins: &[String]) -> Result<String, &'static str> {
+    let mut lines = request.split("\r\n");
+    let request_line = lines.next().unwrap_or_default();
+    if !request_line.starts_with("GET ") {
+        return Err("405 Method Not Allowed");
+    }
+    let mut headers: HashMap<String, &str> = HashMap::new();
+    for line in lines {
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim());
+        }
+    }
+    let header_has = |name: &str, token: &str| {
+        headers.get(name).is_some_and(|value| {
+            value
+                .split(',')
+                .any(|part| part.trim().eq_ignore_ascii_case(token))
+        })
+    };
+    if !header_has("upgrade", "websocket") || !header_has("connection", "upgrade") {
+        return Err("426 Upgrade Required");
+    }
+    if headers.get("sec-websocket-version").copied() != Some("13") {
+        return Err("400 Bad Request");
+    }
+    // Browsers always send an Origin; other clients usually do not.
+    if let Some(origin) = headers.get("origin") {
+        if !allowed_origins.iter().any(|allowed| allowed == origin) {
+            eprintln!("[remote] websocket origin refused: {origin}");
+            return Err("403 Forbidden");
+        }
+    }
+    let key = headers
+        .get("sec-websocket-key")
+        .filter(|value| !value.is_empty())
+        .ok_or("400 Bad Request")?;
+    let digest = ring::digest::digest(
+        &ring::digest::SHA1_FOR_LEGACY_USE_ONLY,
+        format!("{key}{WS_ACCEPT_GUID}").as_bytes(),
+    );
+    Ok(BASE64_STANDARD.encode(digest.as_ref()))
+}
+
+struct WebSocketReader {
+    stream: RemoteReadHalf,
+    // Shared with the outgoing writer so control frames can be answered.
+    writer: Arc<Mutex<RemoteWriteHalf>>,
+    // Bytes received but not yet parsed into frames; kept across read timeouts.
+    buffer: Vec<u8>,
+    // Payload of a fragmented message received so far.
+    message: Vec<u8>,
+}
+
+struct WebSocketFrame {
+    fin: bool,
+    opcode: u8,
+    payload: Vec<u8>,
+}
+
+impl WebSocketReader {
+    fn next_message(&mut self) -> std::io::Result<Option<String>> {
+        loop {
+            while let Some(frame) = self.take_frame()? {
+                match frame.opcode {
+                    0x0..=0x2 => {
+                        if self.message.len() + frame.payload.len() > MAX_LINE_LENGTH as usize {
+                            return Err(std::io::Error::new(
+                                std::io::ErrorKind::InvalidData,
+                                "Message too long",
+                            ));
+                        }
+                        self.message.extend_from_slice(&frame.payload);
+                        if frame.fin {
+                            let message = std::mem::take(&mut self.message);
+                            return String::from_utf8(message).map(Some).map_err(|error| {
+                                std::io::Error::new(std::io::ErrorKind::InvalidData, error)
+                            });
+                        }
+                    }
+                    0x8 => {
+                        let _ = self.send_control(0x8, &frame.payload);
+                        return Ok(None);
+                    }
+                    0x9 => self.send_control(0xA, &frame.payload)?,
+                    0xA => {}
+                    _ => {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            "Unknown WebSocket opcode",
+                        ))
+                    }
+                }
+            }
+            let mut chunk = [0u8; 16 * 1024];
+            let count = self.stream.read(&mut chunk)?;
+            if count == 0 {
+                return Ok(None);
+            }
+            self.buffer.extend_from_slice(&chunk[..count]);
+        }
+    }
+
+    // Parses one complete frame off the front of the buffer, if there is one.
+    fn take_frame(&mut self) -> std::io::Result<Option<WebSocketFrame>> {
+        let invalid = |message: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+        if self.buffer.len() < 2 {
+            return Ok(None);
+        }
+        let fin = self.buffer[0] & 0x80 != 0;
+        let opcode = self.buffer[0] & 0x0F;
+        if self.buffer[1] & 0x80 == 0 {
+            return Err(invalid("Client frames must be masked"));
+        }
+        let (length, mut offset) = match self.buffer[1] & 0x7F {
+            126 if self.buffer.len() >= 4 => (
+                u16::from_be_bytes([self.buffer[2], self.buffer[3]]) as u64,
+                4,
+            ),
+            127 if self.buffer.len() >= 10 => {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(&self.buffer[2..10]);
+                (u64::from_be_bytes(bytes), 10)
+            }
+            126 | 127 => return Ok(None),
+            value => (value as u64, 2),
+        };
+        if length > MAX_LINE_LENGTH {
+            return Err(invalid("Frame too long"));
+        }
+        // Control frames carry at most 125 bytes and are never fragmented.
+        if opcode & 0x8 != 0 && (length > 125 || !fin) {
+            return Err(invalid("Invalid WebSocket control frame"));
+        }
+        let length = length as usize;
+        if self.buffer.len() < offset + 4 + length {
+            return Ok(None);
+        }
+        let mut mask = [0u8; 4];
+        mask.copy_from_slice(&self.buffer[offset..offset + 4]);
+        offset += 4;
+        let payload = self.buffer[offset..offset + length]
+            .iter()
+            .enumerate()
+            .map(|(index, byte)| byte ^ mask[index % 4])
+            .collect();
+        self.buffer.drain(..offset + length);
+        Ok(Some(WebSocketFrame {
+            fin,
+            opcode,
+            payload,
+        }))
+    }
+
+    fn send_control(&self, opcode: u8, payload: &[u8]) -> std::io::Result<()> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| std::io::Error::other("WebSocket writer lock poisoned"))?;
+        write_websocket_frame(&mut *writer, opcode, payload)
+    }
+}
+
+// Sends every write as one text message, minus the NDJSON line terminator.
+struct WebSocketWriter {
+    inner: Arc<Mutex<RemoteWriteHalf>>,
+}
+
+impl Write for WebSocketWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let payload = buf.strip_suffix(b"\n").unwrap_or(buf);
+        let mut writer = self
+            .inner
+            .lock()
+            .map_err(|_| std::io::Error::other("WebSocket writer lock poisoned"))?;
+        write_websocket_frame(&mut *writer, 0x1, payload)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn write_websocket_frame(
+    writer: &mut dyn Write,
+    opcode: u8,
+    payload: &[u8],
+) -> std::io::Result<()> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        length if length < 126 => frame.push(length as u8),
+        length if length <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    writer.write_all(&frame)?;
+    writer.flush()
+}
+
+// Answers one HTTP request by translating it into a protocol request on a short-lived
+// session, so tokens, scopes, sandboxing and auditing match the NDJSON protocol.
+fn serve_http_request(
+    mut reader: RemoteReadHalf,
+    mut writer: RemoteWriteHalf,
+    hub: &Arc<RemoteHub>,
+    peer: Option<SocketAddr>,
+    headless: bool,
+) {
+    let request = match read_http_request(&mut reader) {
+        Ok(value) => value,
+        Err(error) => {
+            eprintln!("[remote] http request error: {error}");
+            let _ = write_http_error(&mut writer, "400 Bad Request", "invalid-request");
+            return;
+        }
+    };
+    eprintln!("[remote] http {} {}", request.method, request.path);
+    let route = match route_http_request(&request) {
+        Ok(value) => value,
+        Err((status, code)) => {
+            let _ = write_http_error(&mut writer, status, code);
+            return;
+        }
+    };
+    let (body, stream) = match route {
+        HttpRoute::OpenApi => {
+            let _ = write_http_response(
+                &mut writer,
+                "200 OK",
+                "application/json",
+                OPENAPI_SPEC.as_bytes(),
+            );
+            return;
+        }
+        HttpRoute::Request(body) => (body, None),
+        HttpRoute::Events { body, scan } => (body, Some(scan)),
+    };
+    let (session, receiver) = match RemoteSession::open(hub, peer) {
+        Ok(value) => value,
+        Err(error) => {
+            eprintln!("[remote] dropping http request: {error}");
+            let _ = write_http_error(&mut writer, "500 Internal Server Error", "internal");
+            return;
+        }
+    };
+    // The bearer token authenticates the session, like the handshake does on a connection.
+    let authorized = match request.bearer_token() {
+        Some(token) => authenticate_session_token(hub, &session, &token)
+            .map(|grant| store_session_grant(&session, grant)),
+        None => Ok(()),
+    };
+    match authorized {
+        Ok(()) => handle_remote_line(&body.to_string(), Arc::clone(hub), &session, headless),
+        Err(code) => {
+            hub.audit(
+                peer,
+                None,
+                &RemoteAuditTarget::connection(),
+                &Err(code.to_string()),
+                Instant::now(),
+            );
+            send_remote_error(&session.sender, body["id"].as_str(), code);
+        }
+    }
+    let result = match receiver.recv_timeout(HTTP_RESPONSE_TIMEOUT) {
+        Ok(line) => {
+            let event = serde_json::from_str::<JsonValue>(&line).unwrap_or_default();
+            match (http_status_for_event(&event), stream) {
+                ("200 OK", Some(scan)) => {
+                    let finished = scan.as_deref().is_some_and(|id| {
+                        !matches!(
+                            hub.scan_state(id),
+                            Some(RemoteScanState::Queued | RemoteScanState::Running)
+                        )
+                    });
+                    stream_http_events(&mut writer, &receiver, &line, scan.is_some(), finished)
+                }
+                // Served as plain text so Prometheus can scrape it directly.
+                ("200 OK", None) if event["event"] == "metrics" => write_http_response(
+                    &mut writer,
+                    "200 OK",
+                    PROMETHEUS_CONTENT_TYPE,
+                    event["data"].as_str().unwrap_or_default().as_bytes(),
+                ),
+                (status, _) => write_http_response(
+                    &mut writer,
+                    status,
+                    "application/json",
+                    line.trim().as_bytes(),
+                ),
+            }
+        }
+        Err(_) => write_http_error(&mut writer, "504 Gateway Timeout", "timeout"),
+    };
+    if let Err(error) = result {
+        eprintln!("[remote] http write failed: {error}");
+    }
+    hub.unregister_client(session.id);
+}
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl HttpRequest {
+    fn bearer_token(&self) -> Option<String> {
+        self.headers
+            .get("authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|token| token.trim().to_string())
+    }
+}
+
+enum HttpRoute {
+    OpenApi,
+    Request(JsonValue),
+    // `scan` is set when the stream follows a single scan.
+    Events {
+        body: JsonValue,
+        scan: Option<String>,
+    },
+}
+
+fn read_http_request(reader: &mut RemoteReadHalf) -> std::io::Result<HttpRequest> {
+    let invalid = |message: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+    let (head, mut body) = read_http_head(reader)?;
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line
+        .next()
+        .ok_or_else(|| invalid("Missing request target"))?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut headers = HashMap::new();
+    for line in lines {
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+    let length = match headers.get("content-length") {
+        Some(value) => value
+            .parse::<usize>()
+            .map_err(|_| invalid("Invalid Content-Length"))?,
+        None => 0,
+    };
+    if length > MAX_HTTP_BODY_BYTES {
+        return Err(invalid("Request body too large"));
+    }
+    let deadline = Instant::now() + TLS_HANDSHAKE_TIMEOUT;
+    while body.len() < length {
+        let mut chunk = [0u8; 16 * 1024];
+        match reader.read(&mut chunk) {
+            Ok(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+            Ok(count) => body.extend_from_slice(&chunk[..count]),
+            Err(error)
+                if matches!(
+                    error.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) && Instant::now() < deadline => {}
+            Err(error) => return Err(error),
+        }
+    }
+    body.truncate(length);
+    Ok(HttpRequest {
+        method,
+        path: percent_decode(path),
+        query: query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (percent_decode(name), percent_decode(value))
+            })
+            .collect(),
+        headers,
+        body,
+    })
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => decoded.push(b' '),
+            b'%' if index + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[index + 1..index + 3]).unwrap_or_default();
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        decoded.push(byte);
+                        index += 2;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+// Maps a method and path to the protocol request it stands for.
+fn route_http_request(request: &HttpRequest) -> Result<HttpRoute, (&'static str, &'static str)> {
+    let segments: Vec<&str> = request
+        .path
+        .trim_matches('/')
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    let query = |name: &str| request.query.get(name).cloned();
+    let body = match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["openapi.json"]) => return Ok(HttpRoute::OpenApi),
+        ("GET", ["metrics"]) => serde_json::json!({ "action": "metrics" }),
+        ("GET", ["api", "v1", "ping"]) => serde_json::json!({ "action": "ping" }),
+        ("GET", ["api", "v1", "hello"]) => serde_json::json!({ "action": "hello" }),
+        ("GET", ["api", "v1", "disk"]) => {
+            serde_json::json!({ "action": "disk", "path": query("path") })
+        }
+        ("GET", ["api", "v1", "read"]) => {
+            serde_json::json!({ "action": "read", "path": query("path") })
+        }
+        ("GET", ["api", "v1", "list"]) => {
+            let mut body = serde_json::json!({ "action": "list", "path": query("path") });
+            for name in ["includeFiles", "details", "descending"] {
+                if let Some(value) = query(name) {
+                    body[name] = JsonValue::Bool(matches!(value.as_str(), "1" | "true" | "yes"));
+                }
+            }
+            for name in ["offset", "limit"] {
+                if let Some(value) = query(name) {
+                    let number = value
+                        .parse::<u64>()
+                        .map_err(|_| ("400 Bad Request", "invalid-query"))?;
+                    body[name] = JsonValue::from(number);
+                }
+            }
+            if let Some(sort) = query("sort") {
+                body["sort"] = JsonValue::String(sort);
+            }
+            body
+        }
+        ("GET", ["api", "v1", "scans"]) => serde_json::json!({ "action": "status" }),
+        ("POST", ["api", "v1", "scans"]) => {
+            let mut body: JsonValue = serde_json::from_slice(&request.body)
+                .map_err(|_| ("400 Bad Request", "invalid_json"))?;
+            if !body.is_object() {
+                return Err(("400 Bad Request", "invalid_json"));
+            }
+            body["action"] = JsonValue::from("scan");
+            body
+        }
+        ("GET", ["api", "v1", "scans", id]) => serde_json::json!({ "action": "status", "id": id }),
+        ("DELETE", ["api", "v1", "scans", id]) => {
+            serde_json::json!({ "action": "cancel", "id": id })
+        }
+        ("GET", ["api", "v1", "scans", id, "result"]) => {
+            serde_json::json!({ "action": "result", "id": id })
+        }
+        ("GET", ["api", "v1", "scans", id, "events"]) => {
+            return Ok(HttpRoute::Events {
+                body: serde_json::json!({ "action": "subscribe", "id": id }),
+                scan: Some(id.to_string()),
+            })
+        }
+        ("GET", ["api", "v1", "events"]) => {
+            return Ok(HttpRoute::Events {
+                body: serde_json::json!({ "action": "subscribe" }),
+                scan: None,
+            })
+        }
+        _ => return Err(("404 Not Found", "not-found")),
+    };
+    Ok(HttpRoute::Request(body))
+}
+
+fn http_status_for_event(event: &JsonValue) -> &'static str {
+    let name = event
+        .get("event")
+        .and_then(JsonValue::as_str)
+        .unwrap_or_default();
+    let code = event
+        .get("message")
+        .and_then(JsonValue::as_str)
+        .unwrap_or_default();
+    match name {
+        "error" | "list-error" | "disk-error" => match code {
+            "unauthorized" => "401 Unauthorized",
+            "insufficient-scope" | "forbidden-path" | "shutdown-not-allowed" => "403 Forbidden",
+            "path-not-found" | "scan-not-found" => "404 Not Found",
+            "file-too-large" => "413 Payload Too Large",
+            "scan-id-in-use" | "scan-id-reserved" | "scan-not-finished" | "scan-has-no-result" => {
+                "409 Conflict"
+            }
+            "rate-limited" => "429 Too Many Requests",
+            "scan-queue-full" => "503 Service Unavailable",
+            _ => "400 Bad Request",
+        },
+        "no-active-scan" => "404 Not Found",
+        "scan-started" | "scan-queued" => "202 Accepted",
+        _ => "200 OK",
+    }
+}
+
+// Relays events as Server-Sent Events, starting with `first`. A stream for a single
+// scan ends once that scan finishes, or right away if it had already finished.
+fn stream_http_events(
+    writer: &mut RemoteWriteHalf,
+    receiver: &mpsc::Receiver<String>,
+    first: &str,
+    single_scan: bool,
+    already_finished: bool,
+) -> std::io::Result<()> {
+    writer.write_all(
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+    )?;
+    let mut line = first.to_string();
+    loop {
+        let value = serde_json::from_str::<JsonValue>(&line).unwrap_or_default();
+        let name = value
+            .get("event")
+            .and_then(JsonValue::as_str)
+            .unwrap_or("message");
+        writer.write_all(format!("event: {name}\ndata: {}\n\n", line.trim()).as_bytes())?;
+        writer.flush()?;
+        if single_scan && matches!(name, "scan-complete" | "scan-error" | "scan-cancelled") {
+            return Ok(());
+        }
+        if already_finished {
+            match receiver.try_recv() {
+                Ok(next) => {
+                    line = next;
+                    continue;
+                }
+                Err(_) => return Ok(()),
+            }
+        }
+        line = loop {
+            match receiver.recv_timeout(SSE_KEEPALIVE_INTERVAL) {
+                Ok(next) => break next,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    // Also detects clients that went away.
+                    writer.write_all(b": keep-alive\n\n")?;
+                    writer.flush()?;
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        };
+    }
+}
+
+fn write_http_response(
+    writer: &mut RemoteWriteHalf,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> std::io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    writer.write_all(head.as_bytes())?;
+    writer.write_all(body)?;
+    writer.flush()
+}
+
+fn write_http_error(writer: &mut RemoteWriteHalf, status: &str, code: &str) -> std::io::Result<()> {
+    let body = serde_json::json!({ "event": "error", "id": null, "message": code });
+    write_http_response(
+        writer,
+        status,
+        "application/json",
+        body.to_string().as_bytes(),
+    )
+}
+
+fn tls_crypto_provider() -> Arc<rustls::crypto::CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+fn load_pem_certs(path: &Path) -> Result<Vec<rustls::pki_types::CertificateDer<'static>>, String> {
+    use rustls::pki_types::pem::PemObject;
+    let certs = rustls::pki_types::CertificateDer::pem_file_iter(path)
+        .and_then(|iter| iter.collect::<Result<Vec<_>, _>>())
+        .map_err(|error| format!("Failed to read certificate {}: {error}", path.display()))?;
+    if certs.is_empty() {
+        return Err(format!("No certificates found in {}", path.display()));
+    }
+    Ok(certs)
+}
+
+fn load_pem_key(path: &Path) -> Result<rustls::pki_types::PrivateKeyDer<'static>, String> {
+    use rustls::pki_types::pem::PemObject;
+    rustls::pki_types::PrivateKeyDer::from_pem_file(path)
+        .map_err(|error| format!("Failed to read private key {}: {error}", path.display()))
+}
+
+fn load_root_store(path: &Path) -> Result<rustls::RootCertStore, String> {
+    let mut roots = rustls::RootCertStore::empty();
+    for cert in load_pem_certs(path)? {
+        roots
+            .add(cert)
+            .map_err(|error| format!("Invalid CA certificate {}: {error}", path.display()))?;
+    }
+    Ok(roots)
+}
+
+// The CAs the operating system trusts: the system store on Windows and macOS, the
+// distribution's bundle on Linux.
+fn native_root_store() -> Result<rustls::RootCertStore, String> {
+    let loaded = rustls_native_certs::load_native_certs();
+    for error in &loaded.errors {
+        eprintln!("[remote] skipping system certificates: {error}");
+    }
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add_parsable_certificates(loaded.certs);
+    if roots.is_empty() {
+        return Err("No trusted CA certificates found on this system".to_string());
+    }
+    Ok(roots)
+}
+
+fn build_tls_server_config(paths: &TlsServerPaths) -> Result<Arc<rustls::ServerConfig>, String> {
+    let provider = tls_crypto_provider();
+    let builder = rustls::ServerConfig::builder_with_provider(Arc::clone(&provider))
+        .with_safe_default_protocol_versions()
+        .map_err(|error| format!("Failed to configure TLS: {error}"))?;
+    let builder = match paths.client_ca.as_ref() {
+        Some(ca_path) => {
+            let roots = Arc::new(load_root_store(ca_path)?);
+            let verifier =
+                rustls::server::WebPkiClientVerifier::builder_with_provider(roots, provider)
+                    .build()
+                    .map_err(|error| format!("Failed to configure client verification: {error}"))?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let config = builder
+        .with_single_cert(load_pem_certs(&paths.cert)?, load_pem_key(&paths.key)?)
+        .map_err(|error| format!("Invalid TLS certificate or key: {error}"))?;
+    Ok(Arc::new(config))
+}
+
+fn build_tls_client_config(
+    options: &RemoteTlsOptions,
+) -> Result<Arc<rustls::ClientConfig>, String> {
+    let provider = tls_crypto_provider();
+    let pin = match options.pin_sha256.as_deref() {
+        Some(value) => Some(parse_fingerprint(value)?),
+        None => None,
+    };
+    let ca_verifier = match options.ca_cert.as_deref() {
+        Some(path) => {
+            let roots = Arc::new(load_root_store(Path::new(path))?);
+            let verifier = rustls::client::WebPkiServerVerifier::builder_with_provider(
+                roots,
+                Arc::clone(&provider),
+            )
+            .build()
+            .map_err(|error| format!("Failed to configure server verification: {error}"))?;
+            Some(verifier)
+        }
+        None => None,
+    };
+    let builder = rustls::ClientConfig::builder_with_provider(Arc::clone(&provider))
+        .with_safe_default_protocol_versions()
+        .map_err(|error| format!("Failed to configure TLS: {error}"))?;
+    let builder = match (pin, ca_verifier) {
+        (None, Some(verifier)) => builder.with_webpki_verifier(verifier),
+        (Some(pin), verifier) => builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(PinnedServerVerifier {
+                pin,
+                inner: verifier,
+                provider,
+            })),
+        (None, None) => {
+            return Err(
+                "TLS requires a CA certificate or a pinned certificate fingerprint".to_string(),
+            )
+        }
+    };
+    let config = match (
+        options.client_cert.as_deref(),
+        options.client_key.as_deref(),
+    ) {
+        (Some(cert), Some(key)) => builder
+            .with_client_auth_cert(
+                load_pem_certs(Path::new(cert))?,
+                load_pem_key(Path::new(key))?,
+            )
+            .map_err(|error| format!("Invalid client certificate or key: {error}"))?,
+        (None, None) => builder.with_no_client_auth(),
+        _ => return Err("Client authentication requires both a certificate and a key".to_string()),
+    };
+    Ok(Arc::new(config))
+}
+
+fn parse_fingerprint(value: &str) -> Result<Vec<u8>, String> {
+    let cleaned: String = value
+        .chars()
+        .filter(|character| *character != ':' && !character.is_whitespace())
+        .collect();
+    decode_hex(&cleaned)
+        .filter(|bytes| bytes.len() == 32)
+        .ok_or_else(|| "Certificate fingerprint must be a SHA-256 hex digest".to_string())
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) || !value.is_ascii() {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&value[index..index + 2], 16).ok())
+        .collect()
+}
+
+// Accepts exactly one server certificate, identified by the SHA-256 of its DER encoding.
+// When a CA is configured too, the chain must also verify against it.
+#[derive(Debug)]
+struct PinnedServerVerifier {
+    pin: Vec<u8>,
+    inner: Option<Arc<rustls::client::WebPkiServerVerifier>>,
+    provider: Arc<rustls::crypto::CryptoProvider>,
+}
+
+impl rustls::client::danger::ServerCertVerifier for PinnedServerVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &rustls::pki_types::CertificateDer<'_>,
+        intermediates: &[rustls::pki_types::CertificateDer<'_>],
+        server_name: &rustls::pki_types::ServerName<'_>,
+        ocsp_response: &[u8],
+        now: rustls::pki_types::UnixTime,
+    ) -> Result<rustls::client::danger::ServerCertVerified, rustls::Error> {
+        if let Some(inner) = self.inner.as_ref() {
+            inner.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)?;
+        }
+        let digest = ring::digest::digest(&ring::digest::SHA256, end_entity.as_ref());
+        if digest.as_ref() != self.pin.as_slice() {
+            return Err(rustls::Error::InvalidCertificate(
+                rustls::CertificateError::ApplicationVerificationFailure,
+            ));
+        }
+        Ok(rustls::client::danger::ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &rustls::pki_types::CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &rustls::pki_types::CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+fn handle_remote_auth(
+    hub: &RemoteHub,
+    session: &RemoteSession,
+    id: Option<&str>,
+    mac: &str,
+) -> Result<String, String> {
+    if hub.auth_failures.is_blocked(session.peer) {
+        return reject_remote(&session.sender, id, "rate-limited");
+    }
+    let Some(grant) = hub.verify_auth_mac(&session.nonce, mac) else {
+        eprintln!("[remote] auth handshake failed");
+        hub.auth_failures.record_failure(session.peer);
+        return reject_remote(&session.sender, id, "unauthorized");
+    };
+    hub.auth_failures.record_success(session.peer);
+    send_remote_event(
+        &session.sender,
+        serde_json::json!({ "event": "auth-ok", "id": id, "name": grant.name }),
+    );
+    let name = grant.name.clone();
+    store_session_grant(session, grant);
+    Ok(name)
+}
+
+// Logs once per session; every later request reuses the stored grant.
+fn store_session_grant(session: &RemoteSession, grant: RemoteGrant) {
+    eprintln!(
+        "[remote] session {} authenticated as {:?}",
+        session.id, grant.name
+    );
+    if let Ok(mut current) = session.grant.lock() {
+        *current = Some(grant);
+    }
+}
+
+// Sessions authenticated by the handshake or a bearer token use their stored grant.
+// Older clients that send the token on every line are only accepted when the server
+// allows legacy tokens; those attempts count toward the rate limit too, and the first
+// accepted token authenticates the session.
+fn resolve_session_grant(
+    hub: &RemoteHub,
+    session: &RemoteSession,
+    token: Option<&str>,
+) -> Result<RemoteGrant, &'static str> {
+    if let Some(grant) = session.grant.lock().ok().and_then(|grant| grant.clone()) {
+        return Ok(grant);
+    }
+    if !hub.requires_auth() {
+        return hub.authenticate(None).ok_or("unauthorized");
+    }
+    match token {
+        Some(value) if hub.legacy_tokens => {
+            let grant = authenticate_session_token(hub, session, value)?;
+            store_session_grant(session, grant.clone());
+            Ok(grant)
+        }
+        Some(_) => {
+            eprintln!("[remote] per-request token refused; legacy tokens are disabled");
+            Err("unauthorized")
+        }
+        None => Err("unauthorized"),
+    }
+}
+
+fn authenticate_session_token(
+    hub: &RemoteHub,
+    session: &RemoteSession,
+    token: &str,
+) -> Result<RemoteGrant, &'static str> {
+    if hub.auth_failures.is_blocked(session.peer) {
+        return Err("rate-limited");
+    }
+    match hub.authenticate(Some(token)) {
+        Some(grant) => {
+            hub.auth_failures.record_success(session.peer);
+            Ok(grant)
+        }
+        None => {
+            eprintln!("[remote] unauthorized token");
+            hub.auth_failures.record_failure(session.peer);
+            Err("unauthorized")
+        }
+    }
+}
+
+fn handle_remote_line(line: &str, hub: Arc<RemoteHub>, session: &RemoteSession, headless: bool) {
+    let sender = &session.sender;
+    // Security: Do not log incoming lines as they may contain auth tokens
+    let envelope: RemoteEnvelope = match serde_json::from_str(line) {
+        Ok(value) => value,
+        Err(_) => {
+            // Well-formed requests for actions this build does not know get their own
+            // error, so newer clients can tell them apart from malformed input.
+            let value = serde_json::from_str::<JsonValue>(line).unwrap_or_default();
+            if let Some(action) = value.get("action").and_then(JsonValue::as_str) {
+                if !REMOTE_ACTIONS.iter().any(|(known, _)| *known == action) {
+                    eprintln!("[remote] unsupported action");
+                    let id = value.get("id").and_then(JsonValue::as_str);
+                    send_remote_error(sender, id, "unsupported-action");
+                    return;
+                }
+            }
+            eprintln!("[remote] invalid json");
+            send_remote_error(sender, None, "invalid_json");
+            return;
+        }
+    };
+    let started = Instant::now();
+    let target = RemoteAuditTarget::of(&envelope.request);
+    if let RemoteRequest::Auth { id, mac } = &envelope.request {
+        let outcome = handle_remote_auth(&hub, session, id.as_deref(), mac);
+        let name = outcome.as_ref().ok().cloned();
+        hub.audit(
+            session.peer,
+            name.as_deref(),
+            &target,
+            &outcome.map(|_| ()),
+            started,
+        );
+        return;
+    }
+    let grant = match resolve_session_grant(&hub, session, envelope.token.as_deref()) {
+        Ok(value) => value,
+        Err(code) => {
+            let outcome = match &envelope.request {
+                // Clients may check the protocol before authenticating.
+                RemoteRequest::Hello(request) if code == "unauthorized" => {
+                    send_remote_event(
+                        sender,
+                        serde_json::json!({
+                          "event": "hello",
+                          "id": request.id,
+                          "data": remote_public_hello(&hub)
+                        }),
+                    );
+                    Ok(())
+                }
+                _ => reject_remote(sender, request_id(&envelope.request), code),
+            };
+            hub.audit(session.peer, None, &target, &outcome, started);
+            return;
+        }
+    };
+    let outcome = match required_scope(&envelope.request) {
+        Err(code) => reject_remote(sender, request_id(&envelope.request), code),
+        Ok(Some(scope)) if !grant.allows(scope) => {
+            eprintln!("[remote] token {:?} lacks scope {:?}", grant.name, scope);
+            reject_remote(sender, request_id(&envelope.request), "insufficient-scope")
+        }
+        Ok(_) => dispatch_remote_request(envelope.request, &hub, session, &grant, headless),
+    };
+    hub.audit(session.peer, Some(&grant.name), &target, &outcome, started);
+}
+
+// Returns the error code sent to the client, if any, so the request can be audited.
+fn dispatch_remote_request(
+    request: RemoteRequest,
+    hub: &Arc<RemoteHub>,
+    session: &RemoteSession,
+    grant: &RemoteGrant,
+    headless: bool,
+) -> Result<(), String> {
+    let sender = &session.sender;
+    match request {
+        RemoteRequest::Ping { id } => {
+            eprintln!("[remote] ping {:?}", id);
+            send_remote_event(sender, serde_json::json!({ "event": "pong", "id": id }));
+            Ok(())
+        }
+        RemoteRequest::Hello(request) => {
+            eprintln!(
+                "[remote] hello {:?} client protocol {:?}",
+                request.id, request.protocol_version
+            );
+            send_remote_event(
+                sender,
+                serde_json::json!({
+                  "event": "hello",
+                  "id": request.id,
+                  "data": remote_hello(hub, grant, headless)
+                }),
+            );
+            Ok(())
+        }
+        // Answered before authorization.
+        RemoteRequest::Auth { .. } => Ok(()),
+        RemoteRequest::List(request) => {
+            eprintln!("[remote] list {:?} {:?}", request.id, request.path);
+            handle_remote_list(sender, grant, request)
+        }
+        RemoteRequest::Disk { id, path } => {
+            eprintln!("[remote] disk {:?} {}", id, path);
+            handle_remote_disk(sender, grant, id, path)
+        }
+        RemoteRequest::Read { id, path } => {
+            eprintln!("[remote] read {:?} {}", id, path);
+            handle_remote_read(sender, grant, id, path)
+        }
+        RemoteRequest::Download(request) => {
+            eprintln!("[remote] download {:?} {}", request.id, request.path);
+            handle_remote_download(session, grant, request)
+        }
+        RemoteRequest::Delete { id, paths } => {
+            eprintln!("[remote] delete {:?} items={}", id, paths.len());
+            let items = paths.into_iter().map(file_op_path).collect();
+            handle_remote_file_op(hub, sender, grant, id, RemoteFileOpKind::Delete, items)
+        }
+        RemoteRequest::Mkdir { id, paths } => {
+            eprintln!("[remote] mkdir {:?} items={}", id, paths.len());
+            let items = paths.into_iter().map(file_op_path).collect();
+            handle_remote_file_op(hub, sender, grant, id, RemoteFileOpKind::Mkdir, items)
+        }
+        RemoteRequest::Rename { id, items } => {
+            eprintln!("[remote] rename {:?} items={}", id, items.len());
+            handle_remote_file_op(hub, sender, grant, id, RemoteFileOpKind::Rename, items)
+        }
+        RemoteRequest::Copy { id, items } => {
+            eprintln!("[remote] copy {:?} items={}", id, items.len());
+            handle_remote_file_op(hub, sender, grant, id, RemoteFileOpKind::Copy, items)
+        }
+        RemoteRequest::Move {
+            id,
+            paths,
+            destination,
+        } => {
+            eprintln!("[remote] move {:?} items={}", id, paths.len());
+            let items = paths
+                .into_iter()
+                .map(|path| {
+                    let new_path = Path::new(&path)
+                        .file_name()
+                        .map(|name| get_path_string(&Path::new(&destination).join(name)));
+                    RemoteFileOpItem { path, new_path }
+                })
+                .collect();
+            handle_remote_file_op(hub, sender, grant, id, RemoteFileOpKind::Move, items)
+        }
+        RemoteRequest::Scan {
+            id,
+            path,
+            paths,
+            options,
+        } => {
+            eprintln!("[remote] scan {:?} {}", id, path);
+            handle_remote_scan(Arc::clone(hub), session, grant, id, path, paths, options)
+        }
+        RemoteRequest::Cancel { id } => {
+            eprintln!("[remote] cancel {:?}", id);
+            let (dropped, signalled) = hub.cancel_scan(session.id, grant, id.as_deref());
+            for scan_id in &dropped {
+                emit_to_remote(
+                    hub,
+                    ScanEvent::Cancelled("Scan cancelled".to_string()),
+                    scan_id,
+                );
+            }
+            let message = if dropped.is_empty() && signalled.is_empty() {
+                "no-active-scan"
+            } else {
+                "cancel-requested"
+            };
+            send_remote_event(sender, serde_json::json!({ "event": message, "id": id }));
+            Ok(())
+        }
+        RemoteRequest::Pause { id } => {
+            eprintln!("[remote] pause {:?}", id);
+            match hub.active_scan_control(session.id, grant, id.as_deref()) {
+                Some((scan_id, control)) if control.pause() => {
+                    emit_to_remote(hub, ScanEvent::Paused("Scan paused".to_string()), &scan_id);
+                    Ok(())
+                }
+                Some(_) => reject_remote(sender, id.as_deref(), "scan-already-paused"),
+                None => reject_remote(sender, id.as_deref(), "no-active-scan"),
+            }
+        }
+        RemoteRequest::Resume { id } => {
+            eprintln!("[remote] resume {:?}", id);
+            match hub.active_scan_control(session.id, grant, id.as_deref()) {
+                Some((scan_id, control)) if control.resume() => {
+                    emit_to_remote(
+                        hub,
+                        ScanEvent::Resumed("Scan resumed".to_string()),
+                        &scan_id,
+                    );
+                    Ok(())
+                }
+                Some(_) => reject_remote(sender, id.as_deref(), "scan-not-paused"),
+                None => reject_remote(sender, id.as_deref(), "no-active-scan"),
+            }
+        }
+        RemoteRequest::Status { id } => {
+            eprintln!("[remote] status {:?}", id);
+            match hub.scan_statuses(session.id, grant, id.as_deref()) {
+                Some(scans) => {
+                    send_remote_event(
+                        sender,
+                        serde_json::json!({
+                          "event": "scan-status",
+                          "id": id,
+                          "maxConcurrent": hub.max_concurrent_scans,
+                          "data": scans
+                        }),
+                    );
+                    Ok(())
+                }
+                None => reject_remote(sender, id.as_deref(), "scan-not-found"),
+            }
+        }
+        RemoteRequest::Result { id } => {
+            eprintln!("[remote] result {:?}", id);
+            let Some(scan_id) = id
+                .as_deref()
+                .filter(|value| hub.scan_visible(session.id, grant, value))
+            else {
+                return reject_remote(sender, id.as_deref(), "scan-not-found");
+            };
+            match hub.scan_result(scan_id) {
+                Ok(summary) => {
+                    send_remote_event(
+                        sender,
+                        serde_json::json!({ "event": "scan-result", "id": id, "data": summary }),
+                    );
+                    Ok(())
+                }
+                Err(code) => reject_remote(sender, id.as_deref(), code),
+            }
+        }
+        RemoteRequest::Subscribe { id } => {
+            eprintln!("[remote] subscribe {:?}", id);
+            // Watching every scan is reserved for grants that can see every path.
+            if id.is_none() && grant.roots.is_some() {
+                return reject_remote(sender, None, "forbidden-path");
+            }
+            if !hub.subscribe(session.id, grant, id.as_deref(), true) {
+                return reject_remote(sender, id.as_deref(), "scan-not-found");
+            }
+            send_remote_event(
+                sender,
+                serde_json::json!({ "event": "subscribed", "id": id }),
+            );
+            Ok(())
+        }
+        RemoteRequest::Unsubscribe { id } => {
+            eprintln!("[remote] unsubscribe {:?}", id);
+            if !hub.subscribe(session.id, grant, id.as_deref(), false) {
+                return reject_remote(sender, id.as_deref(), "scan-not-found");
+            }
+            send_remote_event(
+                sender,
+                serde_json::json!({ "event": "unsubscribed", "id": id }),
+            );
+            Ok(())
+        }
+        RemoteRequest::Metrics { id } => {
+            eprintln!("[remote] metrics {:?}", id);
+            send_remote_event(
+                sender,
+                serde_json::json!({ "event": "metrics", "id": id, "data": hub.render_metrics() }),
+            );
+            Ok(())
+        }
+        RemoteRequest::Shutdown { id } => {
+            eprintln!("[remote] shutdown {:?}", id);
+            if !headless {
+                return reject_remote(sender, id.as_deref(), "shutdown-not-allowed");
+            }
+            if !hub.request_shutdown() {
+                return reject_remote(sender, id.as_deref(), "shutdown-failed");
+            }
+            send_remote_event(sender, serde_json::json!({ "event": "shutdown", "id": id }));
+            Ok(())
+        }
+    }
+}
+
+// Describes this server to the client: versions, the actions the connection may use
+// and the limits it should stay within.
+fn remote_hello(hub: &RemoteHub, grant: &RemoteGrant, headless: bool) -> JsonValue {
+    let capabilities: Vec<&str> = REMOTE_ACTIONS
+        .iter()
+        .filter(|(action, scope)| {
+            let enabled = match *action {
+                "delete" | "mkdir" | "rename" | "copy" | "move" => hub.file_ops,
+                "shutdown" => headless,
+                _ => true,
+            };
+            enabled && scope.is_none_or(|scope| grant.allows(scope))
+        })
+        .map(|(action, _)| *action)
+        .collect();
+    let scopes: Vec<RemoteScope> = ALL_REMOTE_SCOPES
+        .into_iter()
+        .filter(|scope| grant.allows(*scope))
+        .collect();
+    serde_json::json!({
+      "protocolVersion": REMOTE_PROTOCOL_VERSION,
+      "appVersion": env!("CARGO_PKG_VERSION"),
+      "os": std::env::consts::OS,
+      "arch": std::env::consts::ARCH,
+      "headless": headless,
+      "token": grant.name,
+      "scopes": scopes,
+      "capabilities": capabilities,
+      "limits": remote_limits(hub)
+    })
+}
+
+// What an unauthenticated client learns from `hello`: the protocol and the limits only.
+fn remote_public_hello(hub: &RemoteHub) -> JsonValue {
+    serde_json::json!({
+      "protocolVersion": REMOTE_PROTOCOL_VERSION,
+      "authenticated": false,
+      "limits": remote_limits(hub)
+    })
+}
+
+fn remote_limits(hub: &RemoteHub) -> JsonValue {
+    serde_json::json!({
+      "maxLineBytes": MAX_LINE_LENGTH,
+      "maxReadBytes": MAX_READ_BYTES,
+      "maxDownloadChunkBytes": MAX_DOWNLOAD_CHUNK_SIZE,
+      "maxListPageSize": MAX_LIST_PAGE_SIZE,
+      "maxFileOpItems": MAX_FILE_OP_ITEMS,
+      "maxConcurrentScans": hub.max_concurrent_scans,
+      "maxQueuedScans": MAX_QUEUED_SCANS,
+      "maxConnections": MAX_CONNECTIONS
+    })
+}
+
+fn handle_remote_scan(
+    hub: Arc<RemoteHub>,
+    session: &RemoteSession,
+    grant: &RemoteGrant,
+    id: Option<String>,
+    path: String,
+    paths: Option<Vec<String>>,
+    options: Option<ScanOptions>,
+) -> Result<(), String> {
+    let sender = &session.sender;
+    if id.as_deref().is_some_and(|value| {
+        value.starts_with(SERVER_SCAN_ID_PREFIX) || value.starts_with(WATCH_SCAN_ID_PREFIX)
+    }) {
+        return reject_remote(sender, id.as_deref(), "scan-id-reserved");
+    }
+    let roots = collect_scan_roots(&path, paths);
+    if roots.iter().any(|root| !grant.allows_path(root)) {
+        return reject_remote(sender, id.as_deref(), "forbidden-path");
+    }
+    if roots.is_empty() || roots.iter().any(|root| !root.exists()) {
+        return reject_remote(sender, id.as_deref(), "path-not-found");
+    }
+    let config = match build_scan_config(&options.unwrap_or_default()) {
+        Ok(value) => value,
+        Err(error) => return reject_remote(sender, id.as_deref(), &error),
+    };
+    let request_id = id.clone();
+    match hub.submit_scan(session.id, Some(grant), id, roots, config) {
+        Ok(RemoteScanSubmission::Started(start)) => spawn_remote_scan(hub, *start),
+        Ok(RemoteScanSubmission::Queued { id, position }) => send_remote_event(
+            sender,
+            serde_json::json!({ "event": "scan-queued", "id": id, "position": position }),
+        ),
+        Err(code) => return reject_remote(sender, request_id.as_deref(), code),
+    }
     Ok(())
 }
 
-fn copy_dir_recursive(source: &Path, dest: &Path) -> Result<(), String> {
-    fs::create_dir_all(dest).map_err(|e| e.to_string())?;
-    for entry in fs::read_dir(source).map_err(|e| e.to_string())? {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let file_type = entry.file_type().map_err(|e| e.to_string())?;
-        let dest_path = dest.join(entry.file_name());
-        if file_type.is_dir() {
-            copy_dir_recursive(&entry.path(), &dest_path)?;
-        } else {
-            fs::copy(entry.path(), dest_path).map_err(|e| e.to_string())?;
+fn spawn_remote_scan(hub: Arc<RemoteHub>, start: RemoteScanStart) {
+    let line = format!(
+        "{}\n",
+        serde_json::json!({ "event": "scan-started", "id": start.id })
+    );
+    hub.send_to_scan_audience(&start.id, line);
+    thread::spawn(move || {
+        let RemoteScanStart {
+            id,
+            roots,
+            config,
+            control,
+        } = start;
+        let emitter_hub = Arc::clone(&hub);
+        let emitter_id = id.clone();
+        let emitter: ScanEmitter = Arc::new(move |event| {
+            emitter_hub.record_scan_event(&emitter_id, &event);
+            emit_to_remote(&emitter_hub, event, &emitter_id);
+        });
+        let started = Instant::now();
+        if let Err(error) = run_scan(
+            roots,
+            config,
+            control,
+            Arc::clone(&emitter),
+            Some(id.clone()),
+        ) {
+            emitter(ScanEvent::Error(error));
         }
-    }
-    Ok(())
+        let next_scans = hub.finish_scan(&id);
+        hub.record_scan_duration(&id, started.elapsed());
+        for next in next_scans {
+            spawn_remote_scan(Arc::clone(&hub), next);
+        }
+    });
 }
 
-fn get_time_millis(time: std::io::Result<SystemTime>) -> Option<u64> {
-    time.ok()
-        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
-        .map(|d| d.as_millis() as u64)
+// Rescans each watched directory in turn through the scan queue, so scheduled scans
+// share the concurrency limit and can be followed with `subscribe` like any other.
+fn spawn_watch_scheduler(hub: Arc<RemoteHub>, interval: Duration) {
+    thread::spawn(move || loop {
+        for path in hub.watch_paths.clone() {
+            if hub.closed.load(Ordering::Relaxed) {
+                return;
+            }
+            run_watched_scan(&hub, &path);
+        }
+        if !wait_while_open(&hub, interval) {
+            return;
+        }
+    });
 }
 
-fn run_scan(
-    root: PathBuf,
-    config: ScanConfig,
-    cancel_flag: Arc<AtomicBool>,
-    emit: ScanEmitter,
-    scan_id: Option<String>,
-) -> Result<(), String> {
-    let start = Instant::now();
-    let mut stats: HashMap<PathBuf, NodeStats> = HashMap::new();
-    let mut children: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
-    let mut files_by_parent: HashMap<PathBuf, Vec<ScanFile>> = HashMap::new();
-    let mut largest_files: Vec<ScanFile> = Vec::new();
-    let mut last_emit = Instant::now();
-    let mut last_emitted_bytes: u64 = 0;
-    let mut processed: u64 = 0;
-
-    let walk = WalkDir::new(&root).parallelism(config.parallelism.clone());
-    for entry in walk {
-        if cancel_flag.load(Ordering::Relaxed) {
-            emit(ScanEvent::Cancelled("Scan cancelled".to_string()));
-            return Ok(());
+// Sleeps for `duration`, returning false early once the server has stopped.
+fn wait_while_open(hub: &RemoteHub, duration: Duration) -> bool {
+    let until = Instant::now() + duration;
+    while Instant::now() < until {
+        if hub.closed.load(Ordering::Relaxed) {
+            return false;
         }
-        let entry = match entry {
-            Ok(item) => item,
-            Err(_) => continue,
-        };
-        let entry_path = entry.path();
-        let entry_type = entry.file_type();
-        processed += 1;
+        thread::sleep(Duration::from_secs(1));
+    }
+    !hub.closed.load(Ordering::Relaxed)
+}
 
-        if entry_type.is_dir() {
-            if should_skip_dir(&root, &entry_path, &config.filters) {
-                continue;
+fn run_watched_scan(hub: &Arc<RemoteHub>, path: &Path) {
+    let id = format!("{WATCH_SCAN_ID_PREFIX}{}", get_path_string(path));
+    if !path.is_dir() {
+        eprintln!("[remote] watch scan {id} skipped: not a directory");
+        return;
+    }
+    let config = match build_scan_config(&ScanOptions::default()) {
+        Ok(value) => value,
+        Err(error) => {
+            eprintln!("[remote] watch scan {id} not started: {error}");
+            return;
+        }
+    };
+    // Owner 0 is never a client, so events only reach subscribers.
+    match hub.submit_scan(0, None, Some(id.clone()), vec![path.to_path_buf()], config) {
+        Ok(RemoteScanSubmission::Started(start)) => spawn_remote_scan(Arc::clone(hub), *start),
+        Ok(RemoteScanSubmission::Queued { .. }) => {}
+        Err(code) => {
+            eprintln!("[remote] watch scan {id} not started: {code}");
+            return;
+        }
+    }
+    loop {
+        match hub.scan_state(&id) {
+            Some(RemoteScanState::Queued | RemoteScanState::Running) => {}
+            Some(_) => break,
+            None => {
+                eprintln!("[remote] watch scan {id} is no longer tracked");
+                return;
             }
-            stats.entry(entry_path.to_path_buf()).or_default();
-            if let Some(parent) = entry_path.parent() {
-                let parent_buf = parent.to_path_buf();
-                children
-                    .entry(parent_buf.clone())
-                    .or_default()
-                    .push(entry_path.to_path_buf());
-                stats.entry(parent_buf).or_default().direct_dirs += 1;
-            }
-        } else if entry_type.is_file() {
-            let metadata = entry.metadata();
-            let size = metadata.as_ref().map(|meta| meta.len()).unwrap_or(0);
-            let modified = metadata
-                .as_ref()
-                .ok()
-                .and_then(|m| get_time_millis(m.modified()));
+        }
+        if hub.closed.load(Ordering::Relaxed) {
+            return;
+        }
+        thread::sleep(Duration::from_secs(1));
+    }
+    match hub.scan_result(&id) {
+        Ok(summary) => hub.record_watched_scan(path, &summary),
+        Err(code) => eprintln!("[remote] watch scan {id} did not complete: {code}"),
+    }
+}
 
-            if !should_include_file(&entry_path, size, modified, &config.filters) {
-                continue;
-            }
-            let name = get_entry_name_string(&entry_path);
-            if let Some(parent) = entry_path.parent() {
-                let parent_buf = parent.to_path_buf();
-                files_by_parent
-                    .entry(parent_buf)
-                    .or_default()
-                    .push(ScanFile {
-                        path: get_path_string(&entry_path),
-                        name,
-                        size_bytes: size,
-                        modified,
-                    });
+fn spawn_alert_evaluator(hub: Arc<RemoteHub>, config: AlertConfig) {
+    thread::spawn(move || {
+        // Rule name and condition of every alert currently firing.
+        let mut firing = HashSet::new();
+        loop {
+            for rule in &config.rules {
+                for alert in evaluate_alert_rule(&hub, rule, &mut firing) {
+                    dispatch_alert(&hub, &config, alert);
+                }
             }
-            update_largest_files(&mut largest_files, &entry_path, size, modified, 100);
-            if let Some(parent) = entry_path.parent() {
-                let parent_stats = stats.entry(parent.to_path_buf()).or_default();
-                parent_stats.direct_bytes += size;
-                parent_stats.direct_files += 1;
+            if !wait_while_open(&hub, config.interval) {
+                return;
             }
         }
+    });
+}
 
-        if let Some(throttle) = &config.throttle {
-            if throttle.sleep_ms > 0 && processed % throttle.every_entries == 0 {
-                thread::sleep(Duration::from_millis(throttle.sleep_ms));
+// Returns an alert for each condition of the rule that started or stopped firing.
+fn evaluate_alert_rule(
+    hub: &RemoteHub,
+    rule: &AlertRuleSettings,
+    firing: &mut HashSet<(String, &'static str)>,
+) -> Vec<JsonValue> {
+    let path = PathBuf::from(&rule.path);
+    let gigabytes = |bytes: u64| format!("{:.1} GB", bytes as f64 / 1e9);
+    let mut checks = Vec::new();
+    if let Some(threshold) = rule.min_free_percent {
+        match compute_disk_usage(&path) {
+            Ok(snapshot) if snapshot.total_bytes > 0 => {
+                let percent = snapshot.free_bytes as f64 * 100.0 / snapshot.total_bytes as f64;
+                checks.push((
+                    "low-free-space",
+                    percent < threshold,
+                    percent,
+                    threshold,
+                    format!(
+                        "Free space on {} is {percent:.1}% (threshold {threshold}%)",
+                        rule.path
+                    ),
+                ));
             }
+            Ok(_) => {}
+            Err(error) => eprintln!("[remote] alert rule {:?} skipped: {error}", rule.name),
         }
+    }
+    if let Some(limit) = rule.max_growth_bytes {
+        if let Some((previous, current)) = hub.watched_growth(&path) {
+            let growth = current.saturating_sub(previous);
+            checks.push((
+                "growth",
+                growth > limit,
+                growth as f64,
+                limit as f64,
+                format!(
+                    "{} grew by {} between scans (limit {})",
+                    rule.path,
+                    gigabytes(growth),
+                    gigabytes(limit)
+                ),
+            ));
+        }
+    }
+    let mut changed = Vec::new();
+    for (condition, breached, value, threshold, message) in checks {
+        let key = (rule.name.clone(), condition);
+        let state = match (breached, firing.contains(&key)) {
+            (true, false) => "firing",
+            (false, true) => "resolved",
+            _ => continue,
+        };
+        if breached {
+            firing.insert(key);
+        } else {
+            firing.remove(&key);
+        }
+        changed.push(serde_json::json!({
+          "rule": rule.name,
+          "condition": condition,
+          "state": state,
+          "path": rule.path,
+          "value": value,
+          "threshold": threshold,
+          "message": message,
+          "timestamp": get_time_millis(Ok(SystemTime::now()))
+        }));
+    }
+    changed
+}
 
-        if should_emit_progress(processed, &last_emit, &config) {
-            let summary = build_summary(
-                &root,
-                &children,
-                &files_by_parent,
-                &stats,
-                &largest_files,
-                start,
-                scan_id.clone(),
-                true,      // compact mode
-                false,     // sort by name for stability
-                Some(400), // cap children to avoid UI overload
-            );
-
-            // Ensure we don't emit a summary that shows "less" size than before
-            if summary.total_bytes >= last_emitted_bytes {
-                last_emitted_bytes = summary.total_bytes;
-                emit(ScanEvent::Progress(summary));
-                last_emit = Instant::now();
+// Broadcasts the alert to clients watching every scan whose grant covers metrics and the
+// alert's path, then calls the webhook and the command off the evaluator thread so a
+// slow endpoint cannot delay other rules.
+fn dispatch_alert(hub: &RemoteHub, config: &AlertConfig, alert: JsonValue) {
+    eprintln!(
+        "[remote] alert {}: {}",
+        alert["state"].as_str().unwrap_or_default(),
+        alert["message"].as_str().unwrap_or_default()
+    );
+    let path = PathBuf::from(alert["path"].as_str().unwrap_or_default());
+    hub.send_to_watchers(
+        format!(
+            "{}\n",
+            serde_json::json!({ "event": "alert", "id": null, "data": alert })
+        ),
+        |grant| grant.allows(RemoteScope::Metrics) && grant.allows_path(&path),
+    );
+    let webhook = config.webhook.clone();
+    let webhook_ca = config.webhook_ca.clone();
+    let command = config.command.clone();
+    thread::spawn(move || {
+        if let Some(url) = webhook {
+            if let Err(error) = post_webhook(&url, webhook_ca.as_deref(), &alert.to_string()) {
+                eprintln!("[remote] alert webhook failed: {error}");
             }
         }
-    }
+        if let Some(command) = command {
+            run_alert_command(&command, &alert);
+        }
+    });
+}
 
-    let summary = build_summary(
-        &root,
-        &children,
-        &files_by_parent,
-        &stats,
-        &largest_files,
-        start,
-        scan_id,
-        false, // full mode
-        true,  // sort by size for final view
-        None,
+fn post_webhook(url: &str, ca: Option<&Path>, body: &str) -> Result<(), String> {
+    let (scheme, rest) = url
+        .split_once("://")
+        .ok_or_else(|| "Invalid webhook URL".to_string())?;
+    let (authority, target) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, "/"),
+    };
+    // Credentials in the URL (`user:pass@host`) are sent as basic authentication.
+    let (authorization, authority) = match authority.rsplit_once('@') {
+        Some((userinfo, host)) => (
+            format!(
+                "Authorization: Basic {}\r\n",
+                BASE64_STANDARD.encode(percent_decode(&userinfo.replace('+', "%2B")))
+            ),
+            host,
+        ),
+        None => (String::new(), authority),
+    };
+    let default_port = match scheme {
+        "http" => 80,
+        "https" => 443,
+        _ => return Err("Webhook URL must use http or https".to_string()),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.contains(']') => (
+            host,
+            port.parse::<u16>()
+                .map_err(|_| "Invalid webhook port".to_string())?,
+        ),
+        _ => (authority, default_port),
+    };
+    let host = host.trim_matches(['[', ']']);
+    let address = (host, port)
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addresses| addresses.next())
+        .ok_or_else(|| format!("Failed to resolve webhook host {host}"))?;
+    let stream = TcpStream::connect_timeout(&address, WEBHOOK_TIMEOUT)
+        .map_err(|error| format!("Failed to connect to webhook: {error}"))?;
+    let _ = stream.set_read_timeout(Some(WEBHOOK_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(WEBHOOK_TIMEOUT));
+    let request = format!(
+        "POST {target} HTTP/1.1\r\nHost: {authority}\r\n{authorization}Content-Type: application/json\r\nContent-Length: {}\r\nUser-Agent: Dragabyte/{}\r\nConnection: close\r\n\r\n{body}",
+        body.len(),
+        env!("CARGO_PKG_VERSION")
     );
-    emit(ScanEvent::Complete(summary));
+    let status = if scheme == "https" {
+        let roots = match ca {
+            Some(path) => load_root_store(path)?,
+            None => native_root_store()?,
+        };
+        let config = rustls::ClientConfig::builder_with_provider(tls_crypto_provider())
+            .with_safe_default_protocol_versions()
+            .map_err(|error| format!("Failed to configure TLS: {error}"))?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let server_name = rustls::pki_types::ServerName::try_from(host.to_string())
+            .map_err(|_| "Invalid webhook host name".to_string())?;
+        let session = rustls::ClientConnection::new(Arc::new(config), server_name)
+            .map_err(|error| format!("Failed to start TLS session: {error}"))?;
+        exchange_webhook(rustls::StreamOwned::new(session, stream), &request)?
+    } else {
+        exchange_webhook(stream, &request)?
+    };
+    if !(200..300).contains(&status) {
+        return Err(format!("Webhook answered with status {status}"));
+    }
     Ok(())
 }
 
-fn build_scan_config(options: &ScanOptions) -> Result<ScanConfig, String> {
-    let filters = build_filter_config(&options.filters)?;
-    let parallelism = resolve_parallelism(&options.priority_mode);
-    let (emit_every, emit_interval) = match options.priority_mode {
-        ScanPriorityMode::Performance => (5000, Duration::from_millis(500)),
-        ScanPriorityMode::Balanced => (10000, Duration::from_millis(1000)),
-        ScanPriorityMode::Low => (20000, Duration::from_millis(2000)),
+// Sends the request and returns the response's status code.
+fn exchange_webhook(mut stream: impl Read + Write, request: &str) -> Result<u16, String> {
+    stream
+        .write_all(request.as_bytes())
+        .and_then(|_| stream.flush())
+        .map_err(|error| format!("Webhook request failed: {error}"))?;
+    let mut status_line = Vec::new();
+    let mut byte = [0u8; 1];
+    while !status_line.ends_with(b"\r\n") && status_line.len() < 1024 {
+        match stream.read(&mut byte) {
+            Ok(0) => break,
+            Ok(_) => status_line.push(byte[0]),
+            Err(error) => return Err(format!("Webhook response failed: {error}")),
+        }
+    }
+    String::from_utf8_lossy(&status_line)
+        .split(' ')
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| "Invalid webhook response".to_string())
+}
+
+// The alert is passed as JSON in DRAGABYTE_ALERT, with its main fields also split out.
+fn run_alert_command(command: &str, alert: &JsonValue) {
+    #[cfg(target_os = "windows")]
+    let mut process = {
+        let mut process = Command::new("cmd");
+        process.args(["/C", command]);
+        process
     };
-    let throttle = match options.throttle_level {
-        ScanThrottleLevel::Off => None,
-        ScanThrottleLevel::Low => Some(ThrottleConfig {
-            every_entries: 1200,
-            sleep_ms: 1,
-        }),
-        ScanThrottleLevel::Medium => Some(ThrottleConfig {
-            every_entries: 600,
-            sleep_ms: 3,
-        }),
-        ScanThrottleLevel::High => Some(ThrottleConfig {
-            every_entries: 250,
-            sleep_ms: 6,
-        }),
+    #[cfg(not(target_os = "windows"))]
+    let mut process = {
+        let mut process = Command::new("sh");
+        process.args(["-c", command]);
+        process
     };
-    Ok(ScanConfig {
-        filters,
-        emit_every,
-        emit_interval,
-        throttle,
-        parallelism,
-    })
+    let field = |name: &str| {
+        alert
+            .get(name)
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    process
+        .env("DRAGABYTE_ALERT", alert.to_string())
+        .env("DRAGABYTE_ALERT_RULE", field("rule"))
+        .env("DRAGABYTE_ALERT_STATE", field("state"))
+        .env("DRAGABYTE_ALERT_PATH", field("path"))
+        .env("DRAGABYTE_ALERT_MESSAGE", field("message"));
+    match process.status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => eprintln!("[remote] alert command exited with {status}"),
+        Err(error) => eprintln!("[remote] alert command failed: {error}"),
+    }
 }
 
-fn build_filter_config(filters: &ScanFilters) -> Result<FilterConfig, String> {
-    if let (Some(min), Some(max)) = (filters.min_size_bytes, filters.max_size_bytes) {
-        if min > max {
-            return Err("Min size cannot exceed max size".to_string());
+fn prometheus_labels(labels: &[(&str, &str)]) -> String {
+    let pairs: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| {
+            let escaped = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{name}=\"{escaped}\"")
+        })
+        .collect();
+    format!("{{{}}}", pairs.join(","))
+}
+
+fn handle_remote_disk(
+    sender: &mpsc::Sender<String>,
+    grant: &RemoteGrant,
+    id: Option<String>,
+    path: String,
+) -> Result<(), String> {
+    let target = PathBuf::from(&path);
+    if !grant.allows_path(&target) {
+        return reject_remote(sender, id.as_deref(), "forbidden-path");
+    }
+    match compute_disk_usage(&target) {
+        Ok(snapshot) => {
+            send_remote_event(
+                sender,
+                serde_json::json!({ "event": "disk-info", "id": id, "data": snapshot }),
+            );
+            Ok(())
+        }
+        Err(message) => {
+            send_remote_event(
+                sender,
+                serde_json::json!({ "event": "disk-error", "id": id, "message": message }),
+            );
+            Err(message)
         }
     }
-    if let (Some(min), Some(max)) = (
-        filters.min_modified_timestamp,
-        filters.max_modified_timestamp,
-    ) {
-        if min > max {
-            return Err("Min modified timestamp cannot exceed max modified timestamp".to_string());
+}
+
+fn handle_remote_read(
+    sender: &mpsc::Sender<String>,
+    grant: &RemoteGrant,
+    id: Option<String>,
+    path: String,
+) -> Result<(), String> {
+    let target = PathBuf::from(&path);
+    // Checked first so paths outside the sandbox do not reveal whether they exist.
+    if !grant.allows_path(&target) {
+        return reject_remote(sender, id.as_deref(), "forbidden-path");
+    }
+    if !target.exists() {
+        return reject_remote(sender, id.as_deref(), "path-not-found");
+    }
+    if !target.is_file() {
+        return reject_remote(sender, id.as_deref(), "not-a-file");
+    }
+    match fs::metadata(&target) {
+        Ok(meta) => {
+            if meta.len() > MAX_READ_BYTES {
+                return reject_remote(sender, id.as_deref(), "file-too-large");
+            }
+        }
+        Err(e) => {
+            return reject_remote(sender, id.as_deref(), &e.to_string());
         }
     }
-    let include_regex = match &filters.include_regex {
-        Some(pattern) => Some(Regex::new(pattern).map_err(|err| err.to_string())?),
-        None => None,
-    };
-    let exclude_regex = match &filters.exclude_regex {
-        Some(pattern) => Some(Regex::new(pattern).map_err(|err| err.to_string())?),
-        None => None,
-    };
-    let include_extensions = normalize_extensions(&filters.include_extensions);
-    let exclude_extensions = normalize_extensions(&filters.exclude_extensions);
-    let include_names = normalize_list(&filters.include_names);
-    let exclude_names = normalize_list(&filters.exclude_names);
-    let include_paths = normalize_list(&filters.include_paths);
-    let exclude_paths = normalize_list(&filters.exclude_paths);
-    let has_include_extensions = !include_extensions.is_empty();
-    let has_exclude_extensions = !exclude_extensions.is_empty();
-    let has_include_names = !include_names.is_empty();
-    let has_exclude_names = !exclude_names.is_empty();
-    let has_include_paths = !include_paths.is_empty();
-    let has_exclude_paths = !exclude_paths.is_empty();
-    let has_include_regex = include_regex.is_some();
-    let has_exclude_regex = exclude_regex.is_some();
-    let has_includes =
-        has_include_extensions || has_include_names || has_include_paths || has_include_regex;
-    let has_dir_excludes = has_exclude_paths || has_exclude_names || has_exclude_regex;
-    let has_file_excludes = has_dir_excludes || has_exclude_extensions;
-    let needs_path =
-        has_exclude_paths || has_include_paths || has_include_regex || has_exclude_regex;
-    let needs_name = has_exclude_names || has_include_names;
-    let needs_extension = has_include_extensions || has_exclude_extensions;
-    Ok(FilterConfig {
-        include_extensions,
-        exclude_extensions,
-        include_names,
-        exclude_names,
-        min_size_bytes: filters.min_size_bytes,
-        max_size_bytes: filters.max_size_bytes,
-        min_modified_timestamp: filters.min_modified_timestamp,
-        max_modified_timestamp: filters.max_modified_timestamp,
-        include_regex,
-        exclude_regex,
-        include_paths,
-        exclude_paths,
-        flags: FilterFlags {
-            has_includes,
-            has_file_excludes,
-            has_dir_excludes,
-            needs_path,
-            needs_name,
-            needs_extension,
-        },
-    })
+    match fs::read(&target) {
+        Ok(bytes) => {
+            let data = BASE64_STANDARD.encode(&bytes);
+            send_remote_event(
+                sender,
+                serde_json::json!({ "event": "read-complete", "id": id, "data": { "path": path, "content": data } }),
+            );
+            Ok(())
+        }
+        Err(e) => reject_remote(sender, id.as_deref(), &e.to_string()),
+    }
+}
+
+fn handle_remote_download(
+    session: &RemoteSession,
+    grant: &RemoteGrant,
+    request: RemoteDownloadRequest,
+) -> Result<(), String> {
+    let sender = &session.sender;
+    let id = request.id.as_deref();
+    let target = PathBuf::from(&request.path);
+    if !grant.allows_path(&target) {
+        return reject_remote(sender, id, "forbidden-path");
+    }
+    if !target.exists() {
+        return reject_remote(sender, id, "path-not-found");
+    }
+    if !target.is_file() {
+        return reject_remote(sender, id, "not-a-file");
+    }
+    let mut file = match fs::File::open(&target) {
+        Ok(value) => value,
+        Err(error) => return reject_remote(sender, id, &error.to_string()),
+    };
+    let size = match file.metadata() {
+        Ok(meta) => meta.len(),
+        Err(error) => return reject_remote(sender, id, &error.to_string()),
+    };
+    let (offset, length) = match resolve_download_range(&mut file, size, &request) {
+        Ok(value) => value,
+        Err(code) => return reject_remote(sender, id, &code),
+    };
+    let chunk_size = request
+        .chunk_size
+        .unwrap_or(DOWNLOAD_CHUNK_SIZE)
+        .clamp(1, MAX_DOWNLOAD_CHUNK_SIZE);
+    send_remote_event(
+        sender,
+        serde_json::json!({
+          "event": "download-start",
+          "id": id,
+          "data": {
+            "path": request.path,
+            "size": size,
+            "offset": offset,
+            "length": length,
+            "chunkSize": chunk_size,
+            "chunks": length.div_ceil(chunk_size as u64)
+          }
+        }),
+    );
+    let transfer = RemoteDownload {
+        id: request.id,
+        path: request.path,
+        file,
+        size,
+        offset,
+        length,
+        chunk_size,
+    };
+    let sender = sender.clone();
+    let flow = Arc::clone(&session.flow);
+    thread::spawn(move || {
+        let id = transfer.id.clone();
+        if let Err(error) = stream_remote_download(transfer, &sender, &flow) {
+            eprintln!("[remote] download failed: {error}");
+            send_remote_error(&sender, id.as_deref(), &error.to_string());
+        }
+    });
+    Ok(())
 }
 
-fn normalize_extensions(values: &[String]) -> HashSet<String> {
-    let mut set = HashSet::new();
-    for value in values {
-        let cleaned = value.trim().trim_start_matches('.').to_lowercase();
-        if !cleaned.is_empty() {
-            set.insert(cleaned);
+struct RemoteDownload {
+    id: Option<String>,
+    path: String,
+    file: fs::File,
+    size: u64,
+    offset: u64,
+    length: u64,
+    chunk_size: usize,
+}
+
+fn resolve_download_range(
+    file: &mut fs::File,
+    size: u64,
+    request: &RemoteDownloadRequest,
+) -> Result<(u64, u64), String> {
+    match (request.head_lines, request.tail_lines) {
+        (Some(_), Some(_)) => Err("invalid-range".to_string()),
+        (Some(lines), None) => {
+            let end = head_end(file, size, lines).map_err(|error| error.to_string())?;
+            Ok((0, end))
+        }
+        (None, Some(lines)) => {
+            let start = tail_start(file, size, lines).map_err(|error| error.to_string())?;
+            Ok((start, size - start))
+        }
+        (None, None) if request.offset > size => Err("invalid-range".to_string()),
+        (None, None) => {
+            let available = size - request.offset;
+            let length = request
+                .length
+                .map_or(available, |value| value.min(available));
+            Ok((request.offset, length))
         }
     }
-    set
 }
 
-fn normalize_list(values: &[String]) -> Vec<String> {
-    let mut list = Vec::new();
-    for value in values {
-        let cleaned = value.trim().to_lowercase();
-        if !cleaned.is_empty() {
-            list.push(cleaned);
+// End of the first `lines` lines, including the final newline.
+fn head_end(file: &mut fs::File, size: u64, lines: usize) -> std::io::Result<u64> {
+    let mut remaining = lines;
+    if remaining == 0 {
+        return Ok(0);
+    }
+    let mut buffer = vec![0u8; DOWNLOAD_SCAN_BLOCK];
+    let mut position = 0u64;
+    file.seek(SeekFrom::Start(0))?;
+    while position < size {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        for (index, byte) in buffer[..read].iter().enumerate() {
+            if *byte == b'\n' {
+                remaining -= 1;
+                if remaining == 0 {
+                    return Ok((position + index as u64 + 1).min(size));
+                }
+            }
         }
+        position += read as u64;
     }
-    list
+    Ok(size)
 }
 
-fn should_emit_progress(processed: u64, last_emit: &Instant, config: &ScanConfig) -> bool {
-    if processed % config.emit_every == 0 {
-        return true;
+// Start of the last `lines` lines, reading backwards from the end of the file.
+fn tail_start(file: &mut fs::File, size: u64, lines: usize) -> std::io::Result<u64> {
+    if lines == 0 || size == 0 {
+        return Ok(size);
     }
-    last_emit.elapsed() >= config.emit_interval
+    let mut last = [0u8; 1];
+    file.seek(SeekFrom::Start(size - 1))?;
+    file.read_exact(&mut last)?;
+    // A newline ending the file closes the last line rather than starting another.
+    let mut end = if last[0] == b'\n' { size - 1 } else { size };
+    let mut remaining = lines;
+    let mut buffer = vec![0u8; DOWNLOAD_SCAN_BLOCK];
+    while end > 0 {
+        let start = end.saturating_sub(DOWNLOAD_SCAN_BLOCK as u64);
+        let block = &mut buffer[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(block)?;
+        for (index, byte) in block.iter().enumerate().rev() {
+            if *byte == b'\n' {
+                remaining -= 1;
+                if remaining == 0 {
+                    return Ok(start + index as u64 + 1);
+                }
+            }
+        }
+        end = start;
+    }
+    Ok(0)
 }
 
-fn get_path_string(path: &Path) -> String {
-    path.to_string_lossy().to_string()
+fn stream_remote_download(
+    transfer: RemoteDownload,
+    sender: &mpsc::Sender<String>,
+    flow: &RemoteFlowControl,
+) -> std::io::Result<()> {
+    let RemoteDownload {
+        id,
+        path,
+        mut file,
+        size,
+        offset,
+        length,
+        chunk_size,
+    } = transfer;
+    let closed = || std::io::Error::new(std::io::ErrorKind::BrokenPipe, "connection closed");
+    let mut buffer = vec![0u8; chunk_size];
+    // The whole-file digest is only known when the range runs to the end of the file; a
+    // resumed download hashes the part the client already has first.
+    let mut file_digest =
+        (offset + length >= size).then(|| ring::digest::Context::new(&ring::digest::SHA256));
+    if let Some(digest) = file_digest.as_mut() {
+        file.seek(SeekFrom::Start(0))?;
+        let mut prefix = (&mut file).take(offset);
+        loop {
+            let read = prefix.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            digest.update(&buffer[..read]);
+        }
+    }
+    let mut range_digest = ring::digest::Context::new(&ring::digest::SHA256);
+    file.seek(SeekFrom::Start(offset))?;
+    let mut reader = (&mut file).take(length);
+    let mut sent = 0u64;
+    let mut index = 0u64;
+    loop {
+        let read = read_chunk(&mut reader, &mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        let chunk = &buffer[..read];
+        range_digest.update(chunk);
+        if let Some(digest) = file_digest.as_mut() {
+            digest.update(chunk);
+        }
+        let line = format!(
+            "{}\n",
+            serde_json::json!({
+              "event": "download-chunk",
+              "id": id,
+              "index": index,
+              "offset": offset + sent,
+              "data": BASE64_STANDARD.encode(chunk)
+            })
+        );
+        if !flow.wait_for_room(line.len() as u64) || sender.send(line).is_err() {
+            return Err(closed());
+        }
+        sent += read as u64;
+        index += 1;
+    }
+    send_remote_event(
+        sender,
+        serde_json::json!({
+          "event": "download-complete",
+          "id": id,
+          "data": {
+            "path": path,
+            "offset": offset,
+            "length": sent,
+            "chunks": index,
+            "sha256": encode_hex(range_digest.finish().as_ref()),
+            "fileSha256": file_digest.map(|digest| encode_hex(digest.finish().as_ref()))
+          }
+        }),
+    );
+    Ok(())
 }
 
-fn compute_disk_usage(path: &Path) -> Result<DiskUsageSnapshot, String> {
-    if !path.exists() {
-        return Err("path-not-found".to_string());
+// Fills the buffer unless the reader runs out first.
+fn read_chunk<R: Read>(reader: &mut R, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        }
     }
-    let total_bytes =
-        fs2::total_space(path).map_err(|error| format!("disk-usage-failed: {error}"))?;
-    let free_bytes =
-        fs2::available_space(path).map_err(|error| format!("disk-usage-failed: {error}"))?;
-    Ok(DiskUsageSnapshot {
-        path: get_path_string(path),
-        total_bytes,
-        free_bytes,
-    })
+    Ok(filled)
 }
 
-fn get_entry_name_string(path: &Path) -> String {
-    path.file_name()
-        .map(|value| value.to_string_lossy().to_string())
-        .unwrap_or_else(|| get_path_string(path))
+fn file_op_path(path: String) -> RemoteFileOpItem {
+    RemoteFileOpItem {
+        path,
+        new_path: None,
+    }
 }
 
-fn should_skip_dir(root: &Path, path: &Path, filters: &FilterConfig) -> bool {
-    if path == root {
-        return false;
-    }
-    if !filters.flags.has_dir_excludes {
-        return false;
+fn file_op_sources(items: &[RemoteFileOpItem]) -> Vec<String> {
+    items.iter().map(|item| item.path.clone()).collect()
+}
+
+// Applies the operation to every item and reports each one, so a single failure does not
+// hide what already happened to the rest.
+fn handle_remote_file_op(
+    hub: &RemoteHub,
+    sender: &mpsc::Sender<String>,
+    grant: &RemoteGrant,
+    id: Option<String>,
+    kind: RemoteFileOpKind,
+    items: Vec<RemoteFileOpItem>,
+) -> Result<(), String> {
+    if !hub.file_ops {
+        return reject_remote(sender, id.as_deref(), "file-ops-disabled");
     }
-    let path_str = if filters.flags.needs_path {
-        Some(path.to_string_lossy().to_lowercase())
-    } else {
-        None
-    };
-    let name_str = if filters.flags.needs_name {
-        Some(get_entry_name_lower(path))
-    } else {
-        None
-    };
-    if let Some(path_value) = path_str.as_deref() {
-        if matches_regex(path_value, &filters.exclude_regex) {
-            return true;
-        }
-        if path_contains_any(path_value, &filters.exclude_paths) {
-            return true;
-        }
+    if items.len() > MAX_FILE_OP_ITEMS {
+        return reject_remote(sender, id.as_deref(), "too-many-items");
     }
-    if let Some(name_value) = name_str.as_deref() {
-        return path_contains_any(name_value, &filters.exclude_names);
+    let results: Vec<RemoteFileOpResult> = items
+        .into_iter()
+        .map(|item| {
+            let outcome = apply_remote_file_op(kind, grant, &item.path, item.new_path.as_deref());
+            if let Err(error) = outcome.as_ref() {
+                eprintln!("[remote] {} failed {:?}: {error}", kind.action(), item.path);
+            }
+            RemoteFileOpResult {
+                path: item.path,
+                new_path: item.new_path,
+                ok: outcome.is_ok(),
+                error: outcome.err(),
+            }
+        })
+        .collect();
+    let success_count = results.iter().filter(|result| result.ok).count();
+    let error_count = results.len() - success_count;
+    send_remote_event(
+        sender,
+        serde_json::json!({
+          "event": "file-op-result",
+          "id": id,
+          "action": kind.action(),
+          "data": {
+            "successCount": success_count,
+            "errorCount": error_count,
+            "results": results
+          }
+        }),
+    );
+    if error_count > 0 {
+        return Err("items-failed".to_string());
     }
-    false
+    Ok(())
 }
 
-fn should_include_file(
-    path: &Path,
-    size_bytes: u64,
-    modified: Option<u64>,
-    filters: &FilterConfig,
-) -> bool {
-    if let Some(min_size) = filters.min_size_bytes {
-        if size_bytes < min_size {
-            return false;
-        }
+fn apply_remote_file_op(
+    kind: RemoteFileOpKind,
+    grant: &RemoteGrant,
+    path: &str,
+    new_path: Option<&str>,
+) -> Result<(), String> {
+    let source = Path::new(path);
+    if !grant.allows_path(source) {
+        return Err("forbidden-path".to_string());
     }
-    if let Some(max_size) = filters.max_size_bytes {
-        if size_bytes > max_size {
-            return false;
+    let destination = match kind {
+        RemoteFileOpKind::Delete | RemoteFileOpKind::Mkdir => None,
+        _ => {
+            let value = new_path.ok_or_else(|| "missing-new-path".to_string())?;
+            let destination = Path::new(value);
+            if !grant.allows_path(destination) {
+                return Err("forbidden-path".to_string());
+            }
+            // Copying or moving a folder into itself would never finish or always fail.
+            let inside_source = canonicalize_for_check(destination)
+                .zip(canonicalize_for_check(source))
+                .is_some_and(|(target, origin)| target.starts_with(origin));
+            if inside_source {
+                return Err("invalid-destination".to_string());
+            }
+            Some(destination)
         }
+    };
+    let removes_source = matches!(
+        kind,
+        RemoteFileOpKind::Delete | RemoteFileOpKind::Rename | RemoteFileOpKind::Move
+    );
+    if removes_source && grant.protects(source) {
+        return Err("protected-path".to_string());
     }
-    if let Some(min_ts) = filters.min_modified_timestamp {
-        if modified.map_or(false, |ts| ts < min_ts) {
-            return false;
-        }
+    match (kind, destination) {
+        (RemoteFileOpKind::Delete, _) => remove_path(source),
+        (RemoteFileOpKind::Mkdir, _) => create_folder_path(source),
+        (RemoteFileOpKind::Copy, Some(destination)) => copy_path(source, destination),
+        (_, Some(destination)) => rename_path(source, destination),
+        (_, None) => Err("missing-new-path".to_string()),
     }
-    if let Some(max_ts) = filters.max_modified_timestamp {
-        if modified.map_or(false, |ts| ts > max_ts) {
-            return false;
+}
+
+fn handle_remote_list(
+    sender: &mpsc::Sender<String>,
+    grant: &RemoteGrant,
+    request: RemoteListRequest,
+) -> Result<(), String> {
+    let id = request.id.clone();
+    eprintln!("[remote] handle list {:?} {:?}", id, request.path);
+    let target = match (grant.roots.as_ref(), request.path.as_deref().map(str::trim)) {
+        // A restricted token sees its roots in place of the drive or filesystem root.
+        (Some(roots), None | Some("")) => Ok((list_allowed_roots(roots, &request), None)),
+        (Some(_), Some(value)) if !grant.allows_path(Path::new(value)) => {
+            Err("forbidden-path".to_string())
         }
-    }
-    let path_str = if filters.flags.needs_path {
-        Some(path.to_string_lossy().to_lowercase())
-    } else {
-        None
-    };
-    let name_str = if filters.flags.needs_name {
-        Some(get_entry_name_lower(path))
-    } else {
-        None
+        _ => resolve_list_target(&request),
     };
-    let ext = if filters.flags.needs_extension {
-        path.extension()
-            .and_then(|value| value.to_str())
-            .map(|value| value.to_lowercase())
-    } else {
-        None
+    let (mut entries, list_path) = match target {
+        Ok(value) => value,
+        Err(message) => {
+            eprintln!("[remote] list error {:?}", message);
+            send_remote_event(
+                sender,
+                serde_json::json!({ "event": "list-error", "id": id, "message": message }),
+            );
+            return Err(message);
+        }
     };
-    if filters.flags.has_file_excludes {
-        if let Some(path_value) = path_str.as_deref() {
-            if matches_regex(path_value, &filters.exclude_regex) {
-                return false;
-            }
-            if path_contains_any(path_value, &filters.exclude_paths) {
-                return false;
+    sort_list_entries(&mut entries, request.sort, request.descending);
+    let total = entries.len();
+    let offset = request.offset.min(total);
+    let limit = request
+        .limit
+        .map_or(total, |limit| limit.clamp(1, MAX_LIST_PAGE_SIZE));
+    let entries: Vec<RemoteListEntry> = entries.into_iter().skip(offset).take(limit).collect();
+    let end = offset + entries.len();
+    let next_offset = (end < total).then_some(end);
+    eprintln!(
+        "[remote] list ok {:?} entries={} total={}",
+        list_path,
+        entries.len(),
+        total
+    );
+    let payload = serde_json::json!({
+      "event": "list-complete",
+      "id": id,
+      "data": {
+        "path": list_path,
+        "entries": entries,
+        "os": if cfg!(target_os = "windows") { "windows" } else { "unix" },
+        "total": total,
+        "offset": offset,
+        "nextOffset": next_offset
+      }
+    });
+    send_remote_event(sender, payload);
+    Ok(())
+}
+
+fn list_allowed_roots(roots: &[PathBuf], request: &RemoteListRequest) -> Vec<RemoteListEntry> {
+    roots
+        .iter()
+        .map(|root| {
+            let entry = RemoteListEntry::new(
+                get_entry_name_string(root),
+                root.to_string_lossy().to_string(),
+                root.is_dir(),
+            );
+            if request.wants_details() {
+                entry.with_details(root)
+            } else {
+                entry
             }
+        })
+        .collect()
+}
+
+fn resolve_list_target(
+    request: &RemoteListRequest,
+) -> Result<(Vec<RemoteListEntry>, Option<String>), String> {
+    let trimmed = request.path.as_deref().unwrap_or("").trim();
+    if trimmed.is_empty() {
+        #[cfg(target_os = "windows")]
+        {
+            return Ok((list_windows_drives(), None));
         }
-        if let Some(name_value) = name_str.as_deref() {
-            if path_contains_any(name_value, &filters.exclude_names) {
-                return false;
-            }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let root = PathBuf::from("/");
+            let entries = list_directory_entries(&root, request)?;
+            return Ok((entries, Some("/".to_string())));
         }
-        if let Some(ext_value) = ext.as_ref() {
-            if filters.exclude_extensions.contains(ext_value) {
-                return false;
-            }
+    }
+    #[cfg(target_os = "windows")]
+    {
+        if trimmed == "/" || trimmed == "\\" {
+            return Ok((list_windows_drives(), None));
         }
     }
-
-    if !filters.flags.has_includes {
-        return true;
+    let target = PathBuf::from(trimmed);
+    if !target.exists() {
+        return Err("path-not-found".to_string());
     }
+    let entries = list_directory_entries(&target, request)?;
+    Ok((entries, Some(trimmed.to_string())))
+}
 
-    if let Some(path_value) = path_str.as_deref() {
-        if matches_regex(path_value, &filters.include_regex) {
-            return true;
-        }
-        if path_contains_any(path_value, &filters.include_paths) {
-            return true;
+fn list_directory_entries(
+    path: &Path,
+    request: &RemoteListRequest,
+) -> Result<Vec<RemoteListEntry>, String> {
+    let mut entries: Vec<RemoteListEntry> = Vec::new();
+    let read_dir = fs::read_dir(path).map_err(|error| format!("list-failed: {error}"))?;
+    for entry in read_dir {
+        let entry = match entry {
+            Ok(value) => value,
+            Err(error) => {
+                eprintln!("[remote] list entry error: {error}");
+                continue;
+            }
+        };
+        let entry_path = entry.path();
+        let is_dir = entry
+            .file_type()
+            .map(|value| value.is_dir())
+            .unwrap_or(false);
+        if !is_dir && !request.include_files {
+            continue;
         }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path_str = entry_path.to_string_lossy().to_string();
+        let item = RemoteListEntry::new(name, path_str, is_dir);
+        entries.push(if request.wants_details() {
+            item.with_details(&entry_path)
+        } else {
+            item
+        });
     }
-    if let Some(name_value) = name_str.as_deref() {
-        if path_contains_any(name_value, &filters.include_names) {
-            return true;
-        }
+    Ok(entries)
+}
+
+fn sort_list_entries(entries: &mut [RemoteListEntry], sort: RemoteListSort, descending: bool) {
+    match sort {
+        RemoteListSort::Name => entries.sort_by_cached_key(|entry| entry.name.to_lowercase()),
+        RemoteListSort::Size => entries
+            .sort_by_cached_key(|entry| (entry.size_bytes.unwrap_or(0), entry.name.to_lowercase())),
+        RemoteListSort::Modified => entries
+            .sort_by_cached_key(|entry| (entry.modified.unwrap_or(0), entry.name.to_lowercase())),
+        RemoteListSort::Type => entries.sort_by_cached_key(|entry| {
+            let extension = Path::new(&entry.name)
+                .extension()
+                .map(|value| value.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            (!entry.is_dir, extension, entry.name.to_lowercase())
+        }),
     }
-    if let Some(ext_value) = ext.as_ref() {
-        return filters.include_extensions.contains(ext_value);
+    if descending {
+        entries.reverse();
     }
-
-    false
 }
 
-fn resolve_parallelism(priority_mode: &ScanPriorityMode) -> Parallelism {
-    let available = thread::available_parallelism()
-        .map(|value| value.get())
-        .unwrap_or(1);
-    let threads = match priority_mode {
-        ScanPriorityMode::Performance => available,
-        ScanPriorityMode::Balanced => (available + 1) / 2,
-        ScanPriorityMode::Low => 1,
-    };
-    if threads <= 1 {
-        return Parallelism::Serial;
-    }
-    Parallelism::RayonNewPool(threads)
+#[cfg(unix)]
+fn format_permissions(metadata: &fs::Metadata) -> Option<String> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = metadata.permissions().mode();
+    let flags = ['r', 'w', 'x'];
+    Some(
+        (0..9)
+            .map(|bit| {
+                if mode & (1 << (8 - bit)) != 0 {
+                    flags[bit % 3]
+                } else {
+                    '-'
+                }
+            })
+            .collect(),
+    )
 }
 
-fn matches_regex(value: &str, regex: &Option<Regex>) -> bool {
-    regex
-        .as_ref()
-        .map_or(false, |pattern| pattern.is_match(value))
+#[cfg(not(unix))]
+fn format_permissions(_metadata: &fs::Metadata) -> Option<String> {
+    None
 }
 
-fn path_contains_any(path: &str, values: &[String]) -> bool {
-    for value in values {
-        if value.is_empty() {
+#[cfg(target_os = "windows")]
+fn list_windows_drives() -> Vec<RemoteListEntry> {
+    let mut entries = Vec::new();
+    for letter in b'A'..=b'Z' {
+        let drive = format!("{}:\\", letter as char);
+        let path = Path::new(&drive);
+        if !path.exists() {
             continue;
         }
-        if path.contains(value) {
-            return true;
-        }
+        entries.push(RemoteListEntry::new(drive.clone(), drive, true));
     }
-    false
+    entries
 }
 
-fn parse_runtime_options(
-    args: &[String],
-    startup_path: Option<String>,
-    settings: &AppSettings,
-) -> Result<RuntimeOptions, String> {
-    let headless = has_flag(args, "--headless")
-        || env_flag("DRAGABYTE_HEADLESS")
-        || settings.headless.unwrap_or(false);
-    let tcp = parse_tcp_config(args, settings)?;
-    let updater_enabled = resolve_updater_enabled(args, settings);
-    Ok(RuntimeOptions {
-        headless,
-        tcp,
-        startup_path,
-        updater_enabled,
-    })
+fn send_remote_event(sender: &mpsc::Sender<String>, value: serde_json::Value) {
+    let _ = sender.send(format!("{}\n", value));
 }
 
-fn parse_tcp_config(args: &[String], settings: &AppSettings) -> Result<Option<TcpConfig>, String> {
-    let bind_arg = get_arg_value(args, "--tcp-bind");
-    let env_bind = std::env::var("DRAGABYTE_TCP_BIND").ok();
-    let token = get_arg_value(args, "--tcp-token")
-        .or_else(|| std::env::var("DRAGABYTE_TCP_TOKEN").ok())
-        .or_else(|| settings.local_token.clone());
-    let enabled = has_flag(args, "--tcp")
-        || bind_arg.is_some()
-        || env_bind.is_some()
-        || settings.tcp_bind.is_some()
-        || settings.local_token.is_some();
-    if !enabled {
-        return Ok(None);
-    }
-    let bind_raw = bind_arg
-        .or_else(|| env_bind)
-        .or_else(|| settings.tcp_bind.clone())
-        .unwrap_or_else(|| "127.0.0.1:4799".to_string());
-    let bind_addr = bind_raw
-        .parse::<SocketAddr>()
-        .map_err(|_| "Invalid TCP bind address".to_string())?;
-    if !bind_addr.ip().is_loopback() && token.is_none() {
-        return Err("DRAGABYTE_TCP_TOKEN is required when binding to non-loopback".to_string());
-    }
-    Ok(Some(TcpConfig { bind_addr, token }))
+fn send_remote_error(sender: &mpsc::Sender<String>, id: Option<&str>, message: &str) {
+    send_remote_event(
+        sender,
+        serde_json::json!({ "event": "error", "id": id, "message": message }),
+    );
 }
 
-fn resolve_updater_enabled(args: &[String], settings: &AppSettings) -> bool {
-    if has_flag(args, "--disable-updater") || env_flag("DRAGABYTE_DISABLE_UPDATER") {
-        return false;
+// Sends the error and hands the code back as the request's outcome.
+fn reject_remote<T>(
+    sender: &mpsc::Sender<String>,
+    id: Option<&str>,
+    code: &str,
+) -> Result<T, String> {
+    send_remote_error(sender, id, code);
+    Err(code.to_string())
+}
+
+fn request_id(request: &RemoteRequest) -> Option<&str> {
+    match request {
+        RemoteRequest::Ping { id }
+        | RemoteRequest::Auth { id, .. }
+        | RemoteRequest::Disk { id, .. }
+        | RemoteRequest::Read { id, .. }
+        | RemoteRequest::Delete { id, .. }
+        | RemoteRequest::Mkdir { id, .. }
+        | RemoteRequest::Rename { id, .. }
+        | RemoteRequest::Copy { id, .. }
+        | RemoteRequest::Move { id, .. }
+        | RemoteRequest::Scan { id, .. }
+        | RemoteRequest::Cancel { id }
+        | RemoteRequest::Pause { id }
+        | RemoteRequest::Resume { id }
+        | RemoteRequest::Status { id }
+        | RemoteRequest::Result { id }
+        | RemoteRequest::Subscribe { id }
+        | RemoteRequest::Unsubscribe { id }
+        | RemoteRequest::Metrics { id }
+        | RemoteRequest::Shutdown { id } => id.as_deref(),
+        RemoteRequest::Hello(request) => request.id.as_deref(),
+        RemoteRequest::List(request) => request.id.as_deref(),
+        RemoteRequest::Download(request) => request.id.as_deref(),
     }
-    settings.auto_update.unwrap_or(true)
 }
 
-fn env_flag(name: &str) -> bool {
-    match std::env::var(name) {
-        Ok(value) => matches!(value.to_lowercase().as_str(), "1" | "true" | "yes"),
-        Err(_) => false,
+fn resolve_settings_path(args: &[String]) -> PathBuf {
+    if let Some(path) = get_arg_value(args, "--settings") {
+        return PathBuf::from(path);
+    }
+    if let Ok(path) = std::env::var("DRAGABYTE_SETTINGS_PATH") {
+        return PathBuf::from(path);
     }
+    std::env::current_dir()
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join("dragabyte.settings.json")
 }
 
-fn has_flag(args: &[String], flag: &str) -> bool {
-    args.iter().any(|value| value == flag)
+fn load_settings(path: &Path) -> AppSettings {
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    if contents.trim().is_empty() {
+        return AppSettings::default();
+    }
+    serde_json::from_str(&contents).unwrap_or_default()
 }
 
-fn get_arg_value(args: &[String], prefix: &str) -> Option<String> {
-    for value in args {
-        if let Some(stripped) = value.strip_prefix(&format!("{}=", prefix)) {
-            return Some(stripped.to_string());
+fn save_settings(path: &Path, settings: &AppSettings) -> Result<(), String> {
+    let payload = serde_json::to_string_pretty(settings)
+        .map_err(|error| format!("Failed to serialize settings: {error}"))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(file) = fs::File::create(path) {
+            let mut perms = file.metadata().map_err(|e| e.to_string())?.permissions();
+            perms.set_mode(0o600); // Read/write for owner only
+            file.set_permissions(perms).map_err(|e| e.to_string())?;
+            // Write content after setting permissions
+            let mut writer = std::io::BufWriter::new(file);
+            writer
+                .write_all(payload.as_bytes())
+                .map_err(|e| e.to_string())?;
+            return Ok(());
         }
     }
-    None
+
+    fs::write(path, payload).map_err(|error| format!("Failed to save settings: {error}"))
 }
 
-struct RemoteServerHandle {
-    shutdown: mpsc::Sender<()>,
-    join: thread::JoinHandle<()>,
+fn apply_settings_update(settings: &mut AppSettings, update: AppSettingsUpdate) {
+    if update.local_token.is_some() {
+        settings.local_token = update.local_token;
+    }
+    if update.tcp_bind.is_some() {
+        settings.tcp_bind = update.tcp_bind;
+    }
+    if update.headless.is_some() {
+        settings.headless = update.headless;
+    }
+    if update.auto_update.is_some() {
+        settings.auto_update = update.auto_update;
+    }
+    if update.max_concurrent_scans.is_some() {
+        settings.max_concurrent_scans = update.max_concurrent_scans;
+    }
+    if update.tls_cert_path.is_some() {
+        settings.tls_cert_path = update.tls_cert_path;
+    }
+    if update.tls_key_path.is_some() {
+        settings.tls_key_path = update.tls_key_path;
+    }
+    if update.tls_client_ca_path.is_some() {
+        settings.tls_client_ca_path = update.tls_client_ca_path;
+    }
+    if update.api_tokens.is_some() {
+        settings.api_tokens = update.api_tokens;
+    }
+    if update.allowed_roots.is_some() {
+        settings.allowed_roots = update.allowed_roots;
+    }
+    if update.audit_log_path.is_some() {
+        settings.audit_log_path = update.audit_log_path;
+    }
+    if update.remote_file_ops.is_some() {
+        settings.remote_file_ops = update.remote_file_ops;
+    }
+    if update.allow_legacy_tokens.is_some() {
+        settings.allow_legacy_tokens = update.allow_legacy_tokens;
+    }
+    if update.ws_bind.is_some() {
+        settings.ws_bind = update.ws_bind;
+    }
+    if update.ws_allowed_origins.is_some() {
+        settings.ws_allowed_origins = update.ws_allowed_origins;
+    }
+    if update.http_bind.is_some() {
+        settings.http_bind = update.http_bind;
+    }
+    if update.socket_path.is_some() {
+        settings.socket_path = update.socket_path;
+    }
+    if update.mdns.is_some() {
+        settings.mdns = update.mdns;
+    }
+    if update.watch_paths.is_some() {
+        settings.watch_paths = update.watch_paths;
+    }
+    if update.watch_interval_secs.is_some() {
+        settings.watch_interval_secs = update.watch_interval_secs;
+    }
+    if update.alert_rules.is_some() {
+        settings.alert_rules = update.alert_rules;
+    }
+    if update.alert_webhook.is_some() {
+        settings.alert_webhook = update.alert_webhook;
+    }
+    if update.alert_webhook_ca_path.is_some() {
+        settings.alert_webhook_ca_path = update.alert_webhook_ca_path;
+    }
+    if update.alert_command.is_some() {
+        settings.alert_command = update.alert_command;
+    }
+    if update.alert_interval_secs.is_some() {
+        settings.alert_interval_secs = update.alert_interval_secs;
+    }
+    if update.remote_hosts.is_some() {
+        settings.remote_hosts = update.remote_hosts;
+    }
 }
 
-struct RemoteClientHandle {
-    sender: mpsc::Sender<String>,
-    shutdown: mpsc::Sender<()>,
-    join: thread::JoinHandle<()>,
-    token: Option<String>,
-    address: String,
+#[tauri::command]
+fn get_settings(state: tauri::State<SettingsState>) -> Result<AppSettings, String> {
+    let guard = state
+        .value
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?;
+    Ok(guard.clone())
+}
+
+#[tauri::command]
+fn update_settings(
+    state: tauri::State<SettingsState>,
+    update: AppSettingsUpdate,
+) -> Result<AppSettings, String> {
+    let mut guard = state
+        .value
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?;
+    apply_settings_update(&mut guard, update);
+    save_settings(&state.path, &guard)?;
+    Ok(guard.clone())
+}
+
+fn emit_remote_status(
+    app: &tauri::AppHandle,
+    status: &str,
+    message: Option<String>,
+    address: Option<String>,
+) {
+    let payload = serde_json::json!({
+      "status": status,
+      "message": message,
+      "address": address
+    });
+    let _ = app.emit("remote-status", payload);
+}
+
+fn build_remote_payload(payload: JsonValue) -> String {
+    eprintln!("[remote] build payload input={}", payload);
+    format!("{}\n", payload)
+}
+
+fn stop_remote_server(handle: RemoteServerHandle) {
+    let _ = handle.shutdown.send(());
+    let _ = handle.join.join();
+}
+
+fn stop_remote_client(handle: RemoteClientHandle) {
+    let _ = handle.shutdown.send(());
+    drop(handle.sender);
+    let _ = handle.join.join();
+}
+
+fn open_remote_client_connection(
+    target: &RemoteClientTarget,
+) -> Result<RemoteClientConnection, String> {
+    let tls = target
+        .tls
+        .as_ref()
+        .map(|options| build_tls_client_connection(&target.host, options))
+        .transpose()?;
+    let address = &target.address;
+    let stream = TcpStream::connect(address)
+        .map_err(|error| format!("Failed to connect to {address}: {error}"))?;
+    stream
+        .set_read_timeout(Some(Duration::from_millis(200)))
+        .map_err(|error| format!("Failed to configure TCP stream: {error}"))?;
+    let (read_half, mut write_half) = open_remote_stream(stream, tls)
+        .map_err(|error| format!("Failed to open connection to {address}: {error}"))?;
+    let mut reader = BufReader::new(read_half);
+    authenticate_remote_client(&mut reader, &mut write_half, target.token.as_deref())?;
+    Ok((reader, write_half))
+}
+
+// Redialing cannot fix a rejected token, so those errors end the reconnect loop.
+fn is_remote_auth_rejection(error: &str) -> bool {
+    error == "Unauthorized token" || error == "The server requires an access token"
 }
 
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-struct RemoteListEntry {
-    name: String,
-    path: String,
-    is_dir: bool,
+fn remote_reconnect_delay(attempt: u32) -> Duration {
+    let factor = 1u32 << attempt.saturating_sub(1).min(16);
+    REMOTE_RECONNECT_MIN_DELAY
+        .saturating_mul(factor)
+        .min(REMOTE_RECONNECT_MAX_DELAY)
 }
 
-fn start_remote_server(config: TcpConfig, headless: bool) -> Result<RemoteServerHandle, String> {
-    eprintln!("[remote] starting tcp server on {}", config.bind_addr);
-    let listener = TcpListener::bind(config.bind_addr)
-        .map_err(|error| format!("Failed to bind TCP server: {error}"))?;
-    listener
-        .set_nonblocking(true)
-        .map_err(|error| format!("Failed to configure TCP listener: {error}"))?;
-    let (shutdown_tx, shutdown_rx) = mpsc::channel();
-    let hub = Arc::new(RemoteHub::new(
-        config.token.clone(),
-        Some(shutdown_tx.clone()),
-    ));
-    let join = thread::spawn(move || loop {
-        if shutdown_rx.try_recv().is_ok() {
-            break;
-        }
-        match listener.accept() {
-            Ok((stream, _)) => {
-                if let Ok(clients) = hub.clients.lock() {
-                    if clients.len() >= MAX_CONNECTIONS {
-                        eprintln!("[remote] connection limit reached, rejecting");
-                        continue;
-                    }
+fn spawn_remote_client(
+    app: tauri::AppHandle,
+    target: RemoteClientTarget,
+    connection: RemoteClientConnection,
+) -> RemoteClientHandle {
+    let (placeholder, _) = mpsc::channel::<String>();
+    let sender = Arc::new(Mutex::new(placeholder));
+    let connected = Arc::new(AtomicBool::new(false));
+    let requests = Arc::new(RemoteRequestTracker::default());
+    let (shutdown_tx, shutdown_rx) = mpsc::channel::<()>();
+    let address = target.address.clone();
+    let thread_sender = Arc::clone(&sender);
+    let thread_connected = Arc::clone(&connected);
+    let thread_requests = Arc::clone(&requests);
+    let join = thread::spawn(move || {
+        let address = target.address.clone();
+        let mut connection = Some(connection);
+        let mut attempt: u32 = 0;
+        let failure = loop {
+            if let Some((mut reader, write_half)) = connection.take() {
+                let (line_tx, line_rx) = mpsc::channel::<String>();
+                if let Ok(mut guard) = thread_sender.lock() {
+                    *guard = line_tx;
+                }
+                thread::spawn(move || write_remote_lines(write_half, line_rx, None));
+                thread_connected.store(true, Ordering::Relaxed);
+                let closed =
+                    read_remote_events(&app, &mut reader, &address, &shutdown_rx, &thread_requests);
+                thread_requests.abandon();
+                if closed {
+                    return;
                 }
-                eprintln!("[remote] tcp client accepted");
-                let hub_clone = Arc::clone(&hub);
-                thread::spawn(move || handle_client(stream, hub_clone, headless));
+                thread_connected.store(false, Ordering::Relaxed);
             }
-            Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => {
-                thread::sleep(Duration::from_millis(50));
+            if !target.reconnect {
+                break None;
             }
-            Err(_) => break,
+            attempt = attempt.saturating_add(1);
+            let delay = remote_reconnect_delay(attempt);
+            emit_remote_status(
+                &app,
+                "reconnecting",
+                Some(format!(
+                    "Connection lost, retrying in {}s (attempt {attempt})",
+                    delay.as_secs()
+                )),
+                Some(address.clone()),
+            );
+            if !matches!(
+                shutdown_rx.recv_timeout(delay),
+                Err(mpsc::RecvTimeoutError::Timeout)
+            ) {
+                return;
+            }
+            match open_remote_client_connection(&target) {
+                Ok(value) => {
+                    eprintln!("[remote] reconnected {address} after {attempt} attempt(s)");
+                    attempt = 0;
+                    connection = Some(value);
+                    emit_remote_status(&app, "connected", None, Some(address.clone()));
+                }
+                Err(error) if is_remote_auth_rejection(&error) => break Some(error),
+                Err(error) => eprintln!("[remote] reconnect {address} failed: {error}"),
+            }
+        };
+        forget_remote_client(&app, &address);
+        match failure {
+            Some(error) => emit_remote_status(&app, "error", Some(error), Some(address)),
+            None => emit_remote_status(&app, "disconnected", None, Some(address)),
         }
     });
-    Ok(RemoteServerHandle {
+    RemoteClientHandle {
+        sender,
         shutdown: shutdown_tx,
         join,
-    })
+        address,
+        connected,
+        requests,
+    }
 }
 
-fn handle_client(stream: TcpStream, hub: Arc<RemoteHub>, headless: bool) {
-    eprintln!("[remote] tcp client connected");
-    if let Err(error) = stream.set_read_timeout(Some(Duration::from_millis(200))) {
-        eprintln!("[remote] set read timeout failed: {error}");
-    }
-    let (sender, receiver) = mpsc::channel::<String>();
-    hub.register_client(sender.clone());
-    let writer_stream = match stream.try_clone() {
-        Ok(clone) => clone,
-        Err(_) => return,
-    };
-    thread::spawn(move || write_remote_lines(writer_stream, receiver));
-    let mut reader = BufReader::new(stream);
+// Forwards server events until the socket drops. Returns true when the connection was
+// closed on request rather than lost.
+fn read_remote_events(
+    app: &tauri::AppHandle,
+    reader: &mut BufReader<RemoteReadHalf>,
+    address: &str,
+    shutdown: &mpsc::Receiver<()>,
+    requests: &RemoteRequestTracker,
+) -> bool {
+    let mut downloads: HashMap<String, RemoteDownloadSink> = HashMap::new();
     loop {
-        let line = match read_secure_line(&mut reader, MAX_LINE_LENGTH) {
-            Ok(Some(value)) => {
-                eprintln!("[remote] read line bytes={}", value.len());
-                value
-            }
+        if !matches!(shutdown.try_recv(), Err(mpsc::TryRecvError::Empty)) {
+            return true;
+        }
+        match read_secure_line(reader, MAX_LINE_LENGTH) {
             Ok(None) => break,
+            Ok(Some(line)) => {
+                let trimmed = line.trim();
+                if trimmed.is_empty() {
+                    continue;
+                }
+                if let Ok(mut value) = serde_json::from_str::<JsonValue>(trimmed) {
+                    if !track_remote_download(&mut downloads, requests, &mut value) {
+                        continue;
+                    }
+                    if let JsonValue::Object(ref mut map) = value {
+                        map.insert(
+                            "_address".to_string(),
+                            JsonValue::String(address.to_string()),
+                        );
+                    }
+                    if requests.resolve(&value) {
+                        continue;
+                    }
+                    let _ = app.emit("remote-event", value);
+                }
+            }
             Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => continue,
             Err(error) if error.kind() == std::io::ErrorKind::TimedOut => continue,
-            Err(error) => {
-                eprintln!("[remote] read line error: {error}");
-                break;
-            }
-        };
-        if line.trim().is_empty() {
-            eprintln!("[remote] read empty line");
-            continue;
+            Err(_) => break,
         }
-        handle_remote_line(&line, Arc::clone(&hub), &sender, headless);
     }
-}
-
-fn write_remote_lines(mut stream: TcpStream, receiver: mpsc::Receiver<String>) {
-    for line in receiver {
-        eprintln!("[remote] sending line bytes={}", line.len());
-        if let Err(error) = stream.write_all(line.as_bytes()) {
-            eprintln!("[remote] write failed: {error}");
-            break;
-        }
-        if let Err(error) = stream.flush() {
-            eprintln!("[remote] flush failed: {error}");
-            break;
-        }
+    // The server forgets unfinished downloads along with the connection. What arrived so
+    // far stays on disk, and `localPath` lets the dashboard resume from there.
+    for (id, sink) in downloads {
+        let _ = app.emit(
+            "remote-event",
+            serde_json::json!({
+              "event": "error",
+              "id": id,
+              "message": "connection-lost",
+              "localPath": sink.path.to_string_lossy(),
+              "_address": address
+            }),
+        );
     }
+    false
 }
 
-fn handle_remote_line(
-    line: &str,
-    hub: Arc<RemoteHub>,
-    sender: &mpsc::Sender<String>,
-    headless: bool,
-) {
-    // Security: Do not log incoming lines as they may contain auth tokens
-    let envelope: RemoteEnvelope = match serde_json::from_str(line) {
-        Ok(value) => value,
-        Err(_) => {
-            eprintln!("[remote] invalid json");
-            send_remote_error(sender, None, "invalid_json");
-            return;
-        }
-    };
-    if !hub.validate_token(envelope.token.as_deref()) {
-        eprintln!("[remote] unauthorized token");
-        // Security: Artificial delay to impede brute-force attacks
-        thread::sleep(Duration::from_secs(2));
-        send_remote_error(sender, request_id(&envelope.request), "unauthorized");
+// Drops the entry for a client whose thread gave up on its own. A newer connection to
+// the same address may already have replaced it.
+fn forget_remote_client(app: &tauri::AppHandle, address: &str) {
+    let state = app.state::<RemoteClientState>();
+    let Ok(mut guard) = state.0.lock() else {
         return;
-    }
-    match envelope.request {
-        RemoteRequest::Ping { id } => {
-            eprintln!("[remote] ping {:?}", id);
-            send_remote_event(sender, serde_json::json!({ "event": "pong", "id": id }));
-        }
-        RemoteRequest::List { id, path } => {
-            eprintln!("[remote] list {:?} {:?}", id, path);
-            handle_remote_list(sender, id, path);
-        }
-        RemoteRequest::Disk { id, path } => {
-            eprintln!("[remote] disk {:?} {}", id, path);
-            handle_remote_disk(sender, id, path);
-        }
-        RemoteRequest::Read { id, path } => {
-            eprintln!("[remote] read {:?} {}", id, path);
-            handle_remote_read(sender, id, path);
-        }
-        RemoteRequest::Scan { id, path, options } => {
-            eprintln!("[remote] scan {:?} {}", id, path);
-            handle_remote_scan(hub, sender, id, path, options);
-        }
-        RemoteRequest::Cancel { id } => {
-            eprintln!("[remote] cancel {:?}", id);
-            let cancelled = hub.cancel_scan();
-            let message = if cancelled {
-                "cancel-requested"
-            } else {
-                "no-active-scan"
-            };
-            send_remote_event(sender, serde_json::json!({ "event": message, "id": id }));
-        }
-        RemoteRequest::Shutdown { id } => {
-            eprintln!("[remote] shutdown {:?}", id);
-            if !headless {
-                send_remote_error(sender, id.as_deref(), "shutdown-not-allowed");
-                return;
-            }
-            if hub.request_shutdown() {
-                send_remote_event(sender, serde_json::json!({ "event": "shutdown", "id": id }));
-            } else {
-                send_remote_error(sender, id.as_deref(), "shutdown-failed");
-            }
-        }
+    };
+    let current = thread::current().id();
+    if guard
+        .get(address)
+        .is_some_and(|handle| handle.join.thread().id() == current)
+    {
+        guard.remove(address);
     }
 }
 
-fn handle_remote_scan(
-    hub: Arc<RemoteHub>,
-    sender: &mpsc::Sender<String>,
-    id: Option<String>,
-    path: String,
-    options: Option<ScanOptions>,
-) {
-    let root = PathBuf::from(&path);
-    if !root.exists() {
-        send_remote_error(sender, id.as_deref(), "path-not-found");
-        return;
-    }
-    let config = match build_scan_config(&options.unwrap_or_default()) {
-        Ok(value) => value,
-        Err(error) => {
-            send_remote_error(sender, id.as_deref(), &error);
-            return;
+fn resolve_remote_token_ref(reference: &str) -> Result<String, String> {
+    let token = match reference.trim().split_once(':') {
+        Some(("env", name)) => {
+            std::env::var(name).map_err(|_| format!("Token variable {name} is not set"))?
         }
+        Some(("file", path)) => fs::read_to_string(path)
+            .map_err(|error| format!("Failed to read token file {path}: {error}"))?,
+        _ => return Err(format!("Unsupported token reference: {reference}")),
     };
-    let cancel_flag = Arc::new(AtomicBool::new(false));
-    if !hub.start_scan(Arc::clone(&cancel_flag)) {
-        send_remote_error(sender, id.as_deref(), "scan-in-progress");
-        return;
+    let token = token.trim().to_string();
+    if token.is_empty() {
+        return Err(format!("Token reference {reference} is empty"));
     }
-    send_remote_event(
-        sender,
-        serde_json::json!({ "event": "scan-started", "id": id }),
-    );
-    let hub_for_scan = Arc::clone(&hub);
-    let request_id = id.clone();
-    thread::spawn(move || {
-        let hub_ref = Arc::clone(&hub_for_scan);
-        let request_id_for_emit = request_id.clone();
-        let emitter_hub = Arc::clone(&hub_ref);
-        let emitter: ScanEmitter = Arc::new(move |event| {
-            emit_to_remote(&emitter_hub, event, request_id_for_emit.as_deref());
-        });
-        if let Err(error) = run_scan(root, config, Arc::clone(&cancel_flag), emitter, id.clone()) {
-            emit_to_remote(&hub_ref, ScanEvent::Error(error), request_id.as_deref());
-        }
-        hub_ref.finish_scan();
-    });
+    Ok(token)
 }
 
-fn handle_remote_disk(sender: &mpsc::Sender<String>, id: Option<String>, path: String) {
-    let target = PathBuf::from(&path);
-    match compute_disk_usage(&target) {
-        Ok(snapshot) => {
-            send_remote_event(
-                sender,
-                serde_json::json!({ "event": "disk-info", "id": id, "data": snapshot }),
-            );
+// Chunks of a download requested by the dashboard go straight to a temp file instead of
+// through the webview. Returns false for events that should not be forwarded.
+struct RemoteDownloadSink {
+    path: PathBuf,
+    file: fs::File,
+    // Bytes of this transfer, checked against `sha256`.
+    digest: ring::digest::Context,
+    // The whole file when resuming, checked against `fileSha256`.
+    whole: Option<ring::digest::Context>,
+}
+
+impl RemoteDownloadSink {
+    fn create(name: &str) -> std::io::Result<Self> {
+        let (path, file) = create_download_file(name)?;
+        Ok(Self {
+            path,
+            file,
+            digest: ring::digest::Context::new(&ring::digest::SHA256),
+            whole: None,
+        })
+    }
+
+    // Reopens a partial download for appending, after hashing what it already holds.
+    fn resume(path: PathBuf, offset: u64) -> std::io::Result<Self> {
+        let mut file = fs::OpenOptions::new().read(true).append(true).open(&path)?;
+        let mut whole = ring::digest::Context::new(&ring::digest::SHA256);
+        let mut buffer = vec![0u8; 64 * 1024];
+        let mut length = 0u64;
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            whole.update(&buffer[..read]);
+            length += read as u64;
         }
-        Err(message) => {
-            send_remote_event(
-                sender,
-                serde_json::json!({ "event": "disk-error", "id": id, "message": message }),
-            );
+        if length != offset {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "the partial file holds {length} bytes, but the server resumed at {offset}"
+                ),
+            ));
         }
+        Ok(Self {
+            path,
+            file,
+            digest: ring::digest::Context::new(&ring::digest::SHA256),
+            whole: Some(whole),
+        })
     }
 }
 
-fn handle_remote_read(sender: &mpsc::Sender<String>, id: Option<String>, path: String) {
-    let target = PathBuf::from(&path);
-    if !target.exists() {
-        send_remote_error(sender, id.as_deref(), "path-not-found");
-        return;
-    }
-    if !target.is_file() {
-        send_remote_error(sender, id.as_deref(), "not-a-file");
-        return;
-    }
-    match fs::metadata(&target) {
-        Ok(meta) => {
-            if meta.len() > 5 * 1024 * 1024 {
-                send_remote_error(sender, id.as_deref(), "file-too-large");
-                return;
+fn track_remote_download(
+    downloads: &mut HashMap<String, RemoteDownloadSink>,
+    requests: &RemoteRequestTracker,
+    value: &mut JsonValue,
+) -> bool {
+    let event = value.get("event").and_then(|event| event.as_str());
+    let id = value.get("id").and_then(|id| id.as_str());
+    let (Some(event), Some(id)) = (event, id) else {
+        return true;
+    };
+    let id = id.to_string();
+    match event {
+        "download-start" => {
+            // The remote path may use either separator, whatever the local platform.
+            let name = value
+                .pointer("/data/path")
+                .and_then(|path| path.as_str())
+                .and_then(|path| path.rsplit(['/', '\\']).next())
+                .filter(|name| !name.is_empty() && *name != "..")
+                .unwrap_or("download");
+            let sink = match requests.take_resume(&id) {
+                Some(path) => {
+                    let offset = value
+                        .pointer("/data/offset")
+                        .and_then(|offset| offset.as_u64())
+                        .unwrap_or(0);
+                    RemoteDownloadSink::resume(path, offset)
+                }
+                None => RemoteDownloadSink::create(name),
+            };
+            match sink {
+                Ok(sink) => {
+                    downloads.insert(id, sink);
+                }
+                Err(error) => {
+                    *value = serde_json::json!({
+                      "event": "error",
+                      "id": id,
+                      "message": format!("Failed to write file: {error}")
+                    });
+                }
             }
+            true
         }
-        Err(e) => {
-            send_remote_error(sender, id.as_deref(), &e.to_string());
-            return;
+        "download-chunk" => {
+            let Some(sink) = downloads.get_mut(&id) else {
+                return false;
+            };
+            let written = value
+                .get("data")
+                .and_then(|data| data.as_str())
+                .and_then(|data| BASE64_STANDARD.decode(data).ok())
+                .ok_or_else(|| "Invalid download chunk".to_string())
+                .and_then(|bytes| {
+                    sink.digest.update(&bytes);
+                    if let Some(whole) = sink.whole.as_mut() {
+                        whole.update(&bytes);
+                    }
+                    sink.file
+                        .write_all(&bytes)
+                        .map_err(|error| format!("Failed to write file: {error}"))
+                });
+            let Err(message) = written else {
+                return false;
+            };
+            if let Some(sink) = downloads.remove(&id) {
+                let _ = fs::remove_file(sink.path);
+            }
+            *value = serde_json::json!({ "event": "error", "id": id, "message": message });
+            true
         }
-    }
-    match fs::read(&target) {
-        Ok(bytes) => {
-            let data = BASE64_STANDARD.encode(&bytes);
-            send_remote_event(
-                sender,
-                serde_json::json!({ "event": "read-complete", "id": id, "data": { "path": path, "content": data } }),
-            );
+        "download-complete" => {
+            let Some(sink) = downloads.remove(&id) else {
+                return true;
+            };
+            let actual = encode_hex(sink.digest.finish().as_ref());
+            let expected = value.pointer("/data/sha256").and_then(|hash| hash.as_str());
+            // `fileSha256` is only sent once the transfer reaches the end of the file.
+            let file_expected = value
+                .pointer("/data/fileSha256")
+                .and_then(|hash| hash.as_str());
+            let whole_matches = match (sink.whole, file_expected) {
+                (Some(whole), Some(expected)) => expected == encode_hex(whole.finish().as_ref()),
+                _ => true,
+            };
+            if expected != Some(actual.as_str()) || !whole_matches {
+                let _ = fs::remove_file(&sink.path);
+                *value = serde_json::json!({
+                  "event": "error",
+                  "id": id,
+                  "message": "checksum-mismatch"
+                });
+                return true;
+            }
+            if let Some(JsonValue::Object(data)) = value.get_mut("data") {
+                data.insert(
+                    "localPath".to_string(),
+                    JsonValue::String(sink.path.to_string_lossy().to_string()),
+                );
+            }
+            true
         }
-        Err(e) => {
-            send_remote_error(sender, id.as_deref(), &e.to_string());
+        "error" => {
+            requests.take_resume(&id);
+            if let Some(sink) = downloads.remove(&id) {
+                let _ = fs::remove_file(sink.path);
+            }
+            true
         }
+        _ => true,
     }
 }
 
-fn handle_remote_list(sender: &mpsc::Sender<String>, id: Option<String>, path: Option<String>) {
-    eprintln!("[remote] handle list {:?} {:?}", id, path);
-    let target = resolve_list_target(path.as_deref());
-    let (entries, list_path) = match target {
-        Ok(value) => value,
-        Err(message) => {
-            eprintln!("[remote] list error {:?}", message);
-            send_remote_event(
-                sender,
-                serde_json::json!({ "event": "list-error", "id": id, "message": message }),
-            );
-            return;
+// Creates a new file named after the download in the temp directory, with a random
+// suffix so it never replaces an existing file or follows a planted link.
+fn create_download_file(name: &str) -> std::io::Result<(PathBuf, fs::File)> {
+    let path = Path::new(name);
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "download".to_string());
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    for _ in 0..8 {
+        let suffix = random_nonce().map_err(std::io::Error::other)?;
+        let candidate = std::env::temp_dir().join(format!("{stem}-{}{extension}", &suffix[..8]));
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&candidate)
+        {
+            Ok(file) => return Ok((candidate, file)),
+            Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error),
         }
-    };
-    eprintln!("[remote] list ok {:?} entries={}", list_path, entries.len());
-    let payload = serde_json::json!({
-      "event": "list-complete",
-      "id": id,
-      "data": {
-        "path": list_path,
-        "entries": entries,
-        "os": if cfg!(target_os = "windows") { "windows" } else { "unix" }
-      }
-    });
-    send_remote_event(sender, payload);
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::AlreadyExists,
+        "no free temporary file name",
+    ))
 }
 
-fn resolve_list_target(
-    path: Option<&str>,
-) -> Result<(Vec<RemoteListEntry>, Option<String>), String> {
-    let trimmed = path.unwrap_or("").trim();
-    if trimmed.is_empty() {
-        #[cfg(target_os = "windows")]
-        {
-            return Ok((list_windows_drives(), None));
-        }
-        #[cfg(not(target_os = "windows"))]
-        {
-            let root = PathBuf::from("/");
-            let entries = list_directory_entries(&root)?;
-            return Ok((entries, Some("/".to_string())));
-        }
+// Answers the server's challenge with an HMAC of its nonce, so the token itself never
+// crosses the wire. Servers without tokens mark the challenge as not required.
+fn authenticate_remote_client<R: BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    token: Option<&str>,
+) -> Result<(), String> {
+    let deadline = Instant::now() + AUTH_HANDSHAKE_TIMEOUT;
+    let challenge = wait_for_remote_event(reader, deadline, &["auth-challenge", "error"])?;
+    if challenge.get("event").and_then(|value| value.as_str()) == Some("error") {
+        return Err(remote_auth_error(&challenge));
     }
-    #[cfg(target_os = "windows")]
-    {
-        if trimmed == "/" || trimmed == "\\" {
-            return Ok((list_windows_drives(), None));
-        }
+    let required = challenge
+        .get("required")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(true);
+    if !required {
+        return Ok(());
+    }
+    let secret = token
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| "The server requires an access token".to_string())?;
+    let nonce = challenge
+        .get("nonce")
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| "The server sent an invalid auth challenge".to_string())?;
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = ring::hmac::sign(&key, format!("{AUTH_MAC_CONTEXT}{nonce}").as_bytes());
+    let line = serde_json::json!({ "action": "auth", "mac": encode_hex(tag.as_ref()) });
+    writer
+        .write_all(format!("{line}\n").as_bytes())
+        .and_then(|_| writer.flush())
+        .map_err(|error| format!("Failed to send credentials: {error}"))?;
+    let reply = wait_for_remote_event(reader, deadline, &["auth-ok", "error"])?;
+    if reply.get("event").and_then(|value| value.as_str()) == Some("auth-ok") {
+        return Ok(());
     }
-    let target = PathBuf::from(trimmed);
-    if !target.exists() {
-        return Err("path-not-found".to_string());
+    Err(remote_auth_error(&reply))
+}
+
+// "Unauthorized token" is the message the dashboard looks for to prompt for a new token.
+fn remote_auth_error(reply: &JsonValue) -> String {
+    match reply.get("message").and_then(|value| value.as_str()) {
+        Some("unauthorized") | None => "Unauthorized token".to_string(),
+        Some(code) => format!("Authentication failed: {code}"),
     }
-    let entries = list_directory_entries(&target)?;
-    Ok((entries, Some(trimmed.to_string())))
 }
 
-fn list_directory_entries(path: &Path) -> Result<Vec<RemoteListEntry>, String> {
-    let mut entries: Vec<RemoteListEntry> = Vec::new();
-    let read_dir = fs::read_dir(path).map_err(|error| format!("list-failed: {error}"))?;
-    for entry in read_dir {
-        let entry = match entry {
-            Ok(value) => value,
-            Err(error) => {
-                eprintln!("[remote] list entry error: {error}");
-                continue;
+fn wait_for_remote_event<R: BufRead>(
+    reader: &mut R,
+    deadline: Instant,
+    events: &[&str],
+) -> Result<JsonValue, String> {
+    while Instant::now() < deadline {
+        match read_secure_line(reader, MAX_LINE_LENGTH) {
+            Ok(Some(line)) => {
+                let Ok(value) = serde_json::from_str::<JsonValue>(line.trim()) else {
+                    continue;
+                };
+                let event = value.get("event").and_then(|event| event.as_str());
+                if event.is_some_and(|event| events.contains(&event)) {
+                    return Ok(value);
+                }
             }
-        };
-        let entry_path = entry.path();
-        let is_dir = entry
-            .file_type()
-            .map(|value| value.is_dir())
-            .unwrap_or(false);
-        if !is_dir {
-            continue;
+            Ok(None) => return Err("Connection closed during authentication".to_string()),
+            Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => continue,
+            Err(error) if error.kind() == std::io::ErrorKind::TimedOut => continue,
+            Err(error) => return Err(format!("Authentication failed: {error}")),
         }
-        let name = entry.file_name().to_string_lossy().to_string();
-        let path_str = entry_path.to_string_lossy().to_string();
-        entries.push(RemoteListEntry {
-            name,
-            path: path_str,
-            is_dir,
-        });
     }
-    entries.sort_by_key(|entry| entry.name.to_lowercase());
-    Ok(entries)
+    Err("Timed out waiting for the server to authenticate".to_string())
 }
 
-#[cfg(target_os = "windows")]
-fn list_windows_drives() -> Vec<RemoteListEntry> {
-    let mut entries = Vec::new();
-    for letter in b'A'..=b'Z' {
-        let drive = format!("{}:\\", letter as char);
-        let path = Path::new(&drive);
-        if !path.exists() {
-            continue;
+#[tauri::command]
+fn remote_connect(
+    app: tauri::AppHandle,
+    state: tauri::State<RemoteClientState>,
+    payload: RemoteConnectPayload,
+) -> Result<(), String> {
+    let address = format!("{}:{}", payload.host.trim(), payload.port);
+    eprintln!("[remote] connect attempt {}", address);
+    emit_remote_status(&app, "connecting", None, Some(address.clone()));
+    let report_error = |error: &String| {
+        emit_remote_status(&app, "error", Some(error.clone()), Some(address.clone()));
+    };
+    let token = match (
+        payload.token.filter(|token| !token.is_empty()),
+        payload.token_ref,
+    ) {
+        (Some(token), _) => Some(token),
+        (None, Some(reference)) => {
+            Some(resolve_remote_token_ref(&reference).inspect_err(report_error)?)
         }
-        entries.push(RemoteListEntry {
-            name: drive.clone(),
-            path: drive,
-            is_dir: true,
-        });
+        (None, None) => None,
+    };
+    let target = RemoteClientTarget {
+        host: payload.host.trim().to_string(),
+        address: address.clone(),
+        token,
+        tls: payload.tls,
+        reconnect: payload.reconnect.unwrap_or(true),
+    };
+    let connection = open_remote_client_connection(&target).inspect_err(report_error)?;
+    eprintln!("[remote] connect success {}", address);
+    // The previous connection to the same address is replaced; others stay open.
+    let replaced = {
+        let mut state_guard = state
+            .0
+            .lock()
+            .map_err(|_| "Failed to lock remote state".to_string())?;
+        let handle = spawn_remote_client(app.clone(), target, connection);
+        state_guard.insert(address.clone(), handle)
+    };
+    if let Some(existing) = replaced {
+        stop_remote_client(existing);
     }
-    entries
+    emit_remote_status(&app, "connected", None, Some(address));
+    Ok(())
 }
 
-fn send_remote_event(sender: &mpsc::Sender<String>, value: serde_json::Value) {
-    let _ = sender.send(format!("{}\n", value));
+fn build_tls_client_connection(
+    host: &str,
+    options: &RemoteTlsOptions,
+) -> Result<rustls::Connection, String> {
+    let config = build_tls_client_config(options)?;
+    let name = options.server_name.as_deref().unwrap_or(host).to_string();
+    let server_name = rustls::pki_types::ServerName::try_from(name)
+        .map_err(|_| "Invalid TLS server name".to_string())?;
+    let session = rustls::ClientConnection::new(config, server_name)
+        .map_err(|error| format!("Failed to start TLS session: {error}"))?;
+    Ok(rustls::Connection::from(session))
 }
 
-fn send_remote_error(sender: &mpsc::Sender<String>, id: Option<&str>, message: &str) {
-    send_remote_event(
-        sender,
-        serde_json::json!({ "event": "error", "id": id, "message": message }),
-    );
+#[tauri::command]
+fn remote_disconnect(
+    app: tauri::AppHandle,
+    state: tauri::State<RemoteClientState>,
+    payload: Option<RemoteDisconnectPayload>,
+) -> Result<(), String> {
+    let handles: Vec<RemoteClientHandle> = {
+        let mut state_guard = state
+            .0
+            .lock()
+            .map_err(|_| "Failed to lock remote state".to_string())?;
+        match payload.and_then(|payload| payload.address) {
+            Some(address) => state_guard.remove(&address).into_iter().collect(),
+            None => state_guard.drain().map(|(_, handle)| handle).collect(),
+        }
+    };
+    for handle in handles {
+        let address = handle.address.clone();
+        stop_remote_client(handle);
+        emit_remote_status(&app, "disconnected", None, Some(address));
+    }
+    Ok(())
 }
 
-fn request_id(request: &RemoteRequest) -> Option<&str> {
-    match request {
-        RemoteRequest::Ping { id }
-        | RemoteRequest::List { id, .. }
-        | RemoteRequest::Disk { id, .. }
-        | RemoteRequest::Read { id, .. }
-        | RemoteRequest::Scan { id, .. }
-        | RemoteRequest::Cancel { id }
-        | RemoteRequest::Shutdown { id } => id.as_deref(),
-    }
+#[tauri::command]
+fn remote_send(
+    state: tauri::State<RemoteClientState>,
+    payload: RemoteSendPayload,
+) -> Result<(), String> {
+    eprintln!("[remote] send from ui payload={}", payload.payload);
+    let state_guard = state
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock remote state".to_string())?;
+    let handle = select_remote_client(&state_guard, payload.address.as_deref())?;
+    let mut safe_payload = match payload.payload {
+        JsonValue::Object(_) => payload.payload,
+        _ => JsonValue::Object(serde_json::Map::new()),
+    };
+    prepare_download_resume(&handle.requests, &mut safe_payload)?;
+    send_remote_line(handle, build_remote_payload(safe_payload))
 }
 
-fn resolve_settings_path(args: &[String]) -> PathBuf {
-    if let Some(path) = get_arg_value(args, "--settings") {
-        return PathBuf::from(path);
+// A download with a `resumePath` appends to that partial file instead of starting a new
+// one. The path never leaves this machine; the server only sees the offset where the
+// file ends.
+fn prepare_download_resume(
+    requests: &RemoteRequestTracker,
+    payload: &mut JsonValue,
+) -> Result<(), String> {
+    let JsonValue::Object(map) = payload else {
+        return Ok(());
+    };
+    let Some(resume) = map.remove("resumePath") else {
+        return Ok(());
+    };
+    if map.get("action").and_then(|action| action.as_str()) != Some("download") {
+        return Err("resumePath only applies to downloads".to_string());
     }
-    if let Ok(path) = std::env::var("DRAGABYTE_SETTINGS_PATH") {
-        return PathBuf::from(path);
+    let id = map
+        .get("id")
+        .and_then(|id| id.as_str())
+        .ok_or_else(|| "A resumed download needs an id".to_string())?
+        .to_string();
+    let path = resume
+        .as_str()
+        .map(PathBuf::from)
+        .ok_or_else(|| "resumePath must be a string".to_string())?;
+    let (path, size) = partial_download_file(&path)?;
+    match map.get("offset").and_then(|offset| offset.as_u64()) {
+        Some(offset) if offset != size => {
+            return Err(format!(
+                "The partial file holds {size} bytes, not the requested offset {offset}"
+            ));
+        }
+        _ => {}
     }
-    std::env::current_dir()
-        .unwrap_or_else(|_| PathBuf::from("."))
-        .join("dragabyte.settings.json")
+    map.insert("offset".to_string(), JsonValue::from(size));
+    requests.resume(&id, path)
 }
 
-fn load_settings(path: &Path) -> AppSettings {
-    let contents = fs::read_to_string(path).unwrap_or_default();
-    if contents.trim().is_empty() {
-        return AppSettings::default();
+// Only regular files in the temp directory, where downloads are saved, can be resumed, so
+// a request cannot append to an arbitrary local file.
+fn partial_download_file(path: &Path) -> Result<(PathBuf, u64), String> {
+    let metadata = fs::symlink_metadata(path)
+        .map_err(|error| format!("Failed to open partial download: {error}"))?;
+    if !metadata.file_type().is_file() {
+        return Err("The partial download is not a regular file".to_string());
     }
-    serde_json::from_str(&contents).unwrap_or_default()
+    let resolved = fs::canonicalize(path)
+        .map_err(|error| format!("Failed to open partial download: {error}"))?;
+    let temp = fs::canonicalize(std::env::temp_dir())
+        .map_err(|error| format!("Failed to resolve the temp directory: {error}"))?;
+    if resolved.parent() != Some(temp.as_path()) {
+        return Err("Only downloads saved in the temp directory can be resumed".to_string());
+    }
+    Ok((resolved, metadata.len()))
 }
 
-fn save_settings(path: &Path, settings: &AppSettings) -> Result<(), String> {
-    let payload = serde_json::to_string_pretty(settings)
-        .map_err(|error| format!("Failed to serialize settings: {error}"))?;
-
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        if let Ok(file) = fs::File::create(path) {
-            let mut perms = file.metadata().map_err(|e| e.to_string())?.permissions();
-            perms.set_mode(0o600); // Read/write for owner only
-            file.set_permissions(perms).map_err(|e| e.to_string())?;
-            // Write content after setting permissions
-            let mut writer = std::io::BufWriter::new(file);
-            writer
-                .write_all(payload.as_bytes())
-                .map_err(|e| e.to_string())?;
-            return Ok(());
+fn select_remote_client<'a>(
+    connections: &'a HashMap<String, RemoteClientHandle>,
+    address: Option<&str>,
+) -> Result<&'a RemoteClientHandle, String> {
+    let handle = match address {
+        Some(address) => connections
+            .get(address)
+            .ok_or_else(|| format!("Remote {address} is not connected"))?,
+        None if connections.len() > 1 => {
+            return Err("Several remotes are connected; choose an address".to_string());
         }
+        None => connections
+            .values()
+            .next()
+            .ok_or_else(|| "Remote is not connected".to_string())?,
+    };
+    if !handle.connected.load(Ordering::Relaxed) {
+        return Err(format!("Remote {} is reconnecting", handle.address));
     }
+    Ok(handle)
+}
 
-    fs::write(path, payload).map_err(|error| format!("Failed to save settings: {error}"))
+fn send_remote_line(handle: &RemoteClientHandle, line: String) -> Result<(), String> {
+    let sender = handle
+        .sender
+        .lock()
+        .map_err(|_| "Failed to lock remote connection".to_string())?;
+    sender
+        .send(line)
+        .map_err(|_| "Failed to send remote payload".to_string())
 }
 
-fn apply_settings_update(settings: &mut AppSettings, update: AppSettingsUpdate) {
-    if update.local_token.is_some() {
-        settings.local_token = update.local_token;
-    }
-    if update.tcp_bind.is_some() {
-        settings.tcp_bind = update.tcp_bind;
-    }
-    if update.headless.is_some() {
-        settings.headless = update.headless;
-    }
-    if update.auto_update.is_some() {
-        settings.auto_update = update.auto_update;
+// Sends a request under its own or a random id and waits, off the async runtime, for the
+// first event carrying that id. Error events reject with their code; anything streamed
+// afterwards still arrives as `remote-event` with the same id.
+#[tauri::command]
+async fn remote_request(
+    state: tauri::State<'_, RemoteClientState>,
+    payload: RemoteRequestPayload,
+) -> Result<JsonValue, String> {
+    let JsonValue::Object(mut request) = payload.payload else {
+        return Err("Remote requests must be JSON objects".to_string());
+    };
+    let action = request
+        .get("action")
+        .and_then(|action| action.as_str())
+        .unwrap_or("request")
+        .to_string();
+    let (tracker, id, receiver) = {
+        let state_guard = state
+            .0
+            .lock()
+            .map_err(|_| "Failed to lock remote state".to_string())?;
+        let handle = select_remote_client(&state_guard, payload.address.as_deref())?;
+        let tracker = Arc::clone(&handle.requests);
+        let requested_id = request
+            .get("id")
+            .and_then(|id| id.as_str())
+            .map(str::to_string);
+        let (id, receiver) = tracker.register(requested_id)?;
+        request.insert("id".to_string(), JsonValue::String(id.clone()));
+        let line = build_remote_payload(JsonValue::Object(request));
+        send_remote_line(handle, line).inspect_err(|_| tracker.forget(&id))?;
+        (tracker, id, receiver)
+    };
+    let timeout = payload
+        .timeout_ms
+        .map(Duration::from_millis)
+        .unwrap_or(REMOTE_REQUEST_TIMEOUT);
+    let reply = tauri::async_runtime::spawn_blocking(move || receiver.recv_timeout(timeout))
+        .await
+        .map_err(|error| format!("Failed to wait for the reply: {error}"))?;
+    tracker.forget(&id);
+    match reply {
+        Ok(value) if value.get("event").and_then(|event| event.as_str()) == Some("error") => {
+            Err(value
+                .get("message")
+                .and_then(|message| message.as_str())
+                .unwrap_or("Remote error")
+                .to_string())
+        }
+        Ok(value) => Ok(value),
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            Err(format!("Timed out waiting for a reply to {action}"))
+        }
+        Err(mpsc::RecvTimeoutError::Disconnected) => {
+            Err("Connection lost before the reply arrived".to_string())
+        }
     }
 }
 
-#[tauri::command]
-fn get_settings(state: tauri::State<SettingsState>) -> Result<AppSettings, String> {
-    let guard = state
-        .value
-        .lock()
-        .map_err(|_| "Failed to lock settings".to_string())?;
-    Ok(guard.clone())
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FleetHostResult {
+    name: String,
+    address: String,
+    ok: bool,
+    error: Option<String>,
+    total_bytes: Option<u64>,
+    duration_ms: u128,
 }
 
+// Runs the same scan on several saved hosts at once, each over its own connection, and
+// merges the summaries under one node per host. Hosts that fail keep an empty node with
+// an `error` field.
 #[tauri::command]
-fn update_settings(
-    state: tauri::State<SettingsState>,
-    update: AppSettingsUpdate,
-) -> Result<AppSettings, String> {
-    let mut guard = state
+async fn remote_fleet_scan(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SettingsState>,
+    payload: RemoteFleetScanPayload,
+) -> Result<JsonValue, String> {
+    let saved = settings
         .value
         .lock()
-        .map_err(|_| "Failed to lock settings".to_string())?;
-    apply_settings_update(&mut guard, update);
-    save_settings(&state.path, &guard)?;
-    Ok(guard.clone())
+        .map_err(|_| "Failed to lock settings".to_string())?
+        .remote_hosts
+        .clone()
+        .unwrap_or_default();
+    let profiles: Vec<RemoteHostProfile> = if payload.hosts.is_empty() {
+        saved
+    } else {
+        payload
+            .hosts
+            .iter()
+            .map(|name| {
+                saved
+                    .iter()
+                    .find(|profile| &profile.name == name)
+                    .cloned()
+                    .ok_or_else(|| format!("No saved host named {name}"))
+            })
+            .collect::<Result<_, _>>()?
+    };
+    if profiles.is_empty() {
+        return Err("No saved hosts to scan".to_string());
+    }
+    let started = Instant::now();
+    let id = format!(
+        "fleet-{}",
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis())
+            .unwrap_or_default()
+    );
+    let request = serde_json::json!({
+      "action": "scan",
+      "id": id,
+      "path": payload.path,
+      "paths": payload.paths,
+      "options": payload.options
+    });
+    let deadline = started
+        + payload
+            .timeout_secs
+            .map(Duration::from_secs)
+            .unwrap_or(FLEET_SCAN_TIMEOUT);
+    // Scans can run for an hour, so the waiting happens on a blocking thread rather than
+    // tying up the async runtime.
+    tauri::async_runtime::spawn_blocking(move || {
+        collect_fleet_scan(&app, &id, &profiles, &request, started, deadline)
+    })
+    .await
+    .map_err(|error| format!("Failed to wait for the fleet scan: {error}"))
 }
 
-fn emit_remote_status(
+fn collect_fleet_scan(
     app: &tauri::AppHandle,
-    status: &str,
-    message: Option<String>,
-    address: Option<String>,
-) {
-    let payload = serde_json::json!({
-      "status": status,
-      "message": message,
-      "address": address
-    });
-    let _ = app.emit("remote-status", payload);
+    id: &str,
+    profiles: &[RemoteHostProfile],
+    request: &JsonValue,
+    started: Instant,
+    deadline: Instant,
+) -> JsonValue {
+    let (result_tx, result_rx) = mpsc::channel();
+    for (index, profile) in profiles.iter().enumerate() {
+        let profile = profile.clone();
+        let request = request.clone();
+        let result_tx = result_tx.clone();
+        thread::spawn(move || {
+            let host_started = Instant::now();
+            let outcome = run_fleet_host_scan(&profile, &request, deadline);
+            let _ = result_tx.send((index, outcome, host_started.elapsed()));
+        });
+    }
+    drop(result_tx);
+    let mut outcomes: Vec<FleetHostOutcome> = profiles.iter().map(|_| None).collect();
+    for (index, outcome, elapsed) in result_rx {
+        let profile = &profiles[index];
+        let _ = app.emit(
+            "fleet-scan-host",
+            serde_json::json!({
+              "id": id,
+              "name": profile.name,
+              "address": remote_host_address(profile),
+              "ok": outcome.is_ok(),
+              "error": outcome.as_ref().err()
+            }),
+        );
+        outcomes[index] = Some((outcome, elapsed));
+    }
+    merge_fleet_results(id, profiles, outcomes, started.elapsed())
 }
 
-fn build_remote_payload(payload: JsonValue, token: Option<&str>) -> Result<String, String> {
-    eprintln!("[remote] build payload input={}", payload);
-    let mut value = payload;
-    if let Some(secret) = token {
-        match value {
-            JsonValue::Object(ref mut map) => {
-                map.entry("token".to_string())
-                    .or_insert_with(|| JsonValue::String(secret.to_string()));
+type FleetHostOutcome = Option<(Result<JsonValue, String>, Duration)>;
+
+fn merge_fleet_results(
+    id: &str,
+    profiles: &[RemoteHostProfile],
+    outcomes: Vec<FleetHostOutcome>,
+    elapsed: Duration,
+) -> JsonValue {
+    let mut hosts = Vec::new();
+    let mut children = Vec::new();
+    let mut largest_files: Vec<JsonValue> = Vec::new();
+    let mut largest_limit = 0;
+    let (mut total_bytes, mut file_count, mut dir_count) = (0u64, 0u64, 0u64);
+    for (profile, outcome) in profiles.iter().zip(outcomes) {
+        let address = remote_host_address(profile);
+        let prefix = format!("//{address}");
+        let (outcome, host_elapsed) = outcome
+            .unwrap_or_else(|| (Err("Host scan did not finish".to_string()), Duration::ZERO));
+        let mut node = serde_json::json!({
+          "path": prefix,
+          "name": profile.name,
+          "sizeBytes": 0,
+          "fileCount": 0,
+          "dirCount": 0,
+          "files": [],
+          "children": []
+        });
+        let error = match outcome {
+            Ok(mut summary) => {
+                let mut root = summary
+                    .get_mut("root")
+                    .map(JsonValue::take)
+                    .unwrap_or_default();
+                prefix_fleet_paths(&mut root, &prefix);
+                for key in ["sizeBytes", "fileCount", "dirCount"] {
+                    node[key] = root.get(key).cloned().unwrap_or(JsonValue::from(0));
+                }
+                node["children"] = JsonValue::Array(vec![root]);
+                if let Some(JsonValue::Array(files)) = summary.get_mut("largestFiles") {
+                    largest_limit = largest_limit.max(files.len());
+                    for file in files.iter_mut() {
+                        prefix_fleet_paths(file, &prefix);
+                    }
+                    largest_files.append(files);
+                }
+                None
             }
-            _ => return Err("Payload must be an object".to_string()),
-        }
+            Err(error) => {
+                node["error"] = JsonValue::String(error.clone());
+                Some(error)
+            }
+        };
+        let size = node["sizeBytes"].as_u64().unwrap_or(0);
+        total_bytes += size;
+        file_count += node["fileCount"].as_u64().unwrap_or(0);
+        dir_count += node["dirCount"].as_u64().unwrap_or(0);
+        hosts.push(FleetHostResult {
+            name: profile.name.clone(),
+            address,
+            ok: error.is_none(),
+            total_bytes: error.is_none().then_some(size),
+            error,
+            duration_ms: host_elapsed.as_millis(),
+        });
+        children.push(node);
     }
-    Ok(format!("{}\n", value))
-}
-
-fn stop_remote_server(handle: RemoteServerHandle) {
-    let _ = handle.shutdown.send(());
-    let _ = handle.join.join();
+    largest_files.sort_by_key(|file| std::cmp::Reverse(file["sizeBytes"].as_u64().unwrap_or(0)));
+    largest_files.truncate(largest_limit);
+    serde_json::json!({
+      "id": id,
+      "summary": {
+        "id": id,
+        "root": {
+          "path": "",
+          "name": format!("{} hosts", profiles.len()),
+          "sizeBytes": total_bytes,
+          "fileCount": file_count,
+          "dirCount": dir_count,
+          "files": [],
+          "children": children
+        },
+        "totalBytes": total_bytes,
+        "fileCount": file_count,
+        "dirCount": dir_count,
+        "largestFiles": largest_files,
+        "durationMs": elapsed.as_millis()
+      },
+      "hosts": hosts
+    })
 }
 
-fn stop_remote_client(handle: RemoteClientHandle) {
-    let _ = handle.shutdown.send(());
-    drop(handle.sender);
-    let _ = handle.join.join();
+fn remote_host_address(profile: &RemoteHostProfile) -> String {
+    format!("{}:{}", profile.host.trim(), profile.port)
 }
 
-fn spawn_remote_client(
-    app: tauri::AppHandle,
-    stream: TcpStream,
-    token: Option<String>,
-    address: String,
-) -> Result<RemoteClientHandle, String> {
-    stream
-        .set_read_timeout(Some(Duration::from_millis(200)))
-        .map_err(|error| format!("Failed to configure TCP stream: {error}"))?;
-    let (sender, receiver) = mpsc::channel::<String>();
-    let (shutdown_tx, shutdown_rx) = mpsc::channel::<()>();
-    let writer_stream = stream
-        .try_clone()
-        .map_err(|error| format!("Failed to clone TCP stream: {error}"))?;
-    thread::spawn(move || write_remote_lines(writer_stream, receiver));
-    let app_clone = app.clone();
-    let address_clone = address.clone();
-    let join = thread::spawn(move || {
-        let mut reader = BufReader::new(stream);
-        loop {
-            if shutdown_rx.try_recv().is_ok() {
-                break;
+// Hosts in a fleet often share a layout, so every path is qualified with its host to
+// keep them apart in one tree.
+fn prefix_fleet_paths(value: &mut JsonValue, prefix: &str) {
+    if let Some(JsonValue::String(path)) = value.get_mut("path") {
+        let separator = if path.starts_with('/') { "" } else { "/" };
+        *path = format!("{prefix}{separator}{path}");
+    }
+    for key in ["children", "files"] {
+        if let Some(JsonValue::Array(items)) = value.get_mut(key) {
+            for item in items {
+                prefix_fleet_paths(item, prefix);
             }
-            match read_secure_line(&mut reader, MAX_LINE_LENGTH) {
-                Ok(None) => break,
-                Ok(Some(line)) => {
-                    let trimmed = line.trim();
-                    if trimmed.is_empty() {
-                        continue;
+        }
+    }
+}
+
+// Returns the `scan-complete` summary, or the reason the host could not provide one.
+fn run_fleet_host_scan(
+    profile: &RemoteHostProfile,
+    request: &JsonValue,
+    deadline: Instant,
+) -> Result<JsonValue, String> {
+    let token = profile
+        .token_ref
+        .as_deref()
+        .map(resolve_remote_token_ref)
+        .transpose()?;
+    let target = RemoteClientTarget {
+        host: profile.host.trim().to_string(),
+        address: remote_host_address(profile),
+        token,
+        tls: profile.tls_fingerprint.clone().map(|pin| RemoteTlsOptions {
+            pin_sha256: Some(pin),
+            ..RemoteTlsOptions::default()
+        }),
+        reconnect: false,
+    };
+    // This opens the same kind of connection as `spawn_remote_client`, but keeps it to itself:
+    // the shared client forwards every event to the webview and redials on its own, while
+    // here one reader has to see the whole `scan-complete` summary and give up at the deadline.
+    let (mut reader, mut writer) = open_remote_client_connection(&target)?;
+    writer
+        .write_all(format!("{request}\n").as_bytes())
+        .and_then(|_| writer.flush())
+        .map_err(|error| format!("Failed to send scan request: {error}"))?;
+    let id = request.get("id").and_then(|id| id.as_str());
+    // The summary of a large tree can be far bigger than `MAX_LINE_LENGTH`, and the short
+    // read timeout can interrupt it mid-line, so bytes collect here across timeouts.
+    let mut line = Vec::new();
+    while Instant::now() < deadline {
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => return Err("Connection closed before the scan finished".to_string()),
+            Ok(_) if line.last() != Some(&b'\n') => continue,
+            Ok(_) => {
+                let parsed = serde_json::from_slice::<JsonValue>(&line);
+                line.clear();
+                let Ok(mut value) = parsed else {
+                    continue;
+                };
+                // This connection carries nothing else, so an error without an id (such
+                // as `invalid_json`) is about the scan request too.
+                let event_id = value.get("id").and_then(|value| value.as_str());
+                if event_id.is_some() && event_id != id {
+                    continue;
+                }
+                match value.get("event").and_then(|event| event.as_str()) {
+                    Some("scan-complete") => {
+                        return Ok(value
+                            .get_mut("data")
+                            .map(JsonValue::take)
+                            .unwrap_or_default());
                     }
-                    if let Ok(mut value) = serde_json::from_str::<JsonValue>(trimmed) {
-                        if let JsonValue::Object(ref mut map) = value {
-                            map.insert(
-                                "_address".to_string(),
-                                JsonValue::String(address_clone.clone()),
-                            );
-                        }
-                        let _ = app_clone.emit("remote-event", value);
+                    Some("scan-error" | "scan-cancelled" | "error") => {
+                        return Err(value
+                            .get("message")
+                            .and_then(|message| message.as_str())
+                            .unwrap_or("Remote scan failed")
+                            .to_string());
                     }
+                    _ => {}
                 }
-                Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => continue,
-                Err(error) if error.kind() == std::io::ErrorKind::TimedOut => continue,
-                Err(_) => break,
             }
+            Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => continue,
+            Err(error) if error.kind() == std::io::ErrorKind::TimedOut => continue,
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(format!("Connection failed: {error}")),
         }
-        emit_remote_status(&app_clone, "disconnected", None, Some(address_clone));
-    });
-    Ok(RemoteClientHandle {
-        sender,
-        shutdown: shutdown_tx,
-        join,
-        token,
-        address,
-    })
+    }
+    // Closing the connection doesn't stop a queued or running scan, so ask for that first.
+    let cancel = serde_json::json!({ "action": "cancel", "id": id });
+    let _ = writer
+        .write_all(format!("{cancel}\n").as_bytes())
+        .and_then(|_| writer.flush());
+    Err("Timed out waiting for the scan to finish".to_string())
 }
 
-#[tauri::command]
-fn remote_connect(
-    app: tauri::AppHandle,
-    state: tauri::State<RemoteClientState>,
-    payload: RemoteConnectPayload,
-) -> Result<(), String> {
-    let address = format!("{}:{}", payload.host.trim(), payload.port);
-    eprintln!("[remote] connect attempt {}", address);
-    emit_remote_status(&app, "connecting", None, Some(address.clone()));
-    let stream = TcpStream::connect(&address).map_err(|error| {
-        emit_remote_status(
-            &app,
-            "error",
-            Some(format!("Failed to connect: {error}")),
-            Some(address.clone()),
-        );
-        format!("Failed to connect to {address}: {error}")
-    })?;
-    eprintln!("[remote] connect success {}", address);
-    let mut state_guard = state
-        .0
-        .lock()
-        .map_err(|_| "Failed to lock remote state".to_string())?;
-    if let Some(existing) = state_guard.take() {
-        stop_remote_client(existing);
-    }
-    let handle = spawn_remote_client(app.clone(), stream, payload.token, address.clone())?;
-    *state_guard = Some(handle);
-    emit_remote_status(&app, "connected", None, Some(address));
-    Ok(())
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiscoveredHost {
+    name: String,
+    hostname: String,
+    // IPv4 first; mDNS hostnames often don't resolve outside the responder itself.
+    addresses: Vec<String>,
+    port: u16,
+    version: Option<String>,
+    protocol_version: Option<u32>,
+    tls: bool,
+    token_required: bool,
 }
 
-#[tauri::command]
-fn remote_disconnect(
-    app: tauri::AppHandle,
-    state: tauri::State<RemoteClientState>,
-) -> Result<(), String> {
-    let mut state_guard = state
-        .0
-        .lock()
-        .map_err(|_| "Failed to lock remote state".to_string())?;
-    if let Some(handle) = state_guard.take() {
-        let address = handle.address.clone();
-        stop_remote_client(handle);
-        emit_remote_status(&app, "disconnected", None, Some(address));
+// Browses the local network for servers started with `--mdns` and returns every instance
+// resolved before the timeout.
+#[tauri::command(async)]
+fn remote_discover(payload: Option<RemoteDiscoverPayload>) -> Result<Vec<DiscoveredHost>, String> {
+    let timeout = payload
+        .and_then(|payload| payload.timeout_ms)
+        .map(Duration::from_millis)
+        .unwrap_or(MDNS_BROWSE_TIMEOUT)
+        .min(MDNS_MAX_BROWSE_TIMEOUT);
+    let daemon =
+        ServiceDaemon::new().map_err(|error| format!("Failed to start mDNS browser: {error}"))?;
+    let result = browse_remote_hosts(&daemon, timeout);
+    let _ = daemon.shutdown();
+    result
+}
+
+fn browse_remote_hosts(
+    daemon: &ServiceDaemon,
+    timeout: Duration,
+) -> Result<Vec<DiscoveredHost>, String> {
+    let events = daemon
+        .browse(MDNS_SERVICE_TYPE)
+        .map_err(|error| format!("Failed to browse mDNS: {error}"))?;
+    let deadline = Instant::now() + timeout;
+    let mut found: BTreeMap<String, DiscoveredHost> = BTreeMap::new();
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        match events.recv_timeout(remaining) {
+            Ok(ServiceEvent::ServiceResolved(info)) => {
+                found.insert(info.get_fullname().to_string(), discovered_host(&info));
+            }
+            Ok(ServiceEvent::ServiceRemoved(_, fullname)) => {
+                found.remove(&fullname);
+            }
+            Ok(_) => {}
+            Err(_) => break,
+        }
     }
-    Ok(())
+    Ok(found.into_values().collect())
 }
 
-#[tauri::command]
-fn remote_send(
-    state: tauri::State<RemoteClientState>,
-    payload: RemoteSendPayload,
-) -> Result<(), String> {
-    eprintln!("[remote] send from ui payload={}", payload.payload);
-    let state_guard = state
-        .0
-        .lock()
-        .map_err(|_| "Failed to lock remote state".to_string())?;
-    let handle = state_guard
-        .as_ref()
-        .ok_or_else(|| "Remote is not connected".to_string())?;
-    let safe_payload = match payload.payload {
-        JsonValue::Object(_) => payload.payload,
-        _ => JsonValue::Object(serde_json::Map::new()),
-    };
-    let line = build_remote_payload(safe_payload, handle.token.as_deref())?;
-    handle
-        .sender
-        .send(line)
-        .map_err(|_| "Failed to send remote payload".to_string())
+fn discovered_host(info: &ServiceInfo) -> DiscoveredHost {
+    let fullname = info.get_fullname();
+    let name = fullname
+        .strip_suffix(MDNS_SERVICE_TYPE)
+        .map(|value| value.trim_end_matches('.'))
+        .unwrap_or(fullname)
+        .to_string();
+    let mut addresses: Vec<IpAddr> = info.get_addresses().iter().copied().collect();
+    addresses.sort_by_key(|address| (address.is_ipv6(), *address));
+    let flag = |key: &str| info.get_property_val_str(key) == Some("true");
+    DiscoveredHost {
+        name,
+        hostname: info.get_hostname().trim_end_matches('.').to_string(),
+        addresses: addresses.iter().map(IpAddr::to_string).collect(),
+        port: info.get_port(),
+        version: info.get_property_val_str("version").map(str::to_string),
+        protocol_version: info
+            .get_property_val_str("protocol")
+            .and_then(|value| value.parse().ok()),
+        tls: flag("tls"),
+        token_required: flag("token"),
+    }
 }
 
 #[derive(Serialize)]
@@ -1796,6 +8146,14 @@ fn remote_send(
 struct RemoteStatusSnapshot {
     connected: bool,
     address: Option<String>,
+    connections: Vec<RemoteConnectionSnapshot>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteConnectionSnapshot {
+    address: String,
+    status: &'static str,
 }
 
 #[derive(Serialize)]
@@ -1811,10 +8169,26 @@ fn remote_status(state: tauri::State<RemoteClientState>) -> Result<RemoteStatusS
         .0
         .lock()
         .map_err(|_| "Failed to lock remote state".to_string())?;
-    let address = guard.as_ref().map(|handle| handle.address.clone());
+    let mut connections: Vec<RemoteConnectionSnapshot> = guard
+        .values()
+        .map(|handle| RemoteConnectionSnapshot {
+            address: handle.address.clone(),
+            status: if handle.connected.load(Ordering::Relaxed) {
+                "connected"
+            } else {
+                "reconnecting"
+            },
+        })
+        .collect();
+    connections.sort_by(|left, right| left.address.cmp(&right.address));
+    let address = connections
+        .iter()
+        .find(|connection| connection.status == "connected")
+        .map(|connection| connection.address.clone());
     Ok(RemoteStatusSnapshot {
         connected: address.is_some(),
         address,
+        connections,
     })
 }
 
@@ -1860,6 +8234,51 @@ fn resolve_launch_context(args: &[String]) -> LaunchContext {
     LaunchContext { path, paths, mode }
 }
 
+// Runs in the first instance for each later launch. Explorer starts one process per
+// selected item, so launches arriving within LAUNCH_MERGE_WINDOW of each other are
+// combined into one context.
+fn forward_launch(app: &tauri::AppHandle, args: &[String]) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    let incoming = resolve_launch_context(args);
+    if incoming.paths.is_empty() {
+        return;
+    }
+    let state = app.state::<LaunchContextState>();
+    let Ok(mut guard) = state.0.lock() else {
+        return;
+    };
+    let recent = guard.1.elapsed() < LAUNCH_MERGE_WINDOW;
+    let merged = merge_launch_context(&guard.0, incoming, recent);
+    *guard = (merged.clone(), Instant::now());
+    drop(guard);
+    let _ = app.emit_to("main", "launch-context", merged);
+}
+
+fn merge_launch_context(
+    current: &LaunchContext,
+    incoming: LaunchContext,
+    recent: bool,
+) -> LaunchContext {
+    if !recent || current.mode != incoming.mode || current.paths.is_empty() {
+        return incoming;
+    }
+    let mut paths = current.paths.clone();
+    for path in incoming.paths {
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    LaunchContext {
+        path: current.path.clone(),
+        paths,
+        mode: current.mode.clone(),
+    }
+}
+
 #[cfg(target_os = "windows")]
 fn hide_console_window() {
     use windows_sys::Win32::System::Console::GetConsoleWindow;
@@ -1961,14 +8380,19 @@ fn toggle_context_menu(_enable: bool) -> Result<(), String> {
         if _enable {
             let exe_path = std::env::current_exe().map_err(|e| e.to_string())?;
             let exe_str = exe_path.to_str().ok_or("Invalid path")?;
+            // Explorer starts one process per selected item; each hands its path to the
+            // first instance, which merges them.
             let scan_cmd = format!("\"{}\" \"%1\"", exe_str);
-            let rename_cmd = format!("\"{}\" --rename %*", exe_str);
+            let rename_cmd = format!("\"{}\" --rename \"%1\"", exe_str);
 
             for key_path in scan_keys {
                 let (key, _) = hkcu.create_subkey(key_path).map_err(|e| e.to_string())?;
                 key.set_value("", &"Scan with Dragabyte")
                     .map_err(|e| e.to_string())?;
                 key.set_value("Icon", &exe_str).map_err(|e| e.to_string())?;
+                if !key_path.contains("Background") {
+                    let _ = key.set_value("MultiSelectModel", &"Player");
+                }
 
                 let (cmd_key, _) = key.create_subkey("command").map_err(|e| e.to_string())?;
 
@@ -2031,7 +8455,7 @@ fn toggle_context_menu(_enable: bool) -> Result<(), String> {
 Type=Application
 Name=Dragabyte
 Comment=Disk Space Analyzer and Bulk Rename Utility
-Exec="{}" %f
+Exec="{}" %F
 Icon=utilities-terminal
 Terminal=false
 Categories=Utility;FileTools;
@@ -2039,7 +8463,7 @@ Actions=Scan;Rename;
 
 [Desktop Action Scan]
 Name=Scan with Dragabyte
-Exec="{}" %f
+Exec="{}" %F
 
 [Desktop Action Rename]
 Name=Rename with Dragabyte
@@ -2178,7 +8602,7 @@ fn show_in_explorer(path: String) -> Result<(), String> {
 }
 
 fn build_summary(
-    root: &Path,
+    roots: &[PathBuf],
     children: &HashMap<PathBuf, Vec<PathBuf>>,
     files_by_parent: &HashMap<PathBuf, Vec<ScanFile>>,
     stats: &HashMap<PathBuf, NodeStats>,
@@ -2189,13 +8613,20 @@ fn build_summary(
     sort_by_size: bool,
     max_children: Option<usize>,
 ) -> ScanSummary {
+    // Several roots are grouped under an unnamed node with an empty path.
+    let combined = roots.len() > 1;
+    let root = if combined {
+        PathBuf::new()
+    } else {
+        roots.first().cloned().unwrap_or_default()
+    };
     let (max_depth, max_files) = if compact {
-        (Some(1), Some(0))
+        (Some(if combined { 2 } else { 1 }), Some(0))
     } else {
         (None, None)
     };
-    let root_node = build_node(
-        root,
+    let mut root_node = build_node(
+        &root,
         children,
         files_by_parent,
         stats,
@@ -2205,6 +8636,9 @@ fn build_summary(
         sort_by_size,
         max_children,
     );
+    if combined {
+        root_node.name = format!("{} locations", roots.len());
+    }
     ScanSummary {
         id: scan_id,
         total_bytes: root_node.size_bytes,
@@ -2216,24 +8650,12 @@ fn build_summary(
     }
 }
 
-fn update_largest_files(
-    largest_files: &mut Vec<ScanFile>,
-    path: &Path,
-    size_bytes: u64,
-    modified: Option<u64>,
-    limit: usize,
-) {
-    if size_bytes == 0 {
+fn update_largest_files(largest_files: &mut Vec<ScanFile>, file: &ScanFile, limit: usize) {
+    if file.size_bytes == 0 {
         return;
     }
-    let name = get_entry_name_string(path);
     if largest_files.len() < limit {
-        largest_files.push(ScanFile {
-            path: get_path_string(path),
-            name,
-            size_bytes,
-            modified,
-        });
+        largest_files.push(file.clone());
         largest_files.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
         return;
     }
@@ -2241,15 +8663,10 @@ fn update_largest_files(
         .last()
         .map(|file| file.size_bytes)
         .unwrap_or(0);
-    if size_bytes <= smallest {
+    if file.size_bytes <= smallest {
         return;
     }
-    largest_files.push(ScanFile {
-        path: get_path_string(path),
-        name,
-        size_bytes,
-        modified,
-    });
+    largest_files.push(file.clone());
     largest_files.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
     largest_files.truncate(limit);
 }
@@ -2273,6 +8690,7 @@ fn build_node(
     if let Some(stats) = stats.get(path) {
         size_bytes += stats.direct_bytes;
         file_count += stats.direct_files;
+        dir_count += stats.collapsed_dirs;
     }
 
     if let Some(children_paths) = children.get(path) {
@@ -2314,6 +8732,10 @@ fn build_node(
     }
 
     let mut files = files_by_parent.get(path).cloned().unwrap_or_default();
+    let archive = stats
+        .get(path)
+        .and_then(|value| value.archive)
+        .map(|kind| kind.as_str());
     if let Some(limit) = max_files {
         if files.len() > limit {
             files.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
@@ -2329,6 +8751,7 @@ fn build_node(
         dir_count,
         files,
         children: nodes,
+        archive,
     }
 }
 
@@ -2495,7 +8918,7 @@ fn main() {
         }
     };
     if runtime_options.headless && runtime_options.tcp.is_none() {
-        eprintln!("Headless mode requires --tcp");
+        eprintln!("Headless mode requires --tcp or --socket");
         return;
     }
     let tcp_server = match runtime_options.tcp.clone() {
@@ -2513,13 +8936,20 @@ fn main() {
         runtime_options
             .tcp
             .as_ref()
-            .map(|value| value.bind_addr.to_string())
+            .and_then(|value| value.bind_addr)
+            .map(|address| address.to_string())
     } else {
         None
     };
     let headless_mode = runtime_options.headless;
     let updater_enabled = runtime_options.updater_enabled;
-    let mut builder = tauri::Builder::default()
+    let mut builder = tauri::Builder::default();
+    if !headless_mode {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            forward_launch(app, &argv);
+        }));
+    }
+    builder = builder
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_process::init())
@@ -2546,7 +8976,10 @@ fn main() {
                 hide_console_window();
             }
             app.manage(StartupPath(Mutex::new(startup_path_state.clone())));
-            app.manage(LaunchContextState(Mutex::new(launch_context_state.clone())));
+            app.manage(LaunchContextState(Mutex::new((
+                launch_context_state.clone(),
+                Instant::now(),
+            ))));
             app.manage(ScanCancellation(Mutex::new(HashMap::new())));
             app.manage(SettingsState {
                 path: settings_path.clone(),
@@ -2556,7 +8989,7 @@ fn main() {
                 tcp_enabled: tcp_running,
                 tcp_bind: tcp_bind.clone(),
             });
-            app.manage(RemoteClientState(Mutex::new(None)));
+            app.manage(RemoteClientState(Mutex::new(HashMap::new())));
             if !headless_mode {
                 if let Some(window) = app.get_webview_window("main") {
                     let _ = window.restore_state(StateFlags::POSITION | StateFlags::SIZE);
@@ -2570,6 +9003,8 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             scan_path,
             cancel_scan,
+            pause_scan,
+            resume_scan,
             get_disk_usage,
             delete_item,
             rename_item,
@@ -2588,6 +9023,9 @@ fn main() {
             remote_connect,
             remote_disconnect,
             remote_send,
+            remote_request,
+            remote_fleet_scan,
+            remote_discover,
             remote_status,
             get_tcp_status,
             batch_rename
@@ -2598,3 +9036,700 @@ fn main() {
         stop_remote_server(handle);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const MASK: [u8; 4] = [0x37, 0xfa, 0x21, 0x3d];
+
+    // Collects what the server writes, shared with the test after the writer is boxed.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn contents(&self) -> Vec<u8> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    // Hands out one byte per read, so frames arrive split at every position.
+    struct Trickle(Cursor<Vec<u8>>);
+
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let limit = buf.len().min(1);
+            self.0.read(&mut buf[..limit])
+        }
+    }
+
+    fn masked_frame(fin: bool, opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![if fin { 0x80 | opcode } else { opcode }];
+        match payload.len() {
+            length if length < 126 => frame.push(0x80 | length as u8),
+            length if length <= u16::MAX as usize => {
+                frame.push(0x80 | 126);
+                frame.extend_from_slice(&(length as u16).to_be_bytes());
+            }
+            length => {
+                frame.push(0x80 | 127);
+                frame.extend_from_slice(&(length as u64).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(&MASK);
+        frame.extend(
+            payload
+                .iter()
+                .enumerate()
+                .map(|(index, byte)| byte ^ MASK[index % 4]),
+        );
+        frame
+    }
+
+    fn reader_over(stream: impl Read + Send + 'static) -> (WebSocketReader, SharedBuffer) {
+        let output = SharedBuffer::default();
+        let writer: RemoteWriteHalf = Box::new(output.clone());
+        let reader = WebSocketReader {
+            stream: Box::new(stream),
+            writer: Arc::new(Mutex::new(writer)),
+            buffer: Vec::new(),
+            message: Vec::new(),
+        };
+        (reader, output)
+    }
+
+    // A fresh directory under the system temp dir, canonical so prefixes compare cleanly.
+    fn scratch_dir(label: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("dragabyte-test-{label}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::canonicalize(dir).unwrap()
+    }
+
+    #[test]
+    fn remote_actions_table_matches_requests() {
+        for (action, scope) in REMOTE_ACTIONS {
+            // Fields are left out, so most actions fail on a missing field instead.
+            match serde_json::from_value::<RemoteRequest>(serde_json::json!({ "action": action })) {
+                Ok(request) => {
+                    assert_eq!(request.action(), action);
+                    assert_eq!(required_scope(&request), Ok(scope));
+                }
+                Err(error) => assert!(
+                    !error.to_string().contains("unknown variant"),
+                    "{action}: {error}"
+                ),
+            }
+        }
+    }
+
+    fn upgrade_request(extra: &str) -> String {
+        format!(
+            "GET /chat HTTP/1.1\r\nHost: server.example.com\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n{extra}\r\n"
+        )
+    }
+
+    #[test]
+    fn websocket_accept_key_matches_rfc_example() {
+        let accept = websocket_accept_key(&upgrade_request(""), &[]).unwrap();
+        assert_eq!(accept, "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[test]
+    fn websocket_handshake_rejects_bad_requests() {
+        let post = upgrade_request("").replacen("GET", "POST", 1);
+        assert_eq!(
+            websocket_accept_key(&post, &[]),
+            Err("405 Method Not Allowed")
+        );
+        let plain = "GET / HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        assert_eq!(
+            websocket_accept_key(plain, &[]),
+            Err("426 Upgrade Required")
+        );
+        let old = upgrade_request("").replace("Version: 13", "Version: 8");
+        assert_eq!(websocket_accept_key(&old, &[]), Err("400 Bad Request"));
+        let browser = upgrade_request("Origin: https://evil.example\r\n");
+        let allowed = vec!["https://console.example".to_string()];
+        assert_eq!(
+            websocket_accept_key(&browser, &allowed),
+            Err("403 Forbidden")
+        );
+        let trusted = upgrade_request("Origin: https://console.example\r\n");
+        assert!(websocket_accept_key(&trusted, &allowed).is_ok());
+    }
+
+    #[test]
+    fn accept_websocket_upgrades_and_reads_pipelined_frame() {
+        let mut input = upgrade_request("").into_bytes();
+        input.extend_from_slice(&masked_frame(true, 0x1, br#"{"action":"ping"}"#));
+        let output = SharedBuffer::default();
+        let (mut reader, _writer) =
+            accept_websocket(Box::new(Cursor::new(input)), Box::new(output.clone()), &[]).unwrap();
+        let response = String::from_utf8(output.contents()).unwrap();
+        assert!(response.starts_with("HTTP/1.1 101 Switching Protocols\r\n"));
+        assert!(response.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+        assert_eq!(
+            reader.next_line().unwrap().as_deref(),
+            Some(r#"{"action":"ping"}"#)
+        );
+        assert_eq!(reader.next_line().unwrap(), None);
+    }
+
+    #[test]
+    fn accept_websocket_refuses_disallowed_origin() {
+        let input = upgrade_request("Origin: https://evil.example\r\n").into_bytes();
+        let output = SharedBuffer::default();
+        let result = accept_websocket(Box::new(Cursor::new(input)), Box::new(output.clone()), &[]);
+        assert!(result.is_err());
+        let response = String::from_utf8(output.contents()).unwrap();
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden\r\n"));
+    }
+
+    // RFC 6455 section 5.7: a single-frame masked text message.
+    #[test]
+    fn websocket_reads_rfc_masked_hello() {
+        let frame = vec![
+            0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58,
+        ];
+        let (mut reader, _) = reader_over(Cursor::new(frame));
+        assert_eq!(reader.next_message().unwrap().as_deref(), Some("Hello"));
+    }
+
+    // RFC 6455 section 5.7: the unmasked example is only valid from a server.
+    #[test]
+    fn websocket_rejects_unmasked_client_frame() {
+        let frame = vec![0x81, 0x05, 0x48, 0x65, 0x6c, 0x6c, 0x6f];
+        let (mut reader, _) = reader_over(Cursor::new(frame));
+        let error = reader.next_message().unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    // RFC 6455 section 5.7: "Hel" and "lo" as two fragments, with a ping between them.
+    #[test]
+    fn websocket_joins_fragments_and_answers_interleaved_ping() {
+        let mut input = masked_frame(false, 0x1, b"Hel");
+        input.extend(masked_frame(true, 0x9, b"Hello"));
+        input.extend(masked_frame(true, 0x0, b"lo"));
+        let (mut reader, output) = reader_over(Trickle(Cursor::new(input)));
+        assert_eq!(reader.next_message().unwrap().as_deref(), Some("Hello"));
+        // The pong echoes the ping payload, unmasked as the RFC example shows.
+        assert_eq!(
+            output.contents(),
+            vec![0x8a, 0x05, 0x48, 0x65, 0x6c, 0x6c, 0x6f]
+        );
+    }
+
+    #[test]
+    fn websocket_reads_extended_lengths() {
+        let medium = vec![b'a'; 256];
+        let large = vec![b'b'; 65536];
+        let mut input = masked_frame(true, 0x2, &medium);
+        input.extend(masked_frame(true, 0x2, &large));
+        let (mut reader, _) = reader_over(Cursor::new(input));
+        assert_eq!(reader.next_message().unwrap().unwrap().len(), 256);
+        assert_eq!(reader.next_message().unwrap().unwrap().len(), 65536);
+    }
+
+    #[test]
+    fn websocket_close_is_echoed_and_ends_stream() {
+        let input = masked_frame(true, 0x8, &[0x03, 0xe8]);
+        let (mut reader, output) = reader_over(Cursor::new(input));
+        assert_eq!(reader.next_message().unwrap(), None);
+        assert_eq!(output.contents(), vec![0x88, 0x02, 0x03, 0xe8]);
+    }
+
+    #[test]
+    fn websocket_rejects_long_or_fragmented_control_frames() {
+        let (mut reader, output) = reader_over(Cursor::new(masked_frame(true, 0x9, &[0; 126])));
+        assert!(reader.next_message().is_err());
+        assert!(output.contents().is_empty());
+        let (mut reader, _) = reader_over(Cursor::new(masked_frame(false, 0x9, b"ping")));
+        assert!(reader.next_message().is_err());
+    }
+
+    #[test]
+    fn websocket_rejects_oversized_lengths() {
+        // Refused from the header alone, before any payload arrives.
+        let mut frame = vec![0x82, 0x80 | 127];
+        frame.extend_from_slice(&(MAX_LINE_LENGTH + 1).to_be_bytes());
+        frame.extend_from_slice(&MASK);
+        let (mut reader, _) = reader_over(Cursor::new(frame));
+        assert!(reader.next_message().is_err());
+        let mut frame = vec![0x82, 0x80 | 127];
+        frame.extend_from_slice(&u64::MAX.to_be_bytes());
+        let (mut reader, _) = reader_over(Cursor::new(frame));
+        assert!(reader.next_message().is_err());
+    }
+
+    #[test]
+    fn websocket_rejects_messages_over_line_limit_across_fragments() {
+        let half = vec![b'x'; MAX_LINE_LENGTH as usize / 2 + 1];
+        let mut input = masked_frame(false, 0x1, &half);
+        input.extend(masked_frame(true, 0x0, &half));
+        let (mut reader, _) = reader_over(Cursor::new(input));
+        assert!(reader.next_message().is_err());
+    }
+
+    #[test]
+    fn websocket_waits_for_incomplete_frames() {
+        let frame = masked_frame(true, 0x1, b"Hello");
+        let (mut reader, _) = reader_over(Cursor::new(frame[..frame.len() - 1].to_vec()));
+        // The stream ends mid-frame, which reads as a closed connection.
+        assert_eq!(reader.next_message().unwrap(), None);
+        assert_eq!(reader.buffer.len(), frame.len() - 1);
+    }
+
+    #[test]
+    fn websocket_frames_use_rfc_length_encodings() {
+        let mut small = Vec::new();
+        write_websocket_frame(&mut small, 0x1, b"Hello").unwrap();
+        assert_eq!(small, vec![0x81, 0x05, 0x48, 0x65, 0x6c, 0x6c, 0x6f]);
+        let mut medium = Vec::new();
+        write_websocket_frame(&mut medium, 0x2, &[0; 256]).unwrap();
+        assert_eq!(medium[..4], [0x82, 0x7e, 0x01, 0x00]);
+        assert_eq!(medium.len(), 4 + 256);
+        let mut large = Vec::new();
+        write_websocket_frame(&mut large, 0x2, &[0; 65536]).unwrap();
+        assert_eq!(
+            large[..10],
+            [0x82, 0x7f, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00]
+        );
+        assert_eq!(large.len(), 10 + 65536);
+    }
+
+    #[test]
+    fn discovered_hosts_read_the_txt_record_and_addresses() {
+        let properties = [
+            ("version", "1.2.3"),
+            ("protocol", "4"),
+            ("tls", "true"),
+            ("token", "true"),
+        ];
+        let info = ServiceInfo::new(
+            MDNS_SERVICE_TYPE,
+            "office-nas",
+            "nas.local.",
+            "fe80::1,192.168.1.20,10.0.0.5",
+            4799,
+            &properties[..],
+        )
+        .unwrap();
+        let host = discovered_host(&info);
+        assert_eq!(host.name, "office-nas");
+        assert_eq!(host.hostname, "nas.local");
+        assert_eq!(host.addresses, ["10.0.0.5", "192.168.1.20", "fe80::1"]);
+        assert_eq!(host.port, 4799);
+        assert_eq!(host.version.as_deref(), Some("1.2.3"));
+        assert_eq!(host.protocol_version, Some(4));
+        assert!(host.tls);
+        assert!(host.token_required);
+    }
+
+    #[test]
+    fn discovered_hosts_tolerate_missing_or_odd_txt_values() {
+        let properties = [("protocol", "next"), ("tls", "yes")];
+        let info = ServiceInfo::new(
+            MDNS_SERVICE_TYPE,
+            "lab",
+            "lab.local.",
+            "10.0.0.7",
+            4800,
+            &properties[..],
+        )
+        .unwrap();
+        let host = discovered_host(&info);
+        assert_eq!(host.name, "lab");
+        assert_eq!(host.version, None);
+        assert_eq!(host.protocol_version, None);
+        // Only the literal `true` turns a flag on.
+        assert!(!host.tls);
+        assert!(!host.token_required);
+    }
+
+    // Needs multicast on a real network interface, which sandboxed CI usually lacks.
+    // Run with `cargo test -- --ignored`.
+    #[test]
+    #[ignore]
+    fn browse_resolves_advertised_hosts() {
+        let instance = format!("dragabyte-test-{}", std::process::id());
+        let properties = [
+            ("version", "1.2.3"),
+            ("protocol", "4"),
+            ("tls", "true"),
+            ("token", "true"),
+        ];
+        let info = ServiceInfo::new(
+            MDNS_SERVICE_TYPE,
+            &instance,
+            "dragabyte-test.local.",
+            (),
+            4799,
+            &properties[..],
+        )
+        .map(ServiceInfo::enable_addr_auto)
+        .unwrap();
+        let responder = ServiceDaemon::new().unwrap();
+        responder.register(info).unwrap();
+        let browser = ServiceDaemon::new().unwrap();
+        let hosts = browse_remote_hosts(&browser, Duration::from_secs(3)).unwrap();
+        let _ = browser.shutdown();
+        let _ = responder.shutdown();
+        let host = hosts
+            .iter()
+            .find(|host| host.name == instance)
+            .expect("the advertised host was not resolved");
+        assert_eq!(host.hostname, "dragabyte-test.local");
+        assert_eq!(host.port, 4799);
+        assert!(!host.addresses.is_empty());
+        assert_eq!(host.version.as_deref(), Some("1.2.3"));
+        assert_eq!(host.protocol_version, Some(4));
+        assert!(host.tls);
+        assert!(host.token_required);
+    }
+
+    #[test]
+    fn canonicalize_for_check_resolves_missing_tails_and_dot_dot() {
+        let dir = scratch_dir("canonicalize");
+        fs::create_dir(dir.join("a")).unwrap();
+        assert_eq!(
+            canonicalize_for_check(&dir.join("a/../b/new.txt")),
+            Some(dir.join("b/new.txt"))
+        );
+        assert_eq!(
+            canonicalize_for_check(&dir.join("a/missing/new.txt")),
+            Some(dir.join("a/missing/new.txt"))
+        );
+        // A `..` that cannot be resolved on disk is refused rather than taken lexically.
+        assert_eq!(canonicalize_for_check(&dir.join("missing/..")), None);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn allows_path_refuses_dot_dot_escapes() {
+        let dir = scratch_dir("allows-path");
+        fs::create_dir_all(dir.join("allowed/sub")).unwrap();
+        fs::create_dir(dir.join("outside")).unwrap();
+        let grant = RemoteGrant {
+            roots: Some(vec![dir.join("allowed")]),
+            ..RemoteGrant::full("restricted")
+        };
+        assert!(grant.allows_path(&dir.join("allowed")));
+        assert!(grant.allows_path(&dir.join("allowed/sub")));
+        assert!(grant.allows_path(&dir.join("allowed/sub/new.txt")));
+        assert!(grant.allows_path(&dir.join("outside/../allowed/sub")));
+        assert!(!grant.allows_path(&dir.join("allowed/../outside")));
+        assert!(!grant.allows_path(&dir.join("allowed/sub/../../outside/new.txt")));
+        assert!(!grant.allows_path(&dir));
+        // A sibling that shares the root's name as a prefix is still outside it.
+        fs::create_dir(dir.join("allowed-not")).unwrap();
+        assert!(!grant.allows_path(&dir.join("allowed-not")));
+        assert!(RemoteGrant::full("shared").allows_path(&dir.join("outside")));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn allows_path_refuses_symlink_escapes() {
+        let dir = scratch_dir("allows-symlink");
+        fs::create_dir(dir.join("allowed")).unwrap();
+        fs::create_dir(dir.join("outside")).unwrap();
+        fs::write(dir.join("outside/secret.txt"), b"secret").unwrap();
+        std::os::unix::fs::symlink(dir.join("outside"), dir.join("allowed/link")).unwrap();
+        let grant = RemoteGrant {
+            roots: Some(vec![dir.join("allowed")]),
+            ..RemoteGrant::full("restricted")
+        };
+        assert!(!grant.allows_path(&dir.join("allowed/link")));
+        assert!(!grant.allows_path(&dir.join("allowed/link/secret.txt")));
+        assert!(!grant.allows_path(&dir.join("allowed/link/new.txt")));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn intersect_roots_keeps_the_overlap() {
+        let paths = |raw: &[&str]| raw.iter().map(PathBuf::from).collect::<Vec<_>>();
+        assert_eq!(
+            intersect_roots(&paths(&["/srv"]), &paths(&["/srv/share", "/home"])),
+            paths(&["/srv/share"])
+        );
+        assert_eq!(
+            intersect_roots(&paths(&["/srv/share", "/home"]), &paths(&["/srv"])),
+            paths(&["/srv/share"])
+        );
+        assert_eq!(
+            intersect_roots(&paths(&["/srv", "/srv/share"]), &paths(&["/srv/share"])),
+            paths(&["/srv/share"])
+        );
+        assert!(intersect_roots(&paths(&["/srv"]), &paths(&["/backups"])).is_empty());
+        assert!(intersect_roots(&paths(&["/srv"]), &[]).is_empty());
+        let grant = RemoteGrant {
+            roots: Some(paths(&["/srv/share", "/home"])),
+            ..RemoteGrant::full("restricted")
+        };
+        let restricted = grant.restrict_to(Some(&paths(&["/srv"])));
+        assert_eq!(restricted.roots, Some(paths(&["/srv/share"])));
+    }
+
+    fn sign_nonce(secret: &str, nonce: &str) -> String {
+        let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes());
+        encode_hex(ring::hmac::sign(&key, format!("{AUTH_MAC_CONTEXT}{nonce}").as_bytes()).as_ref())
+    }
+
+    fn token_hub() -> RemoteHub {
+        let helpdesk = ApiTokenSettings {
+            name: "helpdesk".to_string(),
+            token: "h3lp".to_string(),
+            scopes: vec![RemoteScope::List],
+            roots: Vec::new(),
+        };
+        RemoteHub::new(
+            build_remote_tokens(Some("s3cret"), &[helpdesk]),
+            None,
+            None,
+            1,
+            None,
+            false,
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn auth_mac_identifies_the_signing_token() {
+        let hub = token_hub();
+        let nonce = random_nonce().unwrap();
+        let shared = hub.verify_auth_mac(&nonce, &sign_nonce("s3cret", &nonce));
+        assert_eq!(shared.map(|grant| grant.name).as_deref(), Some("default"));
+        let helpdesk = hub
+            .verify_auth_mac(&nonce, &sign_nonce("h3lp", &nonce))
+            .unwrap();
+        assert_eq!(helpdesk.name, "helpdesk");
+        assert!(helpdesk.allows(RemoteScope::List));
+        assert!(!helpdesk.allows(RemoteScope::Delete));
+    }
+
+    #[test]
+    fn auth_mac_rejects_wrong_keys_and_replayed_nonces() {
+        let hub = token_hub();
+        let nonce = random_nonce().unwrap();
+        assert!(hub
+            .verify_auth_mac(&nonce, &sign_nonce("wrong", &nonce))
+            .is_none());
+        assert!(hub.verify_auth_mac(&nonce, "not-hex").is_none());
+        assert!(hub.verify_auth_mac(&nonce, "").is_none());
+        // A MAC captured from one connection is useless on the next, which gets a new nonce.
+        let replayed = sign_nonce("s3cret", &nonce);
+        let next = random_nonce().unwrap();
+        assert_ne!(next, nonce);
+        assert!(hub.verify_auth_mac(&next, &replayed).is_none());
+        // Signing without the context prefix doesn't verify either.
+        let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, b"s3cret");
+        let bare = encode_hex(ring::hmac::sign(&key, nonce.as_bytes()).as_ref());
+        assert!(hub.verify_auth_mac(&nonce, &bare).is_none());
+    }
+
+    #[test]
+    fn auth_rate_limiter_locks_out_and_resets() {
+        let limiter = AuthRateLimiter::default();
+        let peer: Option<SocketAddr> = Some("10.0.0.5:51234".parse().unwrap());
+        let other: Option<SocketAddr> = Some("10.0.0.6:51234".parse().unwrap());
+        for _ in 1..AUTH_MAX_FAILURES {
+            limiter.record_failure(peer);
+        }
+        assert!(!limiter.is_blocked(peer));
+        // A success clears the count, so the next failures start over.
+        limiter.record_success(peer);
+        for _ in 1..AUTH_MAX_FAILURES {
+            limiter.record_failure(peer);
+        }
+        assert!(!limiter.is_blocked(peer));
+        limiter.record_failure(peer);
+        assert!(limiter.is_blocked(peer));
+        // The lockout is per address, whatever the port.
+        assert!(limiter.is_blocked(Some("10.0.0.5:40000".parse().unwrap())));
+        assert!(!limiter.is_blocked(other));
+        // Peers without an address, like Unix sockets, are never tracked.
+        for _ in 0..AUTH_MAX_FAILURES {
+            limiter.record_failure(None);
+        }
+        assert!(!limiter.is_blocked(None));
+    }
+
+    #[test]
+    fn auth_rate_limiter_lockout_expires() {
+        let limiter = AuthRateLimiter::default();
+        let address: SocketAddr = "10.0.0.5:51234".parse().unwrap();
+        let (peer, ip) = (Some(address), address.ip());
+        for _ in 0..AUTH_MAX_FAILURES {
+            limiter.record_failure(peer);
+        }
+        assert!(limiter.is_blocked(peer));
+        if let Some(entry) = limiter.peers.lock().unwrap().get_mut(&ip) {
+            entry.blocked_until = Some(Instant::now());
+        }
+        assert!(!limiter.is_blocked(peer));
+        // The expired entry is dropped on the next failure, which starts a new window.
+        limiter.record_failure(peer);
+        assert!(!limiter.is_blocked(peer));
+        assert_eq!(limiter.peers.lock().unwrap()[&ip].count, 1);
+    }
+
+    fn sha256_hex(bytes: &[u8]) -> String {
+        encode_hex(ring::digest::digest(&ring::digest::SHA256, bytes).as_ref())
+    }
+
+    fn download_events(
+        id: &str,
+        offset: u64,
+        tail: &[u8],
+        whole: &[u8],
+    ) -> (JsonValue, JsonValue, JsonValue) {
+        (
+            serde_json::json!({
+              "event": "download-start",
+              "id": id,
+              "data": { "path": "/srv/backup.bin", "offset": offset }
+            }),
+            serde_json::json!({
+              "event": "download-chunk",
+              "id": id,
+              "data": BASE64_STANDARD.encode(tail)
+            }),
+            serde_json::json!({
+              "event": "download-complete",
+              "id": id,
+              "data": { "sha256": sha256_hex(tail), "fileSha256": sha256_hex(whole) }
+            }),
+        )
+    }
+
+    #[test]
+    fn resumed_downloads_append_and_verify_the_whole_file() {
+        let requests = RemoteRequestTracker::default();
+        let mut downloads = HashMap::new();
+        let (partial, _) = create_download_file("backup.bin").unwrap();
+        fs::write(&partial, b"hello ").unwrap();
+        let mut payload = serde_json::json!({
+          "action": "download",
+          "id": "dl-1",
+          "path": "/srv/backup.bin",
+          "resumePath": partial.to_string_lossy()
+        });
+        prepare_download_resume(&requests, &mut payload).unwrap();
+        assert_eq!(payload.get("resumePath"), None);
+        assert_eq!(payload["offset"], 6);
+        let (mut start, mut chunk, mut complete) =
+            download_events("dl-1", 6, b"world", b"hello world");
+        assert!(track_remote_download(&mut downloads, &requests, &mut start));
+        assert!(!track_remote_download(
+            &mut downloads,
+            &requests,
+            &mut chunk
+        ));
+        assert!(track_remote_download(
+            &mut downloads,
+            &requests,
+            &mut complete
+        ));
+        assert_eq!(complete["event"], "download-complete");
+        assert_eq!(
+            complete["data"]["localPath"].as_str().map(PathBuf::from),
+            Some(fs::canonicalize(&partial).unwrap())
+        );
+        assert_eq!(fs::read(&partial).unwrap(), b"hello world");
+        let _ = fs::remove_file(partial);
+    }
+
+    #[test]
+    fn resumed_downloads_reject_a_corrupt_partial_file() {
+        let requests = RemoteRequestTracker::default();
+        let mut downloads = HashMap::new();
+        let (partial, _) = create_download_file("backup.bin").unwrap();
+        fs::write(&partial, b"HELLO ").unwrap();
+        let mut payload = serde_json::json!({
+          "action": "download",
+          "id": "dl-2",
+          "path": "/srv/backup.bin",
+          "resumePath": partial.to_string_lossy()
+        });
+        prepare_download_resume(&requests, &mut payload).unwrap();
+        let (mut start, mut chunk, mut complete) =
+            download_events("dl-2", 6, b"world", b"hello world");
+        track_remote_download(&mut downloads, &requests, &mut start);
+        track_remote_download(&mut downloads, &requests, &mut chunk);
+        track_remote_download(&mut downloads, &requests, &mut complete);
+        assert_eq!(complete["message"], "checksum-mismatch");
+        assert!(!partial.exists());
+    }
+
+    #[test]
+    fn resumed_downloads_must_be_partial_files_in_the_temp_dir() {
+        let requests = RemoteRequestTracker::default();
+        let dir = scratch_dir("resume");
+        let elsewhere = dir.join("notes.txt");
+        fs::write(&elsewhere, b"keep").unwrap();
+        let mut payload = serde_json::json!({
+          "action": "download",
+          "id": "dl-3",
+          "path": "/srv/backup.bin",
+          "resumePath": elsewhere.to_string_lossy()
+        });
+        assert!(prepare_download_resume(&requests, &mut payload).is_err());
+        let (partial, _) = create_download_file("backup.bin").unwrap();
+        fs::write(&partial, b"hello ").unwrap();
+        let mut mismatched = serde_json::json!({
+          "action": "download",
+          "id": "dl-4",
+          "path": "/srv/backup.bin",
+          "offset": 3,
+          "resumePath": partial.to_string_lossy()
+        });
+        assert!(prepare_download_resume(&requests, &mut mismatched).is_err());
+        let _ = fs::remove_file(partial);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copying_a_folder_keeps_nested_links_as_links() {
+        let dir = scratch_dir("copy-symlink");
+        fs::create_dir_all(dir.join("allowed/tree")).unwrap();
+        fs::create_dir(dir.join("outside")).unwrap();
+        fs::write(dir.join("outside/secret.txt"), b"secret").unwrap();
+        fs::write(dir.join("allowed/tree/notes.txt"), b"notes").unwrap();
+        std::os::unix::fs::symlink(dir.join("outside"), dir.join("allowed/tree/folder")).unwrap();
+        std::os::unix::fs::symlink(
+            dir.join("outside/secret.txt"),
+            dir.join("allowed/tree/file"),
+        )
+        .unwrap();
+        copy_path(&dir.join("allowed/tree"), &dir.join("allowed/copy")).unwrap();
+        let copy = dir.join("allowed/copy");
+        assert_eq!(fs::read(copy.join("notes.txt")).unwrap(), b"notes");
+        for name in ["folder", "file"] {
+            let metadata = fs::symlink_metadata(copy.join(name)).unwrap();
+            assert!(metadata.file_type().is_symlink(), "{name} was followed");
+        }
+        // The copied links still resolve outside the root, so the sandbox refuses them.
+        let grant = RemoteGrant {
+            roots: Some(vec![dir.join("allowed")]),
+            ..RemoteGrant::full("restricted")
+        };
+        assert!(!grant.allows_path(&copy.join("file")));
+        assert!(!grant.allows_path(&copy.join("folder/secret.txt")));
+        let _ = fs::remove_dir_all(dir);
+    }
+}
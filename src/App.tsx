@@ -1,8 +1,9 @@
 import { Link, Outlet, useNavigate } from "@tanstack/react-router";
 import { getAllWindows, getCurrentWindow } from "@tauri-apps/api/window";
-import { useEffect, useRef, useState } from "react";
+import { useCallback, useEffect, useRef, useState } from "react";
 import dragabyteLogoUrl from "../.github/assets/icon.png";
-import { getLaunchContext } from "./features/scan/api";
+import type { LaunchContext } from "./features/scan/api";
+import { getLaunchContext, listenLaunchContext } from "./features/scan/api";
 import { useUIStore } from "./store";
 
 const hasMultipleWindows = async (): Promise<boolean> => {
@@ -36,24 +37,46 @@ const App = (): JSX.Element => {
   const navigate = useNavigate();
   const hasCheckedLaunchContext = useRef(false);
 
+  const openLaunchContext = useCallback(
+    (ctx: LaunchContext): void => {
+      if (ctx.mode === "rename" && ctx.paths.length > 0) {
+        void navigate({
+          to: "/bulk-rename",
+          search: {
+            path: ctx.path ?? ctx.paths[0],
+            paths: serializePaths(ctx.paths),
+          },
+        });
+      }
+    },
+    [navigate],
+  );
+
   useEffect(() => {
     if (hasCheckedLaunchContext.current) return;
     hasCheckedLaunchContext.current = true;
 
-    getLaunchContext()
-      .then((ctx) => {
-        if (ctx.mode === "rename" && ctx.paths.length > 0) {
-          void navigate({
-            to: "/bulk-rename",
-            search: {
-              path: ctx.path ?? ctx.paths[0],
-              paths: serializePaths(ctx.paths),
-            },
-          });
-        }
+    getLaunchContext().then(openLaunchContext).catch(console.error);
+  }, [openLaunchContext]);
+
+  useEffect((): (() => void) => {
+    let cleanup: (() => void) | null = null;
+    // The scan view starts forwarded scans itself once it is showing.
+    listenLaunchContext((ctx) => {
+      if (ctx.mode === "scan" && ctx.paths.length > 0) {
+        void navigate({ to: "/" });
+        return;
+      }
+      openLaunchContext(ctx);
+    })
+      .then((unlisten) => {
+        cleanup = unlisten;
       })
       .catch(console.error);
-  }, [navigate]);
+    return (): void => {
+      cleanup?.();
+    };
+  }, [navigate, openLaunchContext]);
 
   useEffect((): (() => void) => {
     const handleContextMenu = (event: MouseEvent): void => {
@@ -0,0 +1,95 @@
+import { useState } from "react";
+import { FiLock, FiRadio } from "react-icons/fi";
+import { discoverRemoteHosts } from "./api";
+import type { DiscoveredHost } from "./types";
+
+interface DiscoveryPanelProps {
+  onSelect: (host: DiscoveredHost) => void;
+}
+
+const formatDiscoveryError = (error: unknown): string => {
+  if (error instanceof Error) {
+    return error.message || "Discovery failed";
+  }
+  if (typeof error === "string") {
+    return error.trim() || "Discovery failed";
+  }
+  return "Discovery failed";
+};
+
+const DiscoveryPanel = ({ onSelect }: DiscoveryPanelProps): JSX.Element => {
+  const [searching, setSearching] = useState(false);
+  const [hosts, setHosts] = useState<DiscoveredHost[] | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  const handleDiscover = async (): Promise<void> => {
+    setError(null);
+    setSearching(true);
+    try {
+      setHosts(await discoverRemoteHosts());
+    } catch (err) {
+      setError(formatDiscoveryError(err));
+    } finally {
+      setSearching(false);
+    }
+  };
+
+  return (
+    <div className="bg-slate-900/40 p-5 rounded-xl border border-slate-800/50 space-y-4">
+      <div className="flex items-center justify-between gap-2">
+        <div className="flex items-center gap-2 text-slate-200">
+          <FiRadio className="text-blue-400" />
+          <h3 className="font-semibold">Local Network</h3>
+        </div>
+        <button
+          type="button"
+          onClick={() => void handleDiscover()}
+          disabled={searching}
+          className="rounded-lg px-4 py-2 text-sm font-semibold border bg-slate-800 text-slate-200 hover:bg-slate-700 border-slate-700 disabled:opacity-50 disabled:cursor-not-allowed cursor-pointer"
+        >
+          {searching ? "Searching..." : "Discover"}
+        </button>
+      </div>
+      {error && <p className="text-xs text-red-400">{error}</p>}
+      {hosts && hosts.length === 0 && (
+        <p className="text-xs text-slate-500">
+          No servers found. Headless instances must be started with --mdns.
+        </p>
+      )}
+      {hosts && hosts.length > 0 && (
+        <div className="space-y-2">
+          {hosts.map((host) => (
+            <div
+              key={`${host.name}-${host.port}`}
+              className="flex items-center justify-between gap-4 rounded-lg border border-slate-800 bg-slate-950/40 px-3 py-2"
+            >
+              <div className="min-w-0">
+                <p className="text-sm font-medium text-slate-200 truncate">
+                  {host.name}
+                </p>
+                <p className="text-[10px] font-mono text-slate-500">
+                  {host.addresses[0] ?? host.hostname}:{host.port}
+                  {host.version ? ` · v${host.version}` : ""}
+                </p>
+              </div>
+              <div className="flex items-center gap-2">
+                {host.tls && (
+                  <FiLock className="text-emerald-400" title="TLS required" />
+                )}
+                <button
+                  type="button"
+                  onClick={() => onSelect(host)}
+                  className="text-xs font-medium text-slate-400 hover:text-slate-200 transition px-2 py-1 rounded hover:bg-slate-800"
+                >
+                  Use
+                </button>
+              </div>
+            </div>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+};
+
+export default DiscoveryPanel;
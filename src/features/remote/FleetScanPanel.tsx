@@ -0,0 +1,143 @@
+import { useEffect, useState } from "react";
+import { FiLayers } from "react-icons/fi";
+import { formatBytes, formatDuration } from "../../lib/utils";
+import { fetchSettings } from "../settings/api";
+import { listenFleetScanHost, runFleetScan } from "./api";
+import type { FleetHostResult, FleetScanResult } from "./types";
+
+const formatFleetError = (error: unknown): string => {
+  if (error instanceof Error) {
+    return error.message || "Fleet scan failed";
+  }
+  if (typeof error === "string") {
+    return error.trim() || "Fleet scan failed";
+  }
+  return "Fleet scan failed";
+};
+
+const FleetHostRow = ({ host }: { host: FleetHostResult }): JSX.Element => (
+  <div className="flex items-center justify-between gap-4 rounded-lg border border-slate-800 bg-slate-950/40 px-3 py-2">
+    <div className="min-w-0">
+      <p className="text-sm font-medium text-slate-200 truncate">
+        {host.name}
+      </p>
+      <p className="text-[10px] font-mono text-slate-500">{host.address}</p>
+    </div>
+    {host.ok ? (
+      <div className="text-right">
+        <p className="text-sm font-semibold text-emerald-300">
+          {formatBytes(host.totalBytes)}
+        </p>
+        <p className="text-[10px] text-slate-500">
+          {formatDuration(host.durationMs)}
+        </p>
+      </div>
+    ) : (
+      <span className="text-[10px] text-red-400 bg-red-500/5 px-2 py-0.5 rounded border border-red-500/10 truncate max-w-[240px]">
+        {host.error}
+      </span>
+    )}
+  </div>
+);
+
+const FleetScanPanel = (): JSX.Element | null => {
+  const [hostNames, setHostNames] = useState<string[]>([]);
+  const [pathInput, setPathInput] = useState("");
+  const [running, setRunning] = useState(false);
+  const [finishedHosts, setFinishedHosts] = useState(0);
+  const [result, setResult] = useState<FleetScanResult | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect((): (() => void) => {
+    let active = true;
+    fetchSettings()
+      .then((settings) => {
+        if (!active) return;
+        setHostNames((settings.remoteHosts ?? []).map((host) => host.name));
+      })
+      .catch(() => undefined);
+    return (): void => {
+      active = false;
+    };
+  }, []);
+
+  useEffect((): (() => void) => {
+    let cleanup: (() => void) | null = null;
+    listenFleetScanHost(() => {
+      setFinishedHosts((count) => count + 1);
+    })
+      .then((unlisten) => {
+        cleanup = unlisten;
+      })
+      .catch(() => undefined);
+    return (): void => {
+      cleanup?.();
+    };
+  }, []);
+
+  if (hostNames.length === 0) return null;
+
+  const handleRun = async (): Promise<void> => {
+    const path = pathInput.trim();
+    if (!path) {
+      setError("Provide a path to scan on every host.");
+      return;
+    }
+    setError(null);
+    setResult(null);
+    setFinishedHosts(0);
+    setRunning(true);
+    try {
+      setResult(await runFleetScan(path));
+    } catch (err) {
+      setError(formatFleetError(err));
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  return (
+    <div className="bg-slate-900/40 p-5 rounded-xl border border-slate-800/50 space-y-4">
+      <div className="flex items-center gap-2 text-slate-200">
+        <FiLayers className="text-blue-400" />
+        <h3 className="font-semibold">Fleet Scan</h3>
+        <span className="text-xs text-slate-500">
+          {hostNames.length} saved hosts
+        </span>
+      </div>
+      <div className="flex gap-3">
+        <input
+          value={pathInput}
+          onChange={(e) => setPathInput(e.target.value)}
+          placeholder="/var/lib/builds"
+          className="flex-1 rounded-lg border border-slate-700/50 bg-slate-950 px-3 py-2 text-sm text-slate-200 placeholder:text-slate-600 focus:border-blue-500/50 focus:ring-1 focus:ring-blue-500/20"
+        />
+        <button
+          type="button"
+          onClick={() => void handleRun()}
+          disabled={running}
+          className="rounded-lg px-4 py-2 text-sm font-semibold border bg-slate-800 text-slate-200 hover:bg-slate-700 border-slate-700 disabled:opacity-50 disabled:cursor-not-allowed cursor-pointer"
+        >
+          {running
+            ? `Scanning ${finishedHosts}/${hostNames.length}`
+            : "Scan all hosts"}
+        </button>
+      </div>
+      {error && <p className="text-xs text-red-400">{error}</p>}
+      {result && (
+        <div className="space-y-2">
+          <p className="text-xs text-slate-400">
+            {formatBytes(result.summary.totalBytes)} across{" "}
+            {result.hosts.filter((host) => host.ok).length} of{" "}
+            {result.hosts.length} hosts
+          </p>
+          {result.hosts.map((host) => (
+            <FleetHostRow key={`${host.name}-${host.address}`} host={host} />
+          ))}
+        </div>
+      )}
+    </div>
+  );
+};
+
+export default FleetScanPanel;
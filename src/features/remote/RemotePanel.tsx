@@ -19,13 +19,18 @@ import { useEffect, useMemo, useState } from "react";
 import { FiActivity, FiPower, FiServer, FiTrash2 } from "react-icons/fi";
 import { MdDragIndicator } from "react-icons/md";
 import { useUIStore } from "../../store";
+import { fetchSettings } from "../settings/api";
+import type { RemoteHostProfile } from "../settings/types";
 import {
   connectRemote,
   disconnectRemote,
+  getRemoteAddress,
   listenRemoteStatus,
   requestRemoteStatus,
 } from "./api";
-import type { RemoteServer, RemoteStatus } from "./types";
+import DiscoveryPanel from "./DiscoveryPanel";
+import FleetScanPanel from "./FleetScanPanel";
+import type { DiscoveredHost, RemoteServer, RemoteStatus } from "./types";
 
 const createId = (): string => {
   if (globalThis.crypto?.randomUUID) {
@@ -44,6 +49,7 @@ const parsePort = (value: string): number | null => {
 const getStatusColor = (status: RemoteStatus): string => {
   if (status === "connected") return "text-emerald-400";
   if (status === "connecting") return "text-blue-400";
+  if (status === "reconnecting") return "text-amber-400";
   if (status === "error") return "text-red-400";
   return "text-slate-500";
 };
@@ -53,6 +59,8 @@ const getStatusBadgeClasses = (status: RemoteStatus): string => {
     return "bg-emerald-500/10 text-emerald-400 border-emerald-500/20";
   if (status === "connecting")
     return "bg-blue-500/10 text-blue-400 border-blue-500/20";
+  if (status === "reconnecting")
+    return "bg-amber-500/10 text-amber-400 border-amber-500/20";
   if (status === "error") return "bg-red-500/10 text-red-400 border-red-500/20";
   return "bg-slate-800/50 text-slate-400 border-slate-700/50";
 };
@@ -60,12 +68,16 @@ const getStatusBadgeClasses = (status: RemoteStatus): string => {
 const getStatusLabel = (status: RemoteStatus): string => {
   if (status === "connected") return "Online";
   if (status === "connecting") return "Connecting...";
+  if (status === "reconnecting") return "Reconnecting...";
   if (status === "error") return "Error";
   return "Offline";
 };
 
+const isRemoteOpen = (status: RemoteStatus): boolean =>
+  status === "connected" || status === "reconnecting";
+
 const getConnectBtnStyles = (status: RemoteStatus): string => {
-  if (status === "connected") {
+  if (isRemoteOpen(status)) {
     return "bg-red-500/10 text-red-200 hover:bg-red-500/20 border-red-500/20";
   }
   if (status === "connecting") {
@@ -157,6 +169,35 @@ const findServerByAddress = (
   return null;
 };
 
+// Saved profiles not yet in the list, matched by address.
+const getMissingProfiles = (
+  servers: RemoteServer[],
+  profiles: RemoteHostProfile[],
+): RemoteServer[] => {
+  const known = new Set(servers.map((server) => getRemoteAddress(server)));
+  const missing: RemoteServer[] = [];
+  for (let i = 0; i < profiles.length; i += 1) {
+    const profile = profiles[i];
+    if (!profile?.host.trim()) continue;
+    const server: RemoteServer = {
+      id: createId(),
+      name: profile.name || profile.host,
+      host: profile.host.trim(),
+      port: profile.port,
+      token: "",
+      tokenRef: profile.tokenRef ?? undefined,
+      tlsFingerprint: profile.tlsFingerprint ?? undefined,
+      status: "disconnected",
+      lastMessage: null,
+    };
+    const address = getRemoteAddress(server);
+    if (known.has(address)) continue;
+    known.add(address);
+    missing.push(server);
+  }
+  return missing;
+};
+
 interface SortableServerItemProps {
   server: RemoteServer;
   activeRemoteServerId: string | null;
@@ -225,6 +266,14 @@ const SortableServerItem = ({
                 <span className="text-xs text-slate-500 mb-0.5 font-mono">
                   {server.host}:{server.port}
                 </span>
+                {server.status === "connected" && server.serverInfo && (
+                  <span
+                    className="text-[10px] text-slate-500 mb-0.5"
+                    title={server.serverInfo.capabilities.join(", ")}
+                  >
+                    v{server.serverInfo.appVersion} · {server.serverInfo.os}
+                  </span>
+                )}
               </div>
               <div className="flex items-center gap-3">
                 <div
@@ -236,9 +285,11 @@ const SortableServerItem = ({
                         ? "bg-emerald-500 shadow-[0_0_8px_rgba(16,185,129,0.4)]"
                         : server.status === "connecting"
                           ? "bg-blue-500 animate-pulse"
-                          : server.status === "error"
-                            ? "bg-red-500"
-                            : "bg-slate-600"
+                          : server.status === "reconnecting"
+                            ? "bg-amber-500 animate-pulse"
+                            : server.status === "error"
+                              ? "bg-red-500"
+                              : "bg-slate-600"
                     }`}
                   />
                   {getStatusLabel(server.status)}
@@ -256,7 +307,7 @@ const SortableServerItem = ({
                 type="button"
                 onPointerDown={(e) => e.stopPropagation()}
                 onClick={() => {
-                  if (server.status === "connected") {
+                  if (isRemoteOpen(server.status)) {
                     onDisconnect(server);
                   } else {
                     onConnect(server);
@@ -268,17 +319,17 @@ const SortableServerItem = ({
                     connectingServerId && connectingServerId !== server.id,
                   )
                 }
-                title={server.status === "connected" ? "Disconnect" : "Connect"}
+                title={isRemoteOpen(server.status) ? "Disconnect" : "Connect"}
                 className={`rounded-lg px-3 py-1.5 text-xs font-semibold border transition-all flex items-center gap-2 cursor-pointer
                   ${getConnectBtnStyles(server.status)}
                 `}
               >
                 <FiPower
                   size={13}
-                  className={server.status === "connected" ? "" : "-mt-0.5"}
+                  className={isRemoteOpen(server.status) ? "" : "-mt-0.5"}
                 />
                 <span>
-                  {server.status === "connected" ? "Disconnect" : "Connect"}
+                  {isRemoteOpen(server.status) ? "Disconnect" : "Connect"}
                 </span>
               </button>
             </div>
@@ -309,7 +360,7 @@ const SortableServerItem = ({
                   onChange={(e) => onUpdateToken(server, e.target.value)}
                   type="password"
                   className="flex-1 bg-slate-950/50 border border-slate-800 rounded px-2.5 py-1.5 text-xs text-slate-300 focus:border-blue-500/40 focus:bg-slate-950 transition-colors font-mono"
-                  placeholder="Access Token"
+                  placeholder={server.tokenRef || "Access Token"}
                 />
                 <button
                   type="button"
@@ -344,6 +395,7 @@ const RemotePanel = (): JSX.Element => {
   const [hostInput, setHostInput] = useState("");
   const [portInput, setPortInput] = useState("4799");
   const [tokenInput, setTokenInput] = useState("");
+  const [tlsFingerprintInput, setTlsFingerprintInput] = useState("");
   const [error, setError] = useState<string | null>(null);
 
   const sensors = useSensors(
@@ -374,16 +426,42 @@ const RemotePanel = (): JSX.Element => {
     return null;
   }, [remoteServers]);
 
+  useEffect((): (() => void) => {
+    let active = true;
+    fetchSettings()
+      .then((settings) => {
+        if (!active) return;
+        const state = useUIStore.getState();
+        const missing = getMissingProfiles(
+          state.remoteServers,
+          settings.remoteHosts ?? [],
+        );
+        if (missing.length === 0) return;
+        setRemoteServers([...state.remoteServers, ...missing]);
+      })
+      .catch(() => undefined);
+    return (): void => {
+      active = false;
+    };
+  }, [setRemoteServers]);
+
   useEffect((): (() => void) => {
     let active = true;
     requestRemoteStatus()
       .then((status) => {
         if (!active || !activeRemoteServerId) return;
-        if (status.connected) {
+        const servers = useUIStore.getState().remoteServers;
+        const server = servers.find((s) => s.id === activeRemoteServerId);
+        if (!server || server.status === "connecting") return;
+        const address = getRemoteAddress(server);
+        const connection = status.connections?.find(
+          (item) => item.address === address,
+        );
+        if (connection) {
           if (isRemoteUnauthorized) {
             return;
           }
-          updateRemoteServerStatus(activeRemoteServerId, "connected");
+          updateRemoteServerStatus(activeRemoteServerId, connection.status);
           return;
         }
         updateRemoteServerStatus(activeRemoteServerId, "disconnected", null);
@@ -425,7 +503,8 @@ const RemotePanel = (): JSX.Element => {
         payload.status,
         payload.message ?? null,
       );
-      if (payload.status === "connected" && matched?.id) {
+      // A background reconnect must not steal the selection.
+      if (payload.status === "connected" && matched?.id && !activeId) {
         setActiveRemoteServerId(matched.id);
       }
     })
@@ -445,12 +524,14 @@ const RemotePanel = (): JSX.Element => {
       return;
     }
     const { name, host, port, token } = result.data;
+    const tlsFingerprint = tlsFingerprintInput.trim();
     const server: RemoteServer = {
       id: createId(),
       name: name || host,
       host,
       port,
       token,
+      tlsFingerprint: tlsFingerprint || undefined,
       status: "disconnected",
       lastMessage: null,
     };
@@ -460,11 +541,20 @@ const RemotePanel = (): JSX.Element => {
     setHostInput("");
     setPortInput("4799");
     setTokenInput("");
+    setTlsFingerprintInput("");
+    setError(null);
+  };
+
+  // Fills the form rather than saving, since the token still has to be entered.
+  const handleUseDiscovered = (host: DiscoveredHost): void => {
+    setNameInput(host.name);
+    setHostInput(host.addresses[0] ?? host.hostname);
+    setPortInput(String(host.port));
     setError(null);
   };
 
   const handleConnect = async (server: RemoteServer): Promise<void> => {
-    if (!server.token.trim()) {
+    if (!server.token.trim() && !server.tokenRef) {
       setError("Token is required for authentication.");
       return;
     }
@@ -489,7 +579,7 @@ const RemotePanel = (): JSX.Element => {
     setError(null);
     setActiveRemoteServerId(server.id);
     try {
-      await disconnectRemote();
+      await disconnectRemote(server);
       updateRemoteServerStatus(server.id, "disconnected", null);
     } catch (err) {
       const message = formatConnectError(err);
@@ -626,6 +716,18 @@ const RemotePanel = (): JSX.Element => {
                   className="w-full rounded-lg border border-slate-700/50 bg-slate-950 px-3 py-2 text-sm text-slate-200 placeholder:text-slate-600 focus:border-blue-500/50 focus:ring-1 focus:ring-blue-500/20"
                 />
               </div>
+
+              <div className="space-y-1.5">
+                <label className="text-xs font-medium text-slate-400 ml-1">
+                  TLS Fingerprint (Optional)
+                </label>
+                <input
+                  value={tlsFingerprintInput}
+                  onChange={(e) => setTlsFingerprintInput(e.target.value)}
+                  placeholder="SHA-256 of the server certificate"
+                  className="w-full rounded-lg border border-slate-700/50 bg-slate-950 px-3 py-2 text-sm text-slate-200 placeholder:text-slate-600 focus:border-blue-500/50 focus:ring-1 focus:ring-blue-500/20 font-mono"
+                />
+              </div>
             </div>
 
             <button
@@ -705,6 +807,10 @@ const RemotePanel = (): JSX.Element => {
           </div>
         </div>
       </div>
+
+      <DiscoveryPanel onSelect={handleUseDiscovered} />
+
+      <FleetScanPanel />
     </div>
   );
 };
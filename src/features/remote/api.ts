@@ -1,16 +1,37 @@
 import { listen } from "@tauri-apps/api/event";
 import { invokeCommand } from "../../lib/tauriInvoke";
 import type {
+  DiscoveredHost,
+  FleetHostEvent,
+  FleetScanResult,
   RemoteEventPayload,
   RemoteServer,
+  RemoteServerInfo,
+  RemoteStatus,
   RemoteStatusPayload,
 } from "./types";
 
+interface RemoteConnectionSnapshot {
+  address: string;
+  status: RemoteStatus;
+}
+
 interface RemoteStatusSnapshot {
   connected: boolean;
   address?: string | null;
+  connections?: RemoteConnectionSnapshot[];
 }
 
+// Connection that requests go to while several servers are connected.
+let remoteTarget: string | null = null;
+
+export const getRemoteAddress = (server: RemoteServer): string =>
+  `${server.host.trim()}:${server.port}`;
+
+export const setRemoteTarget = (address: string | null): void => {
+  remoteTarget = address;
+};
+
 const safeStringify = (value: unknown): string => {
   try {
     return JSON.stringify(value);
@@ -37,19 +58,28 @@ export const connectRemote = async (server: RemoteServer): Promise<void> => {
     host: server.host,
     port: server.port,
     hasToken: Boolean(server.token),
+    tokenRef: server.tokenRef ?? null,
+    tls: Boolean(server.tlsFingerprint),
   });
   await invokeCommand<void>("remote_connect", {
     payload: {
       host: server.host,
       port: server.port,
       token: server.token || null,
+      tokenRef: server.tokenRef || null,
+      tls: server.tlsFingerprint ? { pinSha256: server.tlsFingerprint } : null,
     },
   });
 };
 
-export const disconnectRemote = async (): Promise<void> => {
-  console.debug("[remote] disconnect");
-  await invokeCommand<void>("remote_disconnect");
+export const disconnectRemote = async (
+  server?: RemoteServer | null,
+): Promise<void> => {
+  const address = server ? getRemoteAddress(server) : null;
+  console.debug("[remote] disconnect", { address });
+  await invokeCommand<void>("remote_disconnect", {
+    payload: { address },
+  });
 };
 
 export const sendRemote = async (
@@ -59,16 +89,45 @@ export const sendRemote = async (
   console.debug("[remote] send", safePayload);
   console.debug("[remote] send json", safeStringify(safePayload));
   await invokeCommand<void>("remote_send", {
-    payload: { payload: safePayload },
+    payload: { payload: safePayload, address: remoteTarget },
+  });
+};
+
+// Resolves with the first event answering the request, under the payload's id or
+// a random one the backend assigns. Rejects with the server's error code or when
+// no reply arrives.
+export const requestRemote = async (
+  payload: Record<string, unknown>,
+  timeoutMs?: number,
+): Promise<RemoteEventPayload> => {
+  console.debug("[remote] request", safeStringify(payload));
+  return invokeCommand<RemoteEventPayload>("remote_request", {
+    payload: {
+      payload,
+      address: remoteTarget,
+      timeoutMs: timeoutMs ?? null,
+    },
   });
 };
 
+export type RemoteListSort = "name" | "size" | "modified" | "type";
+
+export interface RemoteListOptions {
+  includeFiles?: boolean;
+  details?: boolean;
+  sort?: RemoteListSort;
+  descending?: boolean;
+  offset?: number;
+  limit?: number;
+}
+
 export const requestRemoteList = async (
   id: string,
   path?: string | null,
+  options: RemoteListOptions = {},
 ): Promise<void> => {
-  console.debug("[remote] list", { id, path: path ?? null });
-  await sendRemote({ action: "list", id, path: path ?? null });
+  console.debug("[remote] list", { id, path: path ?? null, options });
+  await sendRemote({ action: "list", id, path: path ?? null, ...options });
 };
 
 export const requestRemoteDiskUsage = async (
@@ -79,12 +138,25 @@ export const requestRemoteDiskUsage = async (
   await sendRemote({ action: "disk", id, path });
 };
 
+// `resumePath` is the `localPath` of an interrupted download; the rest is appended to it.
 export const requestRemoteFile = async (
   id: string,
   path: string,
+  resumePath?: string,
 ): Promise<void> => {
-  console.debug("[remote] read", { id, path });
-  await sendRemote({ action: "read", id, path });
+  console.debug("[remote] download", { id, path, resumePath });
+  await sendRemote({ action: "download", id, path, resumePath });
+};
+
+export type RemoteFileOpAction = "delete" | "mkdir" | "rename" | "copy" | "move";
+
+export const requestRemoteFileOp = async (
+  id: string,
+  action: RemoteFileOpAction,
+  args: Record<string, unknown>,
+): Promise<void> => {
+  console.debug("[remote] file op", { id, action });
+  await sendRemote({ action, id, ...args });
 };
 
 export const saveTempAndOpen = async (
@@ -103,6 +175,20 @@ export const requestRemotePing = async (id: string): Promise<void> => {
   await sendRemote({ action: "ping", id });
 };
 
+export const REMOTE_PROTOCOL_VERSION = 1;
+
+const REMOTE_HELLO_TIMEOUT_MS = 5000;
+
+// Servers that predate `hello` reject it without an id, so this times out.
+export const requestRemoteHello = async (): Promise<RemoteServerInfo> => {
+  console.debug("[remote] hello");
+  const reply = await requestRemote(
+    { action: "hello", protocolVersion: REMOTE_PROTOCOL_VERSION },
+    REMOTE_HELLO_TIMEOUT_MS,
+  );
+  return reply.data as RemoteServerInfo;
+};
+
 export const requestRemoteStatus = async (): Promise<RemoteStatusSnapshot> => {
   console.debug("[remote] status request");
   return invokeCommand<RemoteStatusSnapshot>("remote_status");
@@ -114,6 +200,33 @@ export const listenRemoteStatus = async (
   return listenToRemoteEvent("remote-status", handler);
 };
 
+// Scans `path` on the named saved hosts (all of them when empty) and resolves
+// once every host has finished or failed.
+export const runFleetScan = async (
+  path: string,
+  hosts: string[] = [],
+): Promise<FleetScanResult> => {
+  console.debug("[remote] fleet scan", { path, hosts });
+  return invokeCommand<FleetScanResult>("remote_fleet_scan", {
+    payload: { path, hosts },
+  });
+};
+
+export const discoverRemoteHosts = async (
+  timeoutMs?: number,
+): Promise<DiscoveredHost[]> => {
+  console.debug("[remote] discover", { timeoutMs });
+  return invokeCommand<DiscoveredHost[]>("remote_discover", {
+    payload: { timeoutMs: timeoutMs ?? null },
+  });
+};
+
+export const listenFleetScanHost = async (
+  handler: (payload: FleetHostEvent) => void,
+): Promise<() => void> => {
+  return listenToRemoteEvent("fleet-scan-host", handler);
+};
+
 export const listenRemoteEvent = async (
   handler: (payload: RemoteEventPayload) => void,
 ): Promise<() => void> => {
@@ -1,7 +1,10 @@
+import type { ScanSummary } from "../scan/types";
+
 export type RemoteStatus =
   | "disconnected"
   | "connecting"
   | "connected"
+  | "reconnecting"
   | "error";
 
 export interface RemoteServer {
@@ -10,8 +13,62 @@ export interface RemoteServer {
   host: string;
   port: number;
   token: string;
+  // Saved profiles point at the token (`env:NAME` or `file:PATH`) instead.
+  tokenRef?: string;
+  // SHA-256 fingerprint of the server certificate; when set the connection uses TLS.
+  tlsFingerprint?: string;
   status: RemoteStatus;
   lastMessage?: string | null;
+  // Reported by the server's `hello`; null for servers that predate it.
+  serverInfo?: RemoteServerInfo | null;
+}
+
+export interface RemoteServerInfo {
+  protocolVersion: number;
+  appVersion: string;
+  os: string;
+  arch: string;
+  headless: boolean;
+  token: string;
+  scopes: string[];
+  capabilities: string[];
+  limits: Record<string, number>;
+}
+
+export interface FleetHostResult {
+  name: string;
+  address: string;
+  ok: boolean;
+  error: string | null;
+  totalBytes: number | null;
+  durationMs: number;
+}
+
+export interface FleetScanResult {
+  id: string;
+  // One child of the root per host, with paths prefixed by `//host:port`.
+  summary: ScanSummary;
+  hosts: FleetHostResult[];
+}
+
+export interface FleetHostEvent {
+  id: string;
+  name: string;
+  address: string;
+  ok: boolean;
+  error: string | null;
+}
+
+export interface DiscoveredHost {
+  name: string;
+  hostname: string;
+  // IPv4 first; `.local` hostnames may not resolve through the system resolver.
+  addresses: string[];
+  port: number;
+  version: string | null;
+  protocolVersion: number | null;
+  tls: boolean;
+  tokenRequired: boolean;
 }
 
 export interface RemoteStatusPayload {
@@ -24,17 +81,42 @@ export interface RemoteListEntry {
   name: string;
   path: string;
   isDir: boolean;
+  sizeBytes?: number;
+  modified?: number;
+  permissions?: string;
+  readonly?: boolean;
+  symlinkTarget?: string;
 }
 
 export interface RemoteListPayload {
   path: string | null;
   entries: RemoteListEntry[];
   os?: "windows" | "unix";
+  total?: number;
+  offset?: number;
+  nextOffset?: number | null;
 }
 
-export interface RemoteReadPayload {
+export interface RemoteDownloadPayload {
   path: string;
-  content: string;
+  offset: number;
+  length: number;
+  sha256: string;
+  fileSha256: string | null;
+  localPath?: string;
+}
+
+export interface RemoteFileOpResult {
+  path: string;
+  newPath: string | null;
+  ok: boolean;
+  error: string | null;
+}
+
+export interface RemoteFileOpPayload {
+  successCount: number;
+  errorCount: number;
+  results: RemoteFileOpResult[];
 }
 
 export interface RemoteEventPayload {
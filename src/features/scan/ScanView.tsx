@@ -15,21 +15,27 @@ import {
 } from "../../lib/utils";
 import { useUIStore } from "../../store";
 import {
+  getRemoteAddress,
   listenRemoteEvent,
   listenRemoteStatus,
   requestRemoteDiskUsage,
   requestRemoteFile,
+  requestRemoteFileOp,
+  requestRemoteHello,
   requestRemoteList,
   requestRemotePing,
   requestRemoteStatus,
-  saveTempAndOpen,
   sendRemote,
+  setRemoteTarget,
+  type RemoteFileOpAction,
+  type RemoteListSort,
 } from "../remote/api";
 import RemotePanel from "../remote/RemotePanel";
 import type {
+  RemoteDownloadPayload,
   RemoteEventPayload,
+  RemoteFileOpPayload,
   RemoteListPayload,
-  RemoteReadPayload,
   RemoteServer,
   RemoteStatus,
 } from "../remote/types";
@@ -42,12 +48,16 @@ import {
   deleteItem,
   getDiskUsage,
   getLaunchContext,
+  listenLaunchContext,
   openPath,
+  pauseScan,
   renameItem,
+  resumeScan,
   showInExplorer,
   startScan,
   toggleContextMenu,
 } from "./api";
+import type { LaunchContext } from "./api";
 import { ExportModal } from "./ExportModal";
 import ScanTree from "./ScanTree";
 import Treemap from "./Treemap";
@@ -350,6 +360,7 @@ const parseAgeInput = (value: string): AgeInputResult => {
 const getRemoteStatusLabel = (status: RemoteStatus): string => {
   if (status === "connected") return "Online";
   if (status === "connecting") return "Connecting";
+  if (status === "reconnecting") return "Reconnecting";
   if (status === "error") return "Error";
   return "Offline";
 };
@@ -357,6 +368,7 @@ const getRemoteStatusLabel = (status: RemoteStatus): string => {
 const getRemoteStatusPillClasses = (status: RemoteStatus): string => {
   if (status === "connected") return "bg-emerald-500/15 text-emerald-200";
   if (status === "connecting") return "bg-blue-500/15 text-blue-200";
+  if (status === "reconnecting") return "bg-amber-500/15 text-amber-200";
   if (status === "error") return "bg-red-500/15 text-red-200";
   return "bg-slate-800/60 text-slate-400";
 };
@@ -364,6 +376,7 @@ const getRemoteStatusPillClasses = (status: RemoteStatus): string => {
 const getRemoteStatusDotClasses = (status: RemoteStatus): string => {
   if (status === "connected") return "bg-emerald-400";
   if (status === "connecting") return "bg-blue-400";
+  if (status === "reconnecting") return "bg-amber-400";
   if (status === "error") return "bg-red-400";
   return "bg-slate-500";
 };
@@ -372,11 +385,24 @@ type RemoteTreeNode = {
   path: string;
   name: string;
   isDir: boolean;
+  sizeBytes?: number;
+  modified?: number;
+  symlinkTarget?: string;
   children: string[] | null;
+  nextOffset?: number | null;
   loading: boolean;
   error: string | null;
 };
 
+const REMOTE_LIST_PAGE_SIZE = 500;
+
+const REMOTE_LIST_SORT_OPTIONS: { value: RemoteListSort; label: string }[] = [
+  { value: "name", label: "Name" },
+  { value: "type", label: "Type" },
+  { value: "size", label: "Largest" },
+  { value: "modified", label: "Newest" },
+];
+
 type RemoteBreadcrumb = {
   label: string;
   path: string;
@@ -747,6 +773,10 @@ const createRemoteRequestId = (): string => {
   return `remote-${Date.now()}-${Math.random().toString(16).slice(2)}`;
 };
 
+// Several roots are combined under a root with an empty path that has no file
+// on disk behind it.
+const isCombinedRoot = (node: ScanNode): boolean => node.path === "";
+
 const resolveScanPath = (ctx: {
   path: string | null;
   paths: string[];
@@ -1020,6 +1050,10 @@ const ScanView = (): JSX.Element => {
   const [summary, setSummary] = useState<ScanSummary | null>(null);
   const [selectedPath, setSelectedPath] = useState<string | null>(null);
   const [isScanning, setIsScanning] = useState(false);
+  const [launchRequest, setLaunchRequest] = useState<LaunchContext | null>(
+    null,
+  );
+  const [isPaused, setIsPaused] = useState(false);
   const [error, setError] = useState<string | null>(null);
   const [isErrorExpanded, setIsErrorExpanded] = useState(false);
   const [errorCopied, setErrorCopied] = useState(false);
@@ -1027,6 +1061,7 @@ const ScanView = (): JSX.Element => {
   const [isSettingsModalOpen, setIsSettingsModalOpen] = useState(false);
   const [isRemotePanelOpen, setIsRemotePanelOpen] = useState(true);
   const [isRemoteScanOpen, setIsRemoteScanOpen] = useState(false);
+  const [remoteRescanPending, setRemoteRescanPending] = useState(false);
   const [isExportModalOpen, setIsExportModalOpen] = useState(false);
   const [remoteListLoading, setRemoteListLoading] = useState(false);
   const [remoteListError, setRemoteListError] = useState<string | null>(null);
@@ -1045,6 +1080,7 @@ const ScanView = (): JSX.Element => {
   );
   const [remoteFocusPath, setRemoteFocusPath] = useState<string | null>(null);
   const [remotePathInput, setRemotePathInput] = useState<string>("");
+  const [remoteListSort, setRemoteListSort] = useState<RemoteListSort>("name");
   const [expandedPaths, setExpandedPaths] = useState<Set<string>>(
     () => new Set<string>(),
   );
@@ -1088,6 +1124,7 @@ const ScanView = (): JSX.Element => {
     hideEmptyExplorerFolders,
     priorityMode,
     throttleLevel,
+    scanArchives,
     filterMode,
     simpleFilterIds,
     includeExtensionsInput,
@@ -1104,6 +1141,7 @@ const ScanView = (): JSX.Element => {
     excludeRegexInput,
     setPriorityMode,
     setThrottleLevel,
+    setScanArchives,
     setFilterMode,
     setSimpleFilterIds,
     setIncludeExtensionsInput,
@@ -1124,6 +1162,7 @@ const ScanView = (): JSX.Element => {
     remoteSyncEnabled,
     remoteServers,
     activeRemoteServerId,
+    updateRemoteServer,
     updateRemoteServerStatus,
     setActiveRemoteServerId,
   } = useUIStore();
@@ -1135,8 +1174,11 @@ const ScanView = (): JSX.Element => {
   const historyIndexRef = useRef<number>(-1);
   const remoteRequestIdRef = useRef<string | null>(null);
   const remoteReadRequestIdRef = useRef<string | null>(null);
+  const remoteFileOpRequestIdRef = useRef<string | null>(null);
   const remoteListRequestIdRef = useRef<string | null>(null);
   const activeScanPathRef = useRef<string | null>(null);
+  // Other roots scanned together with `activeScanPathRef`.
+  const activeScanExtraPathsRef = useRef<string[]>([]);
   const activeScanModeRef = useRef<"local" | "remote" | null>(null);
   const scanRestartTimeoutRef = useRef<number | null>(null);
   const activeScanIdRef = useRef<string | null>(null);
@@ -1486,12 +1528,14 @@ const ScanView = (): JSX.Element => {
       priorityMode,
       throttleLevel,
       filters: scanFilters,
+      scanArchives,
     };
-  }, [priorityMode, scanFilters, throttleLevel]);
+  }, [priorityMode, scanArchives, scanFilters, throttleLevel]);
   const scanRestartKey = useMemo<string>(() => {
     return JSON.stringify({
       priorityMode,
       throttleLevel,
+      scanArchives,
       filterMode,
       simpleFilterIds,
       includeExtensionsInput,
@@ -1511,6 +1555,7 @@ const ScanView = (): JSX.Element => {
   }, [
     priorityMode,
     throttleLevel,
+    scanArchives,
     filterMode,
     simpleFilterIds,
     includeExtensionsInput,
@@ -1550,6 +1595,12 @@ const ScanView = (): JSX.Element => {
     }
     return null;
   }, [activeRemoteServerId, remoteServers]);
+  const activeRemoteAddress = activeRemoteServer
+    ? getRemoteAddress(activeRemoteServer)
+    : null;
+  useEffect((): void => {
+    setRemoteTarget(activeRemoteAddress);
+  }, [activeRemoteAddress]);
   const isRemoteUnauthorized =
     activeRemoteServer?.status === "error" &&
     activeRemoteServer?.lastMessage === "Unauthorized token";
@@ -1669,6 +1720,7 @@ const ScanView = (): JSX.Element => {
     setSelectedPath(null);
     setSelectedFilePath(null);
     setIsScanning(true);
+    setIsPaused(false);
     setScanStatus("scanning");
     setError(null);
     setErrorCopied(false);
@@ -1755,8 +1807,11 @@ const ScanView = (): JSX.Element => {
     }
     remoteReadRequestIdRef.current = null;
     applySummary(payload);
-    addScanHistory(payload.root.path);
+    if (!isCombinedRoot(payload.root)) {
+      addScanHistory(payload.root.path);
+    }
     setIsScanning(false);
+    setIsPaused(false);
     setScanStatus("complete");
     clearListeners();
     clearScanRestartTimeout();
@@ -1771,6 +1826,7 @@ const ScanView = (): JSX.Element => {
     setError(message);
     setIsErrorExpanded(true);
     setIsScanning(false);
+    setIsPaused(false);
     setScanStatus("idle");
     clearListeners();
     activeScanPathRef.current = null;
@@ -1782,6 +1838,7 @@ const ScanView = (): JSX.Element => {
   const cancelScanRun = (_message: string): void => {
     remoteReadRequestIdRef.current = null;
     setIsScanning(false);
+    setIsPaused(false);
     setScanStatus("idle");
     clearListeners();
     activeScanPathRef.current = null;
@@ -1793,6 +1850,16 @@ const ScanView = (): JSX.Element => {
   const handleRemoteEvent = useCallback(
     (payload: RemoteEventPayload): void => {
       if (!remoteSyncEnabled) return;
+      // Only the selected connection drives the view.
+      const eventAddress = (payload as { _address?: string })._address;
+      if (
+        eventAddress &&
+        activeRemoteAddress &&
+        eventAddress !== activeRemoteAddress &&
+        payload.message !== "unauthorized"
+      ) {
+        return;
+      }
       const pingRequestId = remotePingRequestIdRef.current;
       if (payload.event === "pong") {
         if (payload.id && pingRequestId && payload.id === pingRequestId) {
@@ -1861,6 +1928,7 @@ const ScanView = (): JSX.Element => {
             if (timeoutId) window.clearTimeout(timeoutId);
             remoteListTimeoutsRef.current.delete(payload.id);
           }
+          const isNextPage = (data.offset ?? 0) > 0;
           setRemoteTree((previous) => {
             const next = { ...previous };
             const childPaths: string[] = [];
@@ -1873,18 +1941,28 @@ const ScanView = (): JSX.Element => {
                 path: entry.path,
                 name: entry.name,
                 isDir: entry.isDir,
+                sizeBytes: entry.sizeBytes,
+                modified: entry.modified,
+                symlinkTarget: entry.symlinkTarget,
                 children: existing?.children ?? null,
+                nextOffset: existing?.nextOffset ?? null,
                 loading: false,
                 error: null,
               };
             }
             if (listPath) {
               const existing = next[listPath];
+              const previousChildren =
+                isNextPage && existing?.children ? existing.children : [];
               next[listPath] = {
                 path: listPath,
                 name: getRemoteNodeName(listPath),
                 isDir: true,
-                children: childPaths,
+                sizeBytes: existing?.sizeBytes,
+                modified: existing?.modified,
+                symlinkTarget: existing?.symlinkTarget,
+                children: [...previousChildren, ...childPaths],
+                nextOffset: data.nextOffset ?? null,
                 loading: false,
                 error: null,
               };
@@ -1937,28 +2015,53 @@ const ScanView = (): JSX.Element => {
         }
         return;
       }
-      if (payload.event === "read-complete") {
+      if (payload.event === "download-complete") {
         const readId = remoteReadRequestIdRef.current;
         if (!readId) return;
         if (payload.id && payload.id !== readId) return;
         remoteReadRequestIdRef.current = null;
         setError(null);
-        const data = payload.data as RemoteReadPayload | undefined;
-        if (data?.content) {
-          void saveTempAndOpen(
-            data.path.split(/[/\\]/).pop() ?? "download",
-            data.content,
-          ).catch((err) => setError(toErrorMessage(err)));
+        const data = payload.data as RemoteDownloadPayload | undefined;
+        if (data?.localPath) {
+          void openPath(data.localPath).catch((err) =>
+            setError(toErrorMessage(err)),
+          );
+        }
+        return;
+      }
+      if (payload.event === "file-op-result") {
+        const opId = remoteFileOpRequestIdRef.current;
+        if (!opId || payload.id !== opId) return;
+        remoteFileOpRequestIdRef.current = null;
+        const data = payload.data as RemoteFileOpPayload | undefined;
+        const failures = data?.results.filter((result) => !result.ok) ?? [];
+        if (failures.length > 0) {
+          setError(
+            failures
+              .map((result) => `${result.path}: ${result.error ?? "Failed"}`)
+              .join("\n"),
+          );
+        }
+        if (data && data.successCount > 0) {
+          setRemoteRescanPending(true);
         }
         return;
       }
       if (payload.event === "error") {
+        // Servers without `hello` reject it as invalid JSON, with no id.
+        if (!payload.id && payload.message === "invalid_json") return;
         const readId = remoteReadRequestIdRef.current;
         if (payload.id && readId && payload.id === readId) {
           remoteReadRequestIdRef.current = null;
           setError(payload.message ?? "Failed to download file");
           return;
         }
+        const opId = remoteFileOpRequestIdRef.current;
+        if (payload.id && opId && payload.id === opId) {
+          remoteFileOpRequestIdRef.current = null;
+          setError(payload.message ?? "Remote file operation failed");
+          return;
+        }
         if (payload.id) {
           const requestPath =
             remoteListRequestMapRef.current.get(payload.id) ?? null;
@@ -2027,9 +2130,20 @@ const ScanView = (): JSX.Element => {
         if (payload.id && activeId && payload.id !== activeId) return;
         cancelScanRun(payload.message ?? "Remote scan cancelled");
         remoteRequestIdRef.current = null;
+        return;
+      }
+      if (payload.event === "scan-paused") {
+        if (payload.id && activeId && payload.id !== activeId) return;
+        setIsPaused(true);
+        return;
+      }
+      if (payload.event === "scan-resumed") {
+        if (payload.id && activeId && payload.id !== activeId) return;
+        setIsPaused(false);
       }
     },
     [
+      activeRemoteAddress,
       activeRemoteServerId,
       applySummary,
       cancelScanRun,
@@ -2079,9 +2193,19 @@ const ScanView = (): JSX.Element => {
     updateRemoteServerStatus,
   ]);
 
-  const startScanWithFolder = async (folder: string): Promise<void> => {
+  const startScanWithFolder = async (
+    folder: string,
+    extraPaths?: string[],
+  ): Promise<void> => {
+    // Rescanning the same root keeps the roots it was combined with.
+    const paths =
+      extraPaths ??
+      (folder === activeScanPathRef.current
+        ? activeScanExtraPathsRef.current
+        : []);
     clearScanCompleteTimeout();
     activeScanPathRef.current = folder;
+    activeScanExtraPathsRef.current = paths;
     activeScanModeRef.current = "local";
     lastScanPathRef.current = folder;
     lastScanModeRef.current = "local";
@@ -2101,8 +2225,11 @@ const ScanView = (): JSX.Element => {
           onComplete: finishScan,
           onError: failScan,
           onCancel: cancelScanRun,
+          onPause: () => setIsPaused(true),
+          onResume: () => setIsPaused(false),
         },
         scanId,
+        paths,
       );
     } catch (err) {
       failScan(toErrorMessage(err));
@@ -2112,6 +2239,7 @@ const ScanView = (): JSX.Element => {
   const startRemoteScanWithPath = async (path: string): Promise<void> => {
     clearScanCompleteTimeout();
     activeScanPathRef.current = path;
+    activeScanExtraPathsRef.current = [];
     activeScanModeRef.current = "remote";
     lastScanPathRef.current = path;
     lastScanModeRef.current = "remote";
@@ -2141,8 +2269,21 @@ const ScanView = (): JSX.Element => {
     }
   };
 
+  const setRemoteScanPaused = async (paused: boolean): Promise<void> => {
+    const requestId = remoteRequestIdRef.current ?? undefined;
+    try {
+      await sendRemote({ action: paused ? "pause" : "resume", id: requestId });
+    } catch (err) {
+      failScan(toErrorMessage(err));
+    }
+  };
+
   const requestRemoteListing = useCallback(
-    (path?: string | null): void => {
+    (
+      path?: string | null,
+      offset = 0,
+      sort: RemoteListSort = remoteListSort,
+    ): void => {
       if (!isRemoteConnected) {
         setRemoteListError("No remote server connected.");
         return;
@@ -2217,7 +2358,14 @@ const ScanView = (): JSX.Element => {
         });
       }, 5000);
       remoteListTimeoutsRef.current.set(requestId, timeoutId);
-      void requestRemoteList(requestId, path).catch((err) => {
+      void requestRemoteList(requestId, path, {
+        includeFiles: true,
+        details: true,
+        sort,
+        descending: sort === "size" || sort === "modified",
+        offset,
+        limit: REMOTE_LIST_PAGE_SIZE,
+      }).catch((err) => {
         console.warn("[remote] list request failed", { requestId, err });
         if (isTopLevelRequest) {
           setRemoteListLoading(false);
@@ -2225,7 +2373,7 @@ const ScanView = (): JSX.Element => {
         }
       });
     },
-    [isRemoteConnected],
+    [isRemoteConnected, remoteListSort],
   );
 
   useEffect((): (() => void) | void => {
@@ -2330,8 +2478,14 @@ const ScanView = (): JSX.Element => {
     requestRemoteListing(null);
   }, [requestRemoteListing]);
 
+  const selectedRemoteNode = selectedRemotePath
+    ? remoteTree[selectedRemotePath]
+    : undefined;
+  const isRemoteFolderSelected =
+    !!selectedRemotePath && selectedRemoteNode?.isDir !== false;
+
   const handleRemoteScanConfirm = async (): Promise<void> => {
-    if (!selectedRemotePath) {
+    if (!selectedRemotePath || !isRemoteFolderSelected) {
       setRemoteListError("Select a remote folder to scan.");
       return;
     }
@@ -2377,6 +2531,22 @@ const ScanView = (): JSX.Element => {
     [setSimpleFilterIds, simpleFilterIds],
   );
 
+  const handleTogglePause = async (): Promise<void> => {
+    try {
+      if (isRemoteConnected && remoteRequestIdRef.current) {
+        await setRemoteScanPaused(!isPaused);
+        return;
+      }
+      if (isPaused) {
+        await resumeScan();
+      } else {
+        await pauseScan();
+      }
+    } catch (err) {
+      failScan(toErrorMessage(err));
+    }
+  };
+
   const handleCancelScan = async (): Promise<void> => {
     try {
       if (isRemoteConnected && remoteRequestIdRef.current) {
@@ -2389,18 +2559,25 @@ const ScanView = (): JSX.Element => {
     }
   };
 
+  const openRemoteFile = useCallback(
+    (path: string): void => {
+      if (!isRemoteConnected) {
+        setError("Remote server not connected");
+        return;
+      }
+      setError("Downloading remote file...");
+      const requestId = createRemoteRequestId();
+      remoteReadRequestIdRef.current = requestId;
+      requestRemoteFile(requestId, path);
+    },
+    [setError, isRemoteConnected],
+  );
+
   const handleOpenPath = useCallback(
     async (path: string | null): Promise<void> => {
       if (!path) return;
       if (activeScanModeRef.current === "remote") {
-        if (!isRemoteConnected) {
-          setError("Remote server not connected");
-          return;
-        }
-        setError("Downloading remote file...");
-        const requestId = createRemoteRequestId();
-        remoteReadRequestIdRef.current = requestId;
-        requestRemoteFile(requestId, path);
+        openRemoteFile(path);
         return;
       }
       try {
@@ -2410,7 +2587,7 @@ const ScanView = (): JSX.Element => {
         setError(toErrorMessage(err));
       }
     },
-    [setError, isRemoteConnected],
+    [setError, openRemoteFile],
   );
 
   const handleShowInExplorer = useCallback(
@@ -2426,6 +2603,26 @@ const ScanView = (): JSX.Element => {
     [setError],
   );
 
+  const runRemoteFileOp = useCallback(
+    (action: RemoteFileOpAction, args: Record<string, unknown>): void => {
+      const requestId = createRemoteRequestId();
+      remoteFileOpRequestIdRef.current = requestId;
+      requestRemoteFileOp(requestId, action, args).catch((err) =>
+        setError(toErrorMessage(err)),
+      );
+    },
+    [],
+  );
+
+  useEffect((): void => {
+    if (!remoteRescanPending) return;
+    setRemoteRescanPending(false);
+    const path = activeScanPathRef.current;
+    if (path && activeScanModeRef.current === "remote") {
+      void startRemoteScanWithPath(path);
+    }
+  }, [remoteRescanPending, startRemoteScanWithPath]);
+
   const executeRename = useCallback(
     async (newName: string) => {
       const candidate = renameCandidate;
@@ -2443,6 +2640,10 @@ const ScanView = (): JSX.Element => {
         newPath = `${parent}${separator}${newName}`;
       }
 
+      if (activeScanModeRef.current === "remote") {
+        runRemoteFileOp("rename", { items: [{ path, newPath }] });
+        return;
+      }
       try {
         await renameItem(path, newPath);
         const nextPath =
@@ -2454,17 +2655,10 @@ const ScanView = (): JSX.Element => {
         setError(toErrorMessage(err));
       }
     },
-    [renameCandidate, startScanWithFolder, summary],
+    [renameCandidate, runRemoteFileOp, startScanWithFolder, summary],
   );
 
   const handleRename = useCallback((path: string, name: string): void => {
-    if (activeScanModeRef.current === "remote") {
-      setAlertState({
-        title: "Not Supported",
-        message: "Remote rename is not supported yet.",
-      });
-      return;
-    }
     setRenameCandidate({ path, name });
   }, []);
 
@@ -2473,6 +2667,10 @@ const ScanView = (): JSX.Element => {
     setDeleteCandidate(null);
     if (!path) return;
 
+    if (activeScanModeRef.current === "remote") {
+      runRemoteFileOp("delete", { paths: [path] });
+      return;
+    }
     try {
       await deleteItem(path);
       const nextPath = activeScanPathRef.current ?? summary?.root.path ?? null;
@@ -2482,16 +2680,9 @@ const ScanView = (): JSX.Element => {
     } catch (err) {
       setError(toErrorMessage(err));
     }
-  }, [deleteCandidate, startScanWithFolder, summary]);
+  }, [deleteCandidate, runRemoteFileOp, startScanWithFolder, summary]);
 
   const handleDelete = useCallback((path: string): void => {
-    if (activeScanModeRef.current === "remote") {
-      setAlertState({
-        title: "Not Supported",
-        message: "Remote delete is not supported yet.",
-      });
-      return;
-    }
     setDeleteCandidate(path);
   }, []);
 
@@ -2507,6 +2698,10 @@ const ScanView = (): JSX.Element => {
         : `${parentPath}${separator}`;
       const newPath = `${prefix}${name}`;
 
+      if (activeScanModeRef.current === "remote") {
+        runRemoteFileOp("mkdir", { paths: [newPath] });
+        return;
+      }
       try {
         await createFolder(newPath);
         const nextPath =
@@ -2518,17 +2713,10 @@ const ScanView = (): JSX.Element => {
         setError(toErrorMessage(err));
       }
     },
-    [createFolderCandidate, startScanWithFolder, summary],
+    [createFolderCandidate, runRemoteFileOp, startScanWithFolder, summary],
   );
 
   const handleCreateFolder = useCallback((parentPath: string): void => {
-    if (activeScanModeRef.current === "remote") {
-      setAlertState({
-        title: "Not Supported",
-        message: "Remote create folder is not supported yet.",
-      });
-      return;
-    }
     setCreateFolderCandidate(parentPath);
   }, []);
 
@@ -2549,6 +2737,10 @@ const ScanView = (): JSX.Element => {
         newPath = `${parent}${separator}${newName}`;
       }
 
+      if (activeScanModeRef.current === "remote") {
+        runRemoteFileOp("copy", { items: [{ path, newPath }] });
+        return;
+      }
       try {
         await copyItem(path, newPath);
         const nextPath =
@@ -2560,17 +2752,10 @@ const ScanView = (): JSX.Element => {
         setError(toErrorMessage(err));
       }
     },
-    [duplicateCandidate, startScanWithFolder, summary],
+    [duplicateCandidate, runRemoteFileOp, startScanWithFolder, summary],
   );
 
   const handleDuplicate = useCallback((path: string, name: string): void => {
-    if (activeScanModeRef.current === "remote") {
-      setAlertState({
-        title: "Not Supported",
-        message: "Remote copy is not supported yet.",
-      });
-      return;
-    }
     setDuplicateCandidate({ path, name });
   }, []);
 
@@ -2762,14 +2947,36 @@ const ScanView = (): JSX.Element => {
               onClick={(): void => {
                 captureRemoteTreeScroll();
                 setSelectedRemotePath(path);
-                setRemoteFocusPath(path);
+                if (node.isDir) setRemoteFocusPath(path);
               }}
               onDoubleClick={(): void => {
-                toggleRemoteTreeNode(path);
+                if (node.isDir) {
+                  toggleRemoteTreeNode(path);
+                } else {
+                  openRemoteFile(path);
+                }
               }}
-              className="flex-1 text-left py-2"
+              className="flex flex-1 items-center gap-3 py-2 text-left"
             >
-              {node.name}
+              <span className="flex-1 truncate">
+                {node.name}
+                {node.symlinkTarget ? (
+                  <span className="text-slate-500">
+                    {" "}
+                    → {node.symlinkTarget}
+                  </span>
+                ) : null}
+              </span>
+              {node.modified ? (
+                <span className="text-[11px] text-slate-500">
+                  {new Date(node.modified).toLocaleDateString()}
+                </span>
+              ) : null}
+              {node.isDir ? null : (
+                <span className="w-20 text-right text-[11px] text-slate-400">
+                  {formatBytes(node.sizeBytes)}
+                </span>
+              )}
             </button>
           </div>
           {node.error ? (
@@ -2789,13 +2996,28 @@ const ScanView = (): JSX.Element => {
               )}
             </ul>
           ) : null}
+          {isExpanded && node.nextOffset && !node.loading ? (
+            <button
+              type="button"
+              onClick={(): void => {
+                captureRemoteTreeScroll();
+                requestRemoteListing(path, node.nextOffset ?? 0);
+              }}
+              className="py-1 text-[11px] text-blue-300 hover:text-blue-200"
+              style={{ paddingLeft: `${indent + 44}px` }}
+            >
+              Load more...
+            </button>
+          ) : null}
         </li>
       );
     },
     [
       captureRemoteTreeScroll,
+      openRemoteFile,
       remoteTree,
       remoteTreeExpanded,
+      requestRemoteListing,
       selectedRemotePath,
       toggleRemoteTreeNode,
     ],
@@ -2810,6 +3032,7 @@ const ScanView = (): JSX.Element => {
     (event: MouseEvent, node: ScanNode): void => {
       event.preventDefault();
       event.stopPropagation();
+      if (isCombinedRoot(node)) return;
       const position = getMenuPosition(event);
       setContextMenu({ ...position, kind: "folder", node });
     },
@@ -2878,19 +3101,30 @@ const ScanView = (): JSX.Element => {
     requestRemoteStatus()
       .then((status) => {
         if (!active) return;
-        if (status.connected) {
+        const connections = status.connections ?? [];
+        if (status.connected || connections.length > 0) {
           // Prevent reconnect loop if unauthorized
           if (isRemoteUnauthorized) {
             return;
           }
+          for (let i = 0; i < connections.length; i += 1) {
+            const connection = connections[i];
+            if (!connection) continue;
+            const server = resolveRemoteServerByAddress(connection.address);
+            if (server && server.status !== connection.status) {
+              updateRemoteServerStatus(server.id, connection.status);
+            }
+          }
+          if (
+            activeRemoteAddress &&
+            connections.some((item) => item.address === activeRemoteAddress)
+          ) {
+            return;
+          }
           const matched = resolveRemoteServerByAddress(status.address ?? null);
           if (matched) {
             updateRemoteServerStatus(matched.id, "connected");
             setActiveRemoteServerId(matched.id);
-            return;
-          }
-          if (activeRemoteServerId) {
-            updateRemoteServerStatus(activeRemoteServerId, "connected");
           }
           return;
         }
@@ -2908,7 +3142,7 @@ const ScanView = (): JSX.Element => {
       active = false;
     };
   }, [
-    activeRemoteServerId,
+    activeRemoteAddress,
     isRemoteUnauthorized,
     remoteServers,
     resolveRemoteServerByAddress,
@@ -2930,8 +3164,21 @@ const ScanView = (): JSX.Element => {
         payload.status,
         payload.message ?? null,
       );
-      if (payload.status === "connected" && matched?.id) {
-        setActiveRemoteServerId(matched.id);
+      // A background reconnect of another server must not steal the selection.
+      if (
+        payload.status === "connected" &&
+        matched?.id &&
+        (!activeRemoteServerId || matched.id === activeRemoteServerId)
+      ) {
+        const serverId = matched.id;
+        setActiveRemoteServerId(serverId);
+        requestRemoteHello()
+          .then((serverInfo) => {
+            updateRemoteServer(serverId, { serverInfo });
+          })
+          .catch(() => {
+            updateRemoteServer(serverId, { serverInfo: null });
+          });
       }
     })
       .then((unlisten) => {
@@ -2946,6 +3193,7 @@ const ScanView = (): JSX.Element => {
     isRemoteUnauthorized,
     resolveRemoteServerByAddress,
     setActiveRemoteServerId,
+    updateRemoteServer,
     updateRemoteServerStatus,
   ]);
   useEffect((): (() => void) | void => {
@@ -3010,12 +3258,42 @@ const ScanView = (): JSX.Element => {
         const scanPath = resolveScanPath(ctx);
         if (scanPath && ctx.mode === "scan" && !hasAutoScanRef.current) {
           hasAutoScanRef.current = true;
-          startScanWithFolder(scanPath).catch(console.error);
+          const extraPaths = ctx.paths.filter((value) => value !== scanPath);
+          startScanWithFolder(scanPath, extraPaths).catch(console.error);
         }
       })
       .catch(console.error);
   }, []);
 
+  useEffect((): (() => void) => {
+    let cleanup: (() => void) | null = null;
+    listenLaunchContext(setLaunchRequest)
+      .then((unlisten) => {
+        cleanup = unlisten;
+      })
+      .catch(console.error);
+    return (): void => {
+      cleanup?.();
+    };
+  }, []);
+
+  useEffect((): void => {
+    if (!launchRequest) return;
+    setLaunchRequest(null);
+    const scanPath = resolveScanPath(launchRequest);
+    if (!scanPath || launchRequest.mode !== "scan") return;
+    hasAutoScanRef.current = true;
+    const extraPaths = launchRequest.paths.filter(
+      (value) => value !== scanPath,
+    );
+    void (async () => {
+      if (isScanning) {
+        await cancelScan();
+      }
+      await startScanWithFolder(scanPath, extraPaths);
+    })();
+  }, [launchRequest]);
+
   useEffect((): (() => void) | void => {
     if (!contextMenu) {
       return undefined;
@@ -3187,7 +3465,7 @@ const ScanView = (): JSX.Element => {
                   Remote Scan
                 </p>
                 <h3 className="text-lg font-semibold text-slate-100">
-                  Sync & Select a Remote Folder
+                  Browse & Select a Remote Folder
                 </h3>
               </div>
               <button
@@ -3247,6 +3525,26 @@ const ScanView = (): JSX.Element => {
                   >
                     Go
                   </button>
+                  <select
+                    value={remoteListSort}
+                    onChange={(event): void => {
+                      const sort = event.target.value as RemoteListSort;
+                      setRemoteListSort(sort);
+                      requestRemoteListing(
+                        remoteFocusPath ?? remoteRootRequestPath,
+                        0,
+                        sort,
+                      );
+                    }}
+                    className="rounded-md border border-slate-700 bg-slate-950 px-2 py-1 text-xs text-slate-200"
+                    aria-label="Sort remote entries"
+                  >
+                    {REMOTE_LIST_SORT_OPTIONS.map((option) => (
+                      <option key={option.value} value={option.value}>
+                        {option.label}
+                      </option>
+                    ))}
+                  </select>
                 </div>
               </div>
               {remoteListError ? (
@@ -3260,11 +3558,11 @@ const ScanView = (): JSX.Element => {
               >
                 {remoteListLoading ? (
                   <div className="px-4 py-6 text-xs text-slate-400">
-                    Loading remote folders...
+                    Loading remote files...
                   </div>
                 ) : remoteTreeRoots.length === 0 ? (
                   <div className="px-4 py-6 text-xs text-slate-500">
-                    No folders available.
+                    No files or folders available.
                   </div>
                 ) : (
                   <ul className="py-1">
@@ -3293,7 +3591,7 @@ const ScanView = (): JSX.Element => {
                     onClick={(): void => {
                       void handleRemoteScanConfirm();
                     }}
-                    disabled={!selectedRemotePath}
+                    disabled={!isRemoteFolderSelected}
                     className="rounded-md bg-emerald-500/80 px-3 py-1 text-xs font-semibold text-white hover:bg-emerald-500 disabled:bg-slate-700 disabled:cursor-not-allowed"
                   >
                     Start Remote Scan
@@ -3672,8 +3970,21 @@ const ScanView = (): JSX.Element => {
             disabled={isScanning}
             className="rounded-md bg-gradient-to-r from-blue-500 to-blue-400 px-4 py-2 text-sm font-semibold text-white shadow-lg shadow-blue-500/20 transition hover:from-blue-400 hover:to-blue-300 focus-visible:outline-none focus-visible:ring-2 focus-visible:ring-blue-400/60 disabled:cursor-not-allowed disabled:from-slate-700 disabled:to-slate-700 disabled:text-slate-300"
           >
-            {isScanning ? "Scanning..." : "Scan Folder"}
+            {isScanning
+              ? isPaused
+                ? "Paused"
+                : "Scanning..."
+              : "Scan Folder"}
           </button>
+          {isScanning ? (
+            <button
+              type="button"
+              onClick={handleTogglePause}
+              className="rounded-md border border-amber-500/60 bg-amber-500/10 px-3 py-2 text-xs font-semibold text-amber-200 transition hover:bg-amber-500/20 focus-visible:outline-none focus-visible:ring-2 focus-visible:ring-amber-400/60"
+            >
+              {isPaused ? "Resume" : "Pause"}
+            </button>
+          ) : null}
           {isScanning ? (
             <button
               type="button"
@@ -3820,8 +4131,20 @@ const ScanView = (): JSX.Element => {
                   <option value="low">Low</option>
                   <option value="medium">Medium</option>
                   <option value="high">High</option>
+                  <option value="adaptive">Adaptive</option>
                 </select>
               </label>
+              <label className="flex items-center gap-2 text-xs text-slate-400">
+                <input
+                  type="checkbox"
+                  checked={scanArchives}
+                  onChange={(event): void =>
+                    setScanArchives(event.target.checked)
+                  }
+                  className="h-3.5 w-3.5 accent-blue-500"
+                />
+                Look inside archives (.zip, .tar, .tar.gz, .tar.zst)
+              </label>
             </div>
           </div>
 
@@ -4547,7 +4870,7 @@ const ScanView = (): JSX.Element => {
             </svg>
           </div>
           <h3 className="text-lg font-semibold text-slate-200 mb-2">
-            Scanning...
+            {isPaused ? "Paused" : "Scanning..."}
           </h3>
           <p className="text-slate-400 max-w-sm mx-auto mb-6">
             Analyzing storage usage...
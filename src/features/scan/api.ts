@@ -1,4 +1,5 @@
 import { listen } from "@tauri-apps/api/event";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
 import { invokeCommand } from "../../lib/tauriInvoke";
 import type { DiskUsage, ScanOptions, ScanSummary } from "./types";
 
@@ -7,6 +8,8 @@ interface ScanHandlers {
   onComplete: (summary: ScanSummary) => void;
   onError: (message: string) => void;
   onCancel: (message: string) => void;
+  onPause?: (message: string) => void;
+  onResume?: (message: string) => void;
 }
 
 const listenToScanEvent = async <T>(
@@ -26,22 +29,42 @@ export const startScan = async (
   options: ScanOptions,
   handlers: ScanHandlers,
   scanId: string,
+  paths?: string[],
 ): Promise<() => void> => {
-  const [unlistenProgress, unlistenComplete, unlistenError, unlistenCancelled] =
-    await Promise.all([
-      listenToScanEvent<ScanSummary>("scan-progress", handlers.onProgress),
-      listenToScanEvent<ScanSummary>("scan-complete", handlers.onComplete),
-      listenToScanEvent<string>("scan-error", handlers.onError),
-      listenToScanEvent<string>("scan-cancelled", handlers.onCancel),
-    ]);
-
-  await invokeCommand<void>("scan_path", { path, options, id: scanId });
+  const [
+    unlistenProgress,
+    unlistenComplete,
+    unlistenError,
+    unlistenCancelled,
+    unlistenPaused,
+    unlistenResumed,
+  ] = await Promise.all([
+    listenToScanEvent<ScanSummary>("scan-progress", handlers.onProgress),
+    listenToScanEvent<ScanSummary>("scan-complete", handlers.onComplete),
+    listenToScanEvent<string>("scan-error", handlers.onError),
+    listenToScanEvent<string>("scan-cancelled", handlers.onCancel),
+    listenToScanEvent<string>("scan-paused", (message) => {
+      handlers.onPause?.(message);
+    }),
+    listenToScanEvent<string>("scan-resumed", (message) => {
+      handlers.onResume?.(message);
+    }),
+  ]);
+
+  await invokeCommand<void>("scan_path", {
+    path,
+    paths: paths ?? null,
+    options,
+    id: scanId,
+  });
 
   return (): void => {
     unlistenProgress();
     unlistenComplete();
     unlistenError();
     unlistenCancelled();
+    unlistenPaused();
+    unlistenResumed();
   };
 };
 
@@ -49,6 +72,14 @@ export const cancelScan = async (): Promise<void> => {
   return invokeCommand<void>("cancel_scan");
 };
 
+export const pauseScan = async (): Promise<void> => {
+  return invokeCommand<void>("pause_scan");
+};
+
+export const resumeScan = async (): Promise<void> => {
+  return invokeCommand<void>("resume_scan");
+};
+
 export const checkContextMenu = async (): Promise<boolean> => {
   return invokeCommand<boolean>("is_context_menu_enabled");
 };
@@ -71,6 +102,22 @@ export const getLaunchContext = async (): Promise<LaunchContext> => {
   return invokeCommand<LaunchContext>("get_launch_context");
 };
 
+// Sent to the main window when a later launch hands its paths to this
+// instance, with the paths of launches that arrived together merged in.
+export const listenLaunchContext = async (
+  handler: (ctx: LaunchContext) => void,
+): Promise<() => void> => {
+  const unlisten = await getCurrentWebviewWindow().listen<LaunchContext>(
+    "launch-context",
+    (event) => {
+      handler(event.payload);
+    },
+  );
+  return (): void => {
+    unlisten();
+  };
+};
+
 export const openPath = async (path: string): Promise<void> => {
   return invokeCommand<void>("open_path", { path });
 };
@@ -6,6 +6,9 @@ export interface ScanNode {
   dirCount: number;
   files: ScanFile[];
   children: ScanNode[];
+  archive?: "zip" | "tar" | "tar.gz" | "tar.zst";
+  // Set on fleet scan host nodes whose scan failed.
+  error?: string;
 }
 
 export interface ScanFile {
@@ -13,6 +16,7 @@ export interface ScanFile {
   name: string;
   sizeBytes: number;
   modified?: number;
+  uncompressedBytes?: number;
 }
 
 export interface ScanSummary {
@@ -45,7 +49,7 @@ export interface FlatNode {
 
 export type ScanPriorityMode = "performance" | "balanced" | "low";
 
-export type ScanThrottleLevel = "off" | "low" | "medium" | "high";
+export type ScanThrottleLevel = "off" | "low" | "medium" | "high" | "adaptive";
 
 export interface ScanFilters {
   includeExtensions: string[];
@@ -66,4 +70,14 @@ export interface ScanOptions {
   priorityMode: ScanPriorityMode;
   throttleLevel: ScanThrottleLevel;
   filters: ScanFilters;
+  scanArchives?: boolean;
+  maxDepth?: number | null;
+  detailDepth?: number | null;
+  adaptiveThrottle?: AdaptiveThrottleOptions | null;
+}
+
+export interface AdaptiveThrottleOptions {
+  targetLatencyMs?: number | null;
+  maxIoPressure?: number | null;
+  maxSleepMs?: number | null;
 }
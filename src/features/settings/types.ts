@@ -1,8 +1,62 @@
+export type RemoteScope =
+  | "list"
+  | "disk"
+  | "read"
+  | "scan"
+  | "write"
+  | "delete"
+  | "metrics"
+  | "shutdown";
+
+export interface ApiTokenSettings {
+  name: string;
+  token: string;
+  scopes: RemoteScope[];
+  roots?: string[];
+}
+
+export interface AlertRuleSettings {
+  name: string;
+  path: string;
+  minFreePercent?: number | null;
+  maxGrowthBytes?: number | null;
+}
+
+export interface RemoteHostProfile {
+  name: string;
+  host: string;
+  port: number;
+  tokenRef?: string | null;
+  tlsFingerprint?: string | null;
+}
+
 export interface AppSettings {
   localToken: string | null;
   tcpBind: string | null;
   headless: boolean | null;
   autoUpdate: boolean | null;
+  maxConcurrentScans?: number | null;
+  tlsCertPath?: string | null;
+  tlsKeyPath?: string | null;
+  tlsClientCaPath?: string | null;
+  apiTokens?: ApiTokenSettings[] | null;
+  allowedRoots?: string[] | null;
+  auditLogPath?: string | null;
+  remoteFileOps?: boolean | null;
+  allowLegacyTokens?: boolean | null;
+  wsBind?: string | null;
+  wsAllowedOrigins?: string[] | null;
+  httpBind?: string | null;
+  socketPath?: string | null;
+  mdns?: boolean | null;
+  watchPaths?: string[] | null;
+  watchIntervalSecs?: number | null;
+  alertRules?: AlertRuleSettings[] | null;
+  alertWebhook?: string | null;
+  alertWebhookCaPath?: string | null;
+  alertCommand?: string | null;
+  alertIntervalSecs?: number | null;
+  remoteHosts?: RemoteHostProfile[] | null;
 }
 
 export interface AppSettingsUpdate {
@@ -10,6 +64,28 @@ export interface AppSettingsUpdate {
   tcpBind?: string | null;
   headless?: boolean | null;
   autoUpdate?: boolean | null;
+  maxConcurrentScans?: number | null;
+  tlsCertPath?: string | null;
+  tlsKeyPath?: string | null;
+  tlsClientCaPath?: string | null;
+  apiTokens?: ApiTokenSettings[] | null;
+  allowedRoots?: string[] | null;
+  auditLogPath?: string | null;
+  remoteFileOps?: boolean | null;
+  allowLegacyTokens?: boolean | null;
+  wsBind?: string | null;
+  wsAllowedOrigins?: string[] | null;
+  httpBind?: string | null;
+  socketPath?: string | null;
+  mdns?: boolean | null;
+  watchPaths?: string[] | null;
+  watchIntervalSecs?: number | null;
+  alertRules?: AlertRuleSettings[] | null;
+  alertWebhook?: string | null;
+  alertWebhookCaPath?: string | null;
+  alertCommand?: string | null;
+  alertIntervalSecs?: number | null;
+  remoteHosts?: RemoteHostProfile[] | null;
 }
 
 export interface TcpStatus {
@@ -17,7 +17,8 @@ interface UIState {
   showExplorerFiles: boolean;
   hideEmptyExplorerFolders: boolean;
   priorityMode: "performance" | "balanced" | "low";
-  throttleLevel: "off" | "low" | "medium" | "high";
+  throttleLevel: "off" | "low" | "medium" | "high" | "adaptive";
+  scanArchives: boolean;
   filterMode: "simple" | "advanced";
   simpleFilterIds: string[];
   includeExtensionsInput: string;
@@ -33,7 +34,10 @@ interface UIState {
   includeRegexInput: string;
   excludeRegexInput: string;
   setPriorityMode: (value: "performance" | "balanced" | "low") => void;
-  setThrottleLevel: (value: "off" | "low" | "medium" | "high") => void;
+  setThrottleLevel: (
+    value: "off" | "low" | "medium" | "high" | "adaptive",
+  ) => void;
+  setScanArchives: (value: boolean) => void;
   setFilterMode: (value: "simple" | "advanced") => void;
   setSimpleFilterIds: (value: string[]) => void;
   setIncludeExtensionsInput: (value: string) => void;
@@ -143,6 +147,7 @@ export const useUIStore = create<UIState>()(
       hideEmptyExplorerFolders: false,
       priorityMode: "balanced",
       throttleLevel: "off",
+      scanArchives: false,
       filterMode: defaultFilterState.filterMode,
       simpleFilterIds: [...defaultFilterState.simpleFilterIds],
       includeExtensionsInput: defaultFilterState.includeExtensionsInput,
@@ -163,6 +168,9 @@ export const useUIStore = create<UIState>()(
       setThrottleLevel: (value): void => {
         void set({ throttleLevel: value });
       },
+      setScanArchives: (value): void => {
+        void set({ scanArchives: value });
+      },
       setFilterMode: (value): void => {
         void set({ filterMode: value });
       },